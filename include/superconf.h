@@ -0,0 +1,50 @@
+/* C interface to superconf, built with the `ffi` feature */
+
+#ifndef SUPERCONF_H
+#define SUPERCONF_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct SuperConf SuperConf;
+
+typedef enum {
+    SUPERCONF_OK = 0,
+    SUPERCONF_NULL_POINTER = 1,
+    SUPERCONF_INVALID_UTF8 = 2,
+    SUPERCONF_UNBALANCED = 3,
+    SUPERCONF_INVALID_PATH = 4,
+    SUPERCONF_NOT_FOUND = 5,
+    SUPERCONF_WRONG_TYPE = 6,
+    SUPERCONF_BUFFER_TOO_SMALL = 7,
+    SUPERCONF_INVALID = 8,
+} SuperConfStatus;
+
+/* Parses `len` bytes of `text`, which doesn't need to end in a nul */
+SuperConfStatus superconf_parse(const char *text, size_t len, SuperConf **out);
+
+/* Frees a config from superconf_parse, doing nothing if it's null */
+void superconf_free(SuperConf *conf);
+
+SuperConfStatus superconf_get_int(const SuperConf *conf, const char *path, int64_t *out);
+
+/* Integers are converted to floats too */
+SuperConfStatus superconf_get_float(const SuperConf *conf, const char *path, double *out);
+
+SuperConfStatus superconf_get_bool(const SuperConf *conf, const char *path, bool *out);
+
+/* Copies a name into `buf` with a nul on the end, where `len` starts as the
+ * size of `buf` and is set to the length of the name without the nul, even if
+ * SUPERCONF_BUFFER_TOO_SMALL is given */
+SuperConfStatus superconf_get_string(const SuperConf *conf, const char *path, char *buf, size_t *len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,237 @@
+//! Parsing into a [Bump] arena with [ArenaConf::parse_in], behind the
+//! `bumpalo` feature, so that a whole config is freed at once by resetting
+//! or dropping the arena instead of node by node
+
+use super::reader::{Event, Raw, Reader};
+use super::{parse_float, Parse, Path, Segment, SuperConf, SuperError, SuperItem, SuperValue};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use bumpalo::collections::{String as BumpString, Vec as BumpVec};
+use bumpalo::Bump;
+
+/// Config whose items, lists, groups and unescaped text all live in one
+/// [Bump] arena, borrowing from it and the text it was parsed from
+///
+/// Brackets can only be nested [MAX_DEPTH](crate::reader::MAX_DEPTH) deep
+/// as it's parsed with a [Reader].
+///
+/// ```
+/// use bumpalo::Bump;
+/// use superconf::{ArenaConf, ArenaValue};
+///
+/// let mut bump = Bump::new();
+/// for _ in 0..3 {
+///     let conf = ArenaConf::parse_in("port 8080\nhosts [a, b]", &bump).unwrap();
+///     assert_eq!(conf.get("hosts[1]"), Some(&ArenaValue::Name("b")));
+///     bump.reset();
+/// }
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ArenaConf<'b> {
+    pub items: &'b [ArenaItem<'b>],
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ArenaItem<'b> {
+    pub key: &'b str,
+    pub value: ArenaValue<'b>,
+}
+
+/// Arena counterpart to [SuperValue]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ArenaValue<'b> {
+    Nothing,
+    Name(&'b str),
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    List(&'b [ArenaValue<'b>]),
+    Group(&'b [ArenaItem<'b>]),
+}
+
+impl<'b> ArenaValue<'b> {
+    /// Copies into a normal value, still borrowing any text
+    pub fn to_value(self) -> SuperValue<'b> {
+        match self {
+            Self::Nothing => SuperValue::Nothing,
+            Self::Name(name) => SuperValue::Name(Cow::Borrowed(name)),
+            Self::Bool(boolean) => SuperValue::Bool(boolean),
+            Self::Integer(integer) => SuperValue::Integer(integer),
+            Self::Float(float) => SuperValue::Float(float),
+            Self::List(values) => SuperValue::List(Box::new(
+                values.iter().map(|value| value.to_value()).collect(),
+            )),
+            Self::Group(items) => {
+                SuperValue::Group(Box::new(items.iter().map(|item| item.to_item()).collect()))
+            }
+        }
+    }
+}
+
+impl<'b> ArenaItem<'b> {
+    /// Copies into a normal item, still borrowing any text
+    pub fn to_item(self) -> SuperItem<'b> {
+        SuperItem {
+            key: Cow::Borrowed(self.key),
+            value: self.value.to_value(),
+            meta: None,
+        }
+    }
+}
+
+/// List or group which is still being parsed, alongside the key of the item
+/// which it's the value of
+enum Frame<'b> {
+    List(BumpVec<'b, ArenaValue<'b>>, Option<&'b str>),
+    Group(BumpVec<'b, ArenaItem<'b>>, Option<&'b str>),
+}
+
+impl<'b> ArenaConf<'b> {
+    /// Parses a config the same as [SuperConf::parse] would, allocating
+    /// everything inside of `bump`
+    pub fn parse_in(input: &'b str, bump: &'b Bump) -> Result<Self, SuperError> {
+        let mut items = BumpVec::new_in(bump);
+        let mut stack: Vec<Frame<'b>> = Vec::new();
+        let mut key = None;
+
+        for event in Reader::new(input) {
+            let value = match event? {
+                Event::Key(raw) => {
+                    key = Some(unescape_in(raw, bump));
+                    continue;
+                }
+                Event::Scalar(raw) => scalar_in(raw, bump),
+                Event::ListStart => {
+                    stack.push(Frame::List(BumpVec::new_in(bump), key.take()));
+                    continue;
+                }
+                Event::GroupStart => {
+                    stack.push(Frame::Group(BumpVec::new_in(bump), key.take()));
+                    continue;
+                }
+                Event::ListEnd | Event::GroupEnd => match stack.pop() {
+                    Some(Frame::List(values, found)) => {
+                        key = found;
+                        ArenaValue::List(values.into_bump_slice())
+                    }
+                    Some(Frame::Group(found_items, found)) => {
+                        key = found;
+                        ArenaValue::Group(found_items.into_bump_slice())
+                    }
+                    None => unreachable!(),
+                },
+            };
+
+            // every value which isn't a list element comes straight after
+            // the key of its item
+            match stack.last_mut() {
+                Some(Frame::List(values, _)) => values.push(value),
+                Some(Frame::Group(items, _)) => items.push(ArenaItem {
+                    key: key.take().unwrap_or_default(),
+                    value,
+                }),
+                None => items.push(ArenaItem {
+                    key: key.take().unwrap_or_default(),
+                    value,
+                }),
+            }
+        }
+
+        Ok(Self {
+            items: items.into_bump_slice(),
+        })
+    }
+
+    /// Gets the value at a path such as `server.hosts[2].port`, see
+    /// [SuperConf::get]
+    pub fn get(&self, path: &str) -> Option<&'b ArenaValue<'b>> {
+        let path = Path::parse(path).ok()?;
+        let (first, rest) = path.segments.split_first()?;
+        let mut value = match first {
+            Segment::Key(key) => find(self.items, key)?,
+            Segment::Index(_) => return None,
+        };
+
+        for segment in rest {
+            value = match (segment, value) {
+                (Segment::Key(key), ArenaValue::Group(items)) => find(items, key)?,
+                (Segment::Index(index), ArenaValue::List(values)) => values.get(*index)?,
+                _ => return None,
+            };
+        }
+
+        Some(value)
+    }
+
+    /// Copies into a normal config, still borrowing any text
+    pub fn to_conf(&self) -> SuperConf<'b> {
+        SuperConf {
+            items: self.items.iter().map(|item| item.to_item()).collect(),
+        }
+    }
+}
+
+fn find<'b>(items: &'b [ArenaItem<'b>], key: &str) -> Option<&'b ArenaValue<'b>> {
+    items
+        .iter()
+        .find(|item| item.key == key)
+        .map(|item| &item.value)
+}
+
+/// Removes escapes inside of the arena, only using it if there are any
+fn unescape_in<'b>(raw: Raw<'b>, bump: &'b Bump) -> &'b str {
+    match raw.is_escaped() {
+        true => BumpString::from_iter_in(raw.chars(), bump).into_bump_str(),
+        false => raw.as_str(),
+    }
+}
+
+/// Reads a scalar the same as [SuperValue::parse] would
+fn scalar_in<'b>(raw: Raw<'b>, bump: &'b Bump) -> ArenaValue<'b> {
+    let text = raw.as_str();
+
+    match text {
+        "" => ArenaValue::Nothing,
+        "true" => ArenaValue::Bool(true),
+        "false" => ArenaValue::Bool(false),
+        _ => match (text.parse(), parse_float(text)) {
+            (Ok(found), _) => ArenaValue::Integer(found),
+            (_, Some(found)) => ArenaValue::Float(found),
+            _ => ArenaValue::Name(unescape_in(raw, bump)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_as_tree() {
+        const TEXT: &str =
+            "a [1] x\nb {c [1, {d e\\ f}], g 2.5}\nempty\nh\\ i true\nj [[], [1,\n 2]]";
+        let bump = Bump::new();
+        let conf = ArenaConf::parse_in(TEXT, &bump).unwrap();
+
+        assert_eq!(conf.to_conf(), SuperConf::parse(TEXT).unwrap());
+        assert_eq!(conf.get("b.c[1].d"), Some(&ArenaValue::Name("e f")));
+        assert_eq!(conf.get("h i"), Some(&ArenaValue::Bool(true)));
+        assert_eq!(conf.get("j[1][1]"), Some(&ArenaValue::Integer(2)));
+        assert_eq!(conf.get("b.x"), None);
+    }
+
+    #[test]
+    fn errors() {
+        let bump = Bump::new();
+
+        assert_eq!(
+            ArenaConf::parse_in("a [1", &bump),
+            Err(SuperError::Unbalanced)
+        );
+        assert_eq!(
+            ArenaConf::parse_in(&["a ", &"[".repeat(200)].concat(), &bump),
+            Err(SuperError::TooDeep)
+        );
+    }
+}
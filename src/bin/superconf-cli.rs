@@ -0,0 +1,229 @@
+//! Command line tool for checking, formatting, reading and converting
+//! superconf files from scripts and CI, built with the `cli` feature
+//!
+//! Every command reads from standard input when it isn't given a file or is
+//! given `-`.
+
+use std::io::{self, Read};
+use std::process::ExitCode;
+use std::{env, fs};
+use superconf::diagnostics::Severity;
+use superconf::{fmt, lint, Emit, FmtOptions, Parse, SuperConf};
+
+const USAGE: &str = "usage:
+    superconf-cli check [file]
+    superconf-cli fmt [--check | --write] [file]
+    superconf-cli get <path> [file]
+    superconf-cli convert --to json [file]";
+
+/// What a command gives, where a failure is still printed but makes the
+/// tool exit with an error
+struct Output {
+    text: String,
+    failed: bool,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match run(&args, read) {
+        Ok(Output { text, failed }) => {
+            print!("{}", text);
+            match failed {
+                true => ExitCode::FAILURE,
+                false => ExitCode::SUCCESS,
+            }
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Reads the text of `file`, which is standard input for `-`
+fn read(file: &str) -> Result<String, String> {
+    let mut text = String::new();
+    let read = match file {
+        "-" => io::stdin().read_to_string(&mut text).map(|_| ()),
+        _ => fs::read_to_string(file).map(|read| text = read),
+    };
+    read.map_err(|err| format!("couldn't read {}: {}", file, err))?;
+    Ok(text)
+}
+
+/// Runs the command in `args`, reading files with `read`
+fn run(args: &[String], read: fn(&str) -> Result<String, String>) -> Result<Output, String> {
+    let mut args = args.iter().map(String::as_str);
+    let command = args.next().ok_or(USAGE)?;
+    let (flags, rest): (Vec<&str>, Vec<&str>) = args.partition(|arg| arg.starts_with("--"));
+    let mut rest = rest.into_iter();
+
+    match (command, flags.as_slice()) {
+        ("check", []) => {
+            let file = file(&mut rest)?;
+            let text = read(file)?;
+            let diagnostics = lint(&text);
+
+            let mut out = String::new();
+            for diagnostic in &diagnostics {
+                let line = text[..diagnostic.span.start].matches('\n').count() + 1;
+                out.push_str(&format!("{}:{}: {}\n", file, line, diagnostic));
+            }
+            Ok(Output {
+                text: out,
+                failed: diagnostics
+                    .iter()
+                    .any(|diagnostic| diagnostic.severity == Severity::Error),
+            })
+        }
+        ("fmt", [] | ["--check"] | ["--write"]) => {
+            let file = file(&mut rest)?;
+            let text = read(file)?;
+            let formatted =
+                fmt(&text, &FmtOptions::default()).map_err(|err| format!("{}: {}", file, err))?;
+
+            match flags.first() {
+                Some(&"--check") if formatted != text => Ok(Output {
+                    text: format!("{} isn't formatted\n", file),
+                    failed: true,
+                }),
+                Some(&"--check") => Ok(Output {
+                    text: String::new(),
+                    failed: false,
+                }),
+                Some(_) if file == "-" => Err("can't write to standard input".into()),
+                Some(_) => {
+                    fs::write(file, formatted)
+                        .map_err(|err| format!("couldn't write {}: {}", file, err))?;
+                    Ok(Output {
+                        text: String::new(),
+                        failed: false,
+                    })
+                }
+                None => Ok(Output {
+                    text: formatted,
+                    failed: false,
+                }),
+            }
+        }
+        ("get", []) => {
+            let path = rest.next().ok_or(USAGE)?;
+            let file = file(&mut rest)?;
+            let text = read(file)?;
+            let conf = SuperConf::parse(&text).map_err(|err| format!("{}: {}", file, err))?;
+
+            match conf.get(path) {
+                Some(value) => Ok(Output {
+                    text: format!("{}\n", value.emit()),
+                    failed: false,
+                }),
+                None => Ok(Output {
+                    text: format!("{}: nothing at {}\n", file, path),
+                    failed: true,
+                }),
+            }
+        }
+        ("convert", ["--to"]) => {
+            let to = rest.next().ok_or(USAGE)?;
+            let file = file(&mut rest)?;
+            if to != "json" {
+                return Err(format!("can't convert to {}, only to json", to));
+            }
+            let text = read(file)?;
+            let conf = SuperConf::parse(&text).map_err(|err| format!("{}: {}", file, err))?;
+
+            Ok(Output {
+                text: format!(
+                    "{}\n",
+                    serde_json::to_string_pretty(&conf.to_json())
+                        .expect("json values always serialize")
+                ),
+                failed: false,
+            })
+        }
+        _ => Err(USAGE.into()),
+    }
+}
+
+/// Takes the file which is the last argument, if there is one
+fn file<'f>(rest: &mut impl Iterator<Item = &'f str>) -> Result<&'f str, String> {
+    match (rest.next(), rest.next()) {
+        (file, None) => Ok(file.unwrap_or("-")),
+        _ => Err(USAGE.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(file: &str) -> Result<String, String> {
+        match file {
+            "good.conf" => Ok("port 8080\ntls {enabled true, hosts [a, b]}\n".into()),
+            "bad.conf" => Ok("port 8080\ntls {enabled true\n".into()),
+            "messy.conf" => Ok("port   8080\ntls {enabled true}\n".into()),
+            _ => Err(format!("couldn't read {}", file)),
+        }
+    }
+
+    fn run(args: &str) -> Result<(String, bool), String> {
+        let args: Vec<String> = args.split_whitespace().map(String::from).collect();
+        super::run(&args, read).map(|output| (output.text, output.failed))
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(run("check good.conf"), Ok((String::new(), false)));
+        assert_eq!(
+            run("check bad.conf"),
+            Ok((
+                "bad.conf:2: error: tls: brackets are unbalanced\n".into(),
+                true
+            ))
+        );
+        assert_eq!(
+            run("fmt --check good.conf").map(|(_, failed)| failed),
+            Ok(false)
+        );
+        assert_eq!(
+            run("fmt --check messy.conf"),
+            Ok(("messy.conf isn't formatted\n".into(), true))
+        );
+        assert_eq!(run("get tls.hosts[1] good.conf"), Ok(("b\n".into(), false)));
+        assert_eq!(
+            run("get tls.x good.conf"),
+            Ok(("good.conf: nothing at tls.x\n".into(), true))
+        );
+        assert_eq!(
+            run("convert --to json good.conf").map(|(text, _)| text.contains("\"port\": 8080")),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        for args in [
+            "",
+            "help",
+            "check a b",
+            "fmt --check --write good.conf",
+            "get",
+            "convert good.conf",
+        ] {
+            assert_eq!(run(args), Err(USAGE.into()), "{}", args);
+        }
+        assert_eq!(
+            run("convert --to yaml good.conf"),
+            Err("can't convert to yaml, only to json".into())
+        );
+        assert_eq!(
+            run("get port nope.conf"),
+            Err("couldn't read nope.conf".into())
+        );
+        assert_eq!(
+            run("get port bad.conf"),
+            Err("bad.conf: brackets are unbalanced".into())
+        );
+    }
+}
@@ -0,0 +1,261 @@
+//! Compact binary encoding of parsed configs, which is much faster to decode
+//! than the text is to parse so it's suited to caches and sending configs
+//! between processes
+//!
+//! Everything starts with the [MAGIC] bytes, and then each value is a one
+//! byte tag followed by its data, using LEB128 varints for lengths and
+//! zigzagged integers, little-endian floats and length-prefixed strings.
+
+use super::{SuperConf, SuperError, SuperItem, SuperValue};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::str;
+
+/// Bytes at the start of every encoding, the last of which is its version
+pub const MAGIC: [u8; 3] = [b's', b'c', 1];
+
+/// The deepest that lists and groups can be nested whilst decoding, which
+/// stops malicious input from overflowing the stack
+const MAX_DEPTH: usize = 128;
+
+const NOTHING: u8 = 0;
+const NAME: u8 = 1;
+const FALSE: u8 = 2;
+const TRUE: u8 = 3;
+const INTEGER: u8 = 4;
+const FLOAT: u8 = 5;
+const LIST: u8 = 6;
+const GROUP: u8 = 7;
+const BYTES: u8 = 8;
+
+impl<'a> SuperConf<'a> {
+    /// Encodes into the compact binary format, see [SuperConf::from_bytes]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::from(MAGIC);
+        encode_items(&mut out, &self.items);
+        out
+    }
+
+    /// Decodes bytes made by [SuperConf::to_bytes], borrowing all keys and
+    /// names straight from `bytes`
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, SuperError> {
+        let mut decoder = Decoder { bytes, pos: 0 };
+
+        if decoder.take(MAGIC.len())? != MAGIC {
+            return Err(SuperError::InvalidBinary);
+        }
+
+        let items = decoder.items(0)?;
+
+        match decoder.pos == bytes.len() {
+            true => Ok(Self { items }),
+            false => Err(SuperError::InvalidBinary),
+        }
+    }
+}
+
+fn encode_items(out: &mut Vec<u8>, items: &[SuperItem]) {
+    encode_varint(out, items.len() as u64);
+
+    for item in items {
+        encode_str(out, &item.key);
+        encode_value(out, &item.value);
+    }
+}
+
+fn encode_value(out: &mut Vec<u8>, value: &SuperValue) {
+    match value {
+        SuperValue::Nothing => out.push(NOTHING),
+        SuperValue::Name(name) => {
+            out.push(NAME);
+            encode_str(out, name);
+        }
+        SuperValue::Bool(false) => out.push(FALSE),
+        SuperValue::Bool(true) => out.push(TRUE),
+        SuperValue::Integer(integer) => {
+            out.push(INTEGER);
+            encode_varint(out, ((integer << 1) ^ (integer >> 63)) as u64);
+        }
+        SuperValue::Float(float) => {
+            out.push(FLOAT);
+            out.extend_from_slice(&float.to_le_bytes());
+        }
+        SuperValue::Bytes(data) => {
+            out.push(BYTES);
+            encode_str(out, data);
+        }
+        SuperValue::List(values) => {
+            out.push(LIST);
+            encode_varint(out, values.len() as u64);
+            values.iter().for_each(|value| encode_value(out, value));
+        }
+        SuperValue::Group(items) => {
+            out.push(GROUP);
+            encode_items(out, items);
+        }
+    }
+}
+
+fn encode_str(out: &mut Vec<u8>, text: &str) {
+    encode_varint(out, text.len() as u64);
+    out.extend_from_slice(text.as_bytes());
+}
+
+fn encode_varint(out: &mut Vec<u8>, mut num: u64) {
+    while num >= 0x80 {
+        out.push(num as u8 | 0x80);
+        num >>= 7;
+    }
+    out.push(num as u8);
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SuperError> {
+        let end = self.pos.checked_add(len).ok_or(SuperError::InvalidBinary)?;
+        let taken = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or(SuperError::InvalidBinary)?;
+
+        self.pos = end;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, SuperError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, SuperError> {
+        let mut num = 0u64;
+
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            num |= u64::from(byte & 0x7f) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(num);
+            }
+        }
+
+        Err(SuperError::InvalidBinary)
+    }
+
+    /// Reads a length, making sure it isn't longer than the remaining bytes
+    /// so that a corrupted length can't cause a huge allocation
+    fn len(&mut self) -> Result<usize, SuperError> {
+        match usize::try_from(self.varint()?) {
+            Ok(len) if len <= self.bytes.len() - self.pos => Ok(len),
+            _ => Err(SuperError::InvalidBinary),
+        }
+    }
+
+    fn str(&mut self) -> Result<&'a str, SuperError> {
+        let len = self.len()?;
+        str::from_utf8(self.take(len)?).map_err(|_| SuperError::InvalidBinary)
+    }
+
+    fn items(&mut self, depth: usize) -> Result<Vec<SuperItem<'a>>, SuperError> {
+        let len = self.len()?;
+        let mut items = Vec::with_capacity(len);
+
+        for _ in 0..len {
+            items.push(SuperItem {
+                key: Cow::Borrowed(self.str()?),
+                value: self.value(depth)?,
+                meta: None,
+            });
+        }
+
+        Ok(items)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<SuperValue<'a>, SuperError> {
+        if depth > MAX_DEPTH {
+            return Err(SuperError::InvalidBinary);
+        }
+
+        match self.byte()? {
+            NOTHING => Ok(SuperValue::Nothing),
+            NAME => Ok(SuperValue::Name(Cow::Borrowed(self.str()?))),
+            BYTES => Ok(SuperValue::Bytes(Box::new(Cow::Borrowed(self.str()?)))),
+            FALSE => Ok(SuperValue::Bool(false)),
+            TRUE => Ok(SuperValue::Bool(true)),
+            INTEGER => {
+                let zigzag = self.varint()?;
+                Ok(SuperValue::Integer(
+                    (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64),
+                ))
+            }
+            FLOAT => {
+                let mut bytes = [0; 8];
+                bytes.copy_from_slice(self.take(8)?);
+                Ok(SuperValue::Float(f64::from_le_bytes(bytes)))
+            }
+            LIST => {
+                let len = self.len()?;
+                let mut values = Vec::with_capacity(len);
+
+                for _ in 0..len {
+                    values.push(self.value(depth + 1)?);
+                }
+
+                Ok(SuperValue::list(values))
+            }
+            GROUP => Ok(SuperValue::group(self.items(depth + 1)?)),
+            _ => Err(SuperError::InvalidBinary),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+
+    #[test]
+    fn round_trip() {
+        let conf = SuperConf::parse(
+            "port 8080\nneg -9223372036854775808\nratio 1.5\nkey b64\"AQI=\"\nname hello there\nempty\ntls {enabled true, hosts [a, [], {x false}]}",
+        )
+        .unwrap();
+        let bytes = conf.to_bytes();
+
+        assert_eq!(&bytes[..3], &MAGIC);
+        assert_eq!(SuperConf::from_bytes(&bytes).unwrap(), conf);
+    }
+
+    #[test]
+    fn invalid() {
+        let bytes = SuperConf::parse("a [1, 2]\nb x").unwrap().to_bytes();
+
+        for len in 0..bytes.len() {
+            assert_eq!(
+                SuperConf::from_bytes(&bytes[..len]),
+                Err(SuperError::InvalidBinary)
+            );
+        }
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            SuperConf::from_bytes(&trailing),
+            Err(SuperError::InvalidBinary)
+        );
+
+        let mut huge = Vec::from(MAGIC);
+        huge.extend_from_slice(&[0xff, 0xff, 0xff, 0xff, 0x0f]);
+        assert_eq!(SuperConf::from_bytes(&huge), Err(SuperError::InvalidBinary));
+
+        let mut deep = Vec::from(MAGIC);
+        deep.extend_from_slice(&[1, 1, b'k']);
+        deep.extend([LIST, 1].repeat(MAX_DEPTH + 2));
+        assert_eq!(SuperConf::from_bytes(&deep), Err(SuperError::InvalidBinary));
+    }
+}
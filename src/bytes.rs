@@ -0,0 +1,143 @@
+//! Binary data written as base64 like `b64"3q2+7w=="`, for embedding small
+//! payloads such as keys or calibration tables straight into a config
+
+use super::convert::ConvertError;
+use super::SuperValue;
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Characters of standard base64, in the order of the values they stand for
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+impl SuperValue<'_> {
+    /// Encodes binary data into a [SuperValue::Bytes]
+    pub fn encode_bytes(data: &[u8]) -> SuperValue<'static> {
+        let mut text = String::with_capacity(data.len().div_ceil(3) * 4);
+        for chunk in data.chunks(3) {
+            let bits = chunk.iter().enumerate().fold(0u32, |bits, (ind, byte)| {
+                bits | ((*byte as u32) << (16 - ind * 8))
+            });
+            for ind in 0..4 {
+                text.push(match ind <= chunk.len() {
+                    true => ALPHABET[(bits >> (18 - ind * 6)) as usize & 63] as char,
+                    false => '=',
+                });
+            }
+        }
+        SuperValue::Bytes(Box::new(Cow::Owned(text)))
+    }
+
+    /// Decodes the data of a [SuperValue::Bytes], which is only done when
+    /// it's asked for
+    ///
+    /// ```
+    /// use superconf::{Parse, SuperConf};
+    ///
+    /// let conf = SuperConf::parse("key b64\"3q2+7w==\"").unwrap();
+    ///
+    /// assert_eq!(conf.get("key").unwrap().decode_bytes(), Ok(vec![0xde, 0xad, 0xbe, 0xef]));
+    /// ```
+    pub fn decode_bytes(&self) -> Result<Vec<u8>, ConvertError> {
+        match self {
+            Self::Bytes(text) => decode(text).ok_or(ConvertError::expected("base64 data")),
+            _ => Err(ConvertError::expected("bytes")),
+        }
+    }
+}
+
+/// Finds the base64 text of a value written like `b64"3q2+7w=="`, which
+/// has to be valid to count
+pub(crate) fn literal(text: &str) -> Option<&str> {
+    let text = text.strip_prefix("b64\"")?.strip_suffix('"')?;
+    is_base64(text).then_some(text)
+}
+
+fn is_base64(text: &str) -> bool {
+    let data = text.trim_end_matches('=');
+    text.len().is_multiple_of(4)
+        && text.len() - data.len() <= 2
+        && data.bytes().all(|byte| ALPHABET.contains(&byte))
+}
+
+fn decode(text: &str) -> Option<Vec<u8>> {
+    if !is_base64(text) {
+        return None;
+    }
+
+    let data = text.trim_end_matches('=').as_bytes();
+    let mut decoded = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.chunks(4) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (ind, byte)| {
+            let value = ALPHABET.iter().position(|found| found == byte).unwrap_or(0);
+            bits | ((value as u32) << (18 - ind * 6))
+        });
+        for ind in 0..chunk.len() - 1 {
+            decoded.push((bits >> (16 - ind * 8)) as u8);
+        }
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Emit, Parse, SuperConf};
+
+    #[test]
+    fn round_trip() {
+        for data in [
+            &b""[..],
+            b"f",
+            b"fo",
+            b"foo",
+            b"foob",
+            b"fooba",
+            b"foobar",
+            &[0, 255, 128],
+        ] {
+            let value = SuperValue::encode_bytes(data);
+            assert_eq!(value.decode_bytes().as_deref(), Ok(data));
+
+            let conf = SuperConf::parse(
+                &SuperConf {
+                    items: alloc::vec![crate::SuperItem {
+                        key: "a".into(),
+                        value: value.clone(),
+                        meta: None,
+                    }],
+                }
+                .emit(),
+            )
+            .unwrap()
+            .into_owned();
+            assert_eq!(conf.get("a"), Some(&value));
+        }
+        assert_eq!(
+            SuperValue::encode_bytes(b"foobar"),
+            SuperValue::Bytes(Box::new("Zm9vYmFy".into()))
+        );
+    }
+
+    #[test]
+    fn literals() {
+        let conf =
+            SuperConf::parse("a b64\"Zm8=\"\nb b64\"Zm8\"\nc [b64\"\"]\nd b64\"Zm8=\" x").unwrap();
+
+        assert_eq!(
+            conf.get("a"),
+            Some(&SuperValue::Bytes(Box::new("Zm8=".into())))
+        );
+        assert_eq!(conf.get("b"), Some(&SuperValue::Name("b64\"Zm8\"".into())));
+        assert_eq!(conf.get("c[0]").unwrap().decode_bytes(), Ok(Vec::new()));
+        assert_eq!(
+            conf.get("d"),
+            Some(&SuperValue::Name("b64\"Zm8=\" x".into()))
+        );
+        assert_eq!(
+            SuperValue::Name("Zm8=".into()).decode_bytes(),
+            Err(ConvertError::expected("bytes"))
+        );
+    }
+}
@@ -0,0 +1,221 @@
+//! Conversion of keys between kebab-case, snake_case and camelCase with
+//! [Case], so keys copied from documentation written in any of them are
+//! found all the same
+
+use super::{Parse, Path, Segment, SuperConf, SuperItem, SuperValue};
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Style which keys can be written in
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Case {
+    /// `max-connections`
+    Kebab,
+    /// `max_connections`
+    Snake,
+    /// `maxConnections`
+    Camel,
+}
+
+impl Case {
+    /// Writes a key in this case, splitting it into words at dashes,
+    /// underscores, whitespace and where its letters change case
+    ///
+    /// ```
+    /// use superconf::Case;
+    ///
+    /// assert_eq!(Case::Snake.apply("maxConnections"), "max_connections");
+    /// assert_eq!(Case::Kebab.apply("HTTPServer"), "http-server");
+    /// assert_eq!(Case::Camel.apply("retry-after_ms"), "retryAfterMs");
+    /// ```
+    pub fn apply(self, key: &str) -> String {
+        let mut applied = String::with_capacity(key.len());
+        for (ind, word) in words(key).into_iter().enumerate() {
+            match self {
+                Self::Kebab | Self::Snake if ind != 0 => {
+                    applied.push(if self == Self::Kebab { '-' } else { '_' })
+                }
+                Self::Camel if ind != 0 => {
+                    let mut chars = word.chars();
+                    applied.extend(chars.next().into_iter().flat_map(char::to_uppercase));
+                    applied.extend(chars.flat_map(char::to_lowercase));
+                    continue;
+                }
+                _ => (),
+            }
+            applied.extend(word.chars().flat_map(char::to_lowercase));
+        }
+        applied
+    }
+}
+
+impl<'a> SuperConf<'a> {
+    /// Rewrites every key in `case`, including the keys inside of groups and
+    /// lists
+    pub fn normalize_keys(&mut self, case: Case) {
+        normalize_items(&mut self.items, case);
+    }
+
+    /// Gets the value at a path like [SuperConf::get], where each key in the
+    /// path matches keys which are written in any [Case]
+    ///
+    /// ```
+    /// use superconf::{Parse, SuperConf, SuperValue};
+    ///
+    /// let conf = SuperConf::parse("server {max-connections 64}").unwrap();
+    ///
+    /// assert_eq!(conf.get_any_case("server.maxConnections"), Some(&SuperValue::Integer(64)));
+    /// assert_eq!(conf.get_any_case("server.max_connections"), Some(&SuperValue::Integer(64)));
+    /// ```
+    pub fn get_any_case(&self, path: &str) -> Option<&SuperValue<'a>> {
+        let path = Path::parse(path).ok()?;
+        let (first, rest) = path.segments.split_first()?;
+        let mut value = match first {
+            Segment::Key(key) => find_any_case(&self.items, key)?,
+            Segment::Index(_) => return None,
+        };
+
+        for segment in rest {
+            value = match (segment, value) {
+                (Segment::Key(key), SuperValue::Group(items)) => find_any_case(items, key)?,
+                (Segment::Index(index), SuperValue::List(values)) => values.get(*index)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+}
+
+fn normalize_items(items: &mut [SuperItem<'_>], case: Case) {
+    for item in items {
+        let key = case.apply(&item.key);
+        if key != item.key {
+            item.key = Cow::Owned(key);
+        }
+        normalize_value(&mut item.value, case);
+    }
+}
+
+fn normalize_value(value: &mut SuperValue<'_>, case: Case) {
+    match value {
+        SuperValue::Group(items) => normalize_items(items, case),
+        SuperValue::List(values) => {
+            for value in values.iter_mut() {
+                normalize_value(value, case);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Finds the value of the first item whose key is `key` in any case
+fn find_any_case<'b, 'a>(items: &'b [SuperItem<'a>], key: &str) -> Option<&'b SuperValue<'a>> {
+    let key = Case::Snake.apply(key);
+    items
+        .iter()
+        .find(|item| Case::Snake.apply(&item.key) == key)
+        .map(|item| &item.value)
+}
+
+/// Splits a key into its words, where an uppercase letter starts a new word
+/// unless it follows another one, so that `HTTPServer` is `HTTP` and `Server`
+fn words(key: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut chars = key.char_indices().peekable();
+    let mut prev: Option<char> = None;
+
+    while let Some((ind, c)) = chars.next() {
+        if c == '-' || c == '_' || c.is_whitespace() {
+            words.extend(start.take().map(|start| &key[start..ind]));
+            prev = None;
+            continue;
+        }
+
+        let next_lower = chars.peek().is_some_and(|(_, next)| next.is_lowercase());
+        let boundary = c.is_uppercase()
+            && prev.is_some_and(|prev| {
+                prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower)
+            });
+        if boundary {
+            words.extend(start.replace(ind).map(|start| &key[start..ind]));
+        }
+        start.get_or_insert(ind);
+        prev = Some(c);
+    }
+
+    words.extend(start.map(|start| &key[start..]));
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cases() {
+        for (key, kebab, snake, camel) in [
+            (
+                "maxConnections",
+                "max-connections",
+                "max_connections",
+                "maxConnections",
+            ),
+            (
+                "max-connections",
+                "max-connections",
+                "max_connections",
+                "maxConnections",
+            ),
+            (
+                "MAX_CONNECTIONS",
+                "max-connections",
+                "max_connections",
+                "maxConnections",
+            ),
+            ("HTTPServer", "http-server", "http_server", "httpServer"),
+            ("ipv4Address", "ipv4-address", "ipv4_address", "ipv4Address"),
+            (
+                "__private--key ",
+                "private-key",
+                "private_key",
+                "privateKey",
+            ),
+            ("port", "port", "port", "port"),
+            ("", "", "", ""),
+        ] {
+            assert_eq!(Case::Kebab.apply(key), kebab, "{}", key);
+            assert_eq!(Case::Snake.apply(key), snake, "{}", key);
+            assert_eq!(Case::Camel.apply(key), camel, "{}", key);
+        }
+    }
+
+    #[test]
+    fn normalized() {
+        let mut conf =
+            SuperConf::parse("maxConnections 1\nserver {tls-cert x, hosts [{HostName a}]}")
+                .unwrap();
+
+        assert_eq!(
+            conf.get_any_case("max-connections"),
+            Some(&SuperValue::Integer(1))
+        );
+        assert_eq!(
+            conf.get_any_case("server.tlsCert"),
+            Some(&SuperValue::Name("x".into()))
+        );
+        assert_eq!(
+            conf.get_any_case("server.hosts[0].host_name"),
+            Some(&SuperValue::Name("a".into()))
+        );
+        assert_eq!(conf.get_any_case("server.tls"), None);
+
+        conf.normalize_keys(Case::Snake);
+        assert_eq!(
+            conf,
+            SuperConf::parse("max_connections 1\nserver {tls_cert x, hosts [{host_name a}]}")
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! Parsing of text which arrives in pieces with [ChunkedParser] or as
+//! separate lines with [SuperConf::parse_lines], where only the item which
+//! has been started but not finished is held onto
+
+use super::cursor::{Cursor, Held};
+use super::{SuperConf, SuperError, SuperItem};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Parser which is fed a config a chunk at a time, such as from a serial
+/// link or a socket, giving the same config as
+/// [SuperConf::parse](crate::SuperConf::parse) would for all of the text
+///
+/// Chunks can be split anywhere, even inside of an item or an escape. Every
+/// line which ends outside of any brackets is parsed as soon as it's been
+/// fed, so only the text of an unfinished item is kept around, along with
+/// any documentation and attributes waiting for the item below them. Nothing
+/// fed after an error is given can be parsed properly.
+///
+/// ```
+/// use superconf::{ChunkedParser, SuperValue};
+///
+/// let mut parser = ChunkedParser::new();
+/// parser.feed("port 80\nhosts [a,").unwrap();
+/// assert_eq!(parser.take_items().len(), 1);
+///
+/// parser.feed(" b]\ntls").unwrap();
+/// let conf = parser.finish().unwrap();
+///
+/// assert_eq!(conf.get("hosts[1]"), Some(&SuperValue::Name("b".into())));
+/// assert_eq!(conf.get("tls"), Some(&SuperValue::Nothing));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ChunkedParser {
+    items: Vec<SuperItem<'static>>,
+    /// Text after the last line which ended outside of any brackets
+    pending: String,
+    /// Documentation and attributes from the lines which were parsed, which
+    /// aren't above any item yet
+    held: Held,
+    lines: LineEnds,
+}
+
+/// Finds the newlines which end items at the top level, a byte at a time so
+/// that text can be split anywhere
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct LineEnds {
+    depth: usize,
+    comment: bool,
+    escaped: bool,
+}
+
+impl LineEnds {
+    /// Moves past `byte`, saying if it's a newline outside of any brackets
+    pub(crate) fn is_end(&mut self, byte: u8) -> bool {
+        if self.escaped {
+            self.escaped = false;
+            return false;
+        }
+
+        match byte {
+            b'\n' => {
+                self.comment = false;
+                return self.depth == 0;
+            }
+            _ if self.comment => (),
+            b'#' => self.comment = true,
+            b'\\' => self.escaped = true,
+            b'[' | b'{' => self.depth += 1,
+            // a closer without an opener is left for the parser to find
+            b']' | b'}' => self.depth = self.depth.saturating_sub(1),
+            _ => (),
+        }
+
+        false
+    }
+}
+
+impl<'a> SuperConf<'a> {
+    /// Parses a config which is held as separate lines, such as in a rope or
+    /// a ring buffer, without joining all of them up into one string first
+    ///
+    /// Every line is taken to end in a newline which it doesn't have to
+    /// include. Items which are written on a single line borrow from it, and
+    /// only the lines of an item which spans more than one are copied.
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    /// use superconf::{SuperConf, SuperValue};
+    ///
+    /// let lines = ["port 80", "hosts [a,", "  b]"];
+    /// let conf = SuperConf::parse_lines(lines).unwrap();
+    ///
+    /// assert!(matches!(conf.items[0].key, Cow::Borrowed("port")));
+    /// assert_eq!(conf.get("hosts[1]"), Some(&SuperValue::Name("b".into())));
+    /// ```
+    pub fn parse_lines(lines: impl IntoIterator<Item = &'a str>) -> Result<Self, SuperError> {
+        let mut items = Vec::new();
+        let mut pending = String::new();
+        let mut held = Held::default();
+        let mut ends = LineEnds::default();
+
+        for line in lines {
+            // only the newline after the line can end an item, as any inside
+            // of it are left for the parser along with the rest of the line
+            for byte in line.bytes() {
+                ends.is_end(byte);
+            }
+            if !ends.is_end(b'\n') {
+                pending.push_str(line);
+                pending.push('\n');
+                continue;
+            }
+
+            match pending.is_empty() {
+                true => items.extend(Cursor::new(line).conf_piece(&mut held)?),
+                false => {
+                    pending.push_str(line);
+                    for item in Cursor::new(&pending).conf_piece(&mut held)? {
+                        items.push(item.into_owned());
+                    }
+                    pending.clear();
+                }
+            }
+        }
+
+        for item in Cursor::new(&pending).conf_piece(&mut held)? {
+            items.push(item.into_owned());
+        }
+        items.extend(held.finish());
+        Ok(Self { items })
+    }
+}
+
+impl ChunkedParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the next chunk of text, parsing any items which it finishes
+    pub fn feed(&mut self, chunk: &str) -> Result<(), SuperError> {
+        let Some(end) = self.last_line_end(chunk) else {
+            self.pending.push_str(chunk);
+            return Ok(());
+        };
+
+        let (lines, rest) = chunk.split_at(end);
+        let items = match self.pending.is_empty() {
+            true => Cursor::new(lines).conf_piece(&mut self.held),
+            false => {
+                self.pending.push_str(lines);
+                Cursor::new(&self.pending).conf_piece(&mut self.held)
+            }
+        }?;
+
+        self.items
+            .extend(items.into_iter().map(SuperItem::into_owned));
+        self.pending.clear();
+        self.pending.push_str(&rest[1..]);
+        Ok(())
+    }
+
+    /// Hands over the items which have been parsed so far, so that they don't
+    /// all have to be held onto until [ChunkedParser::finish]
+    pub fn take_items(&mut self) -> Vec<SuperItem<'static>> {
+        core::mem::take(&mut self.items)
+    }
+
+    /// Parses whatever is left once there are no chunks left to feed, giving
+    /// every item which hasn't been taken yet
+    pub fn finish(mut self) -> Result<SuperConf<'static>, SuperError> {
+        let items = Cursor::new(&self.pending).conf_piece(&mut self.held)?;
+        self.items
+            .extend(items.into_iter().map(SuperItem::into_owned));
+        self.items.extend(self.held.finish());
+
+        Ok(SuperConf { items: self.items })
+    }
+
+    /// Finds the last newline in `chunk` which is outside of any brackets,
+    /// keeping track of where the text is up to for the next chunk
+    fn last_line_end(&mut self, chunk: &str) -> Option<usize> {
+        let lines = &mut self.lines;
+        chunk
+            .bytes()
+            .enumerate()
+            .filter(|(_, byte)| lines.is_end(*byte))
+            .last()
+            .map(|(ind, _)| ind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+    use alloc::borrow::Cow;
+
+    const TEXT: &str = "## the port\n##\n@unit(port)\nport 80 # main [\n## not docs\n\nhosts [\n  a,\n  b\\\nc,\n]\n@secret\n## the tls\ntls {enabled true\n  ## certs\n  certs {}} \\# x\nname café\n@dangling";
+
+    #[test]
+    fn every_split() {
+        let expected = SuperConf::parse(TEXT).unwrap();
+        assert_eq!(expected.items[0].doc(), Some("the port\n"));
+        assert_eq!(expected.items[2].doc(), Some("the tls"));
+        assert!(expected.items[2].attr("secret").is_some());
+
+        for size in 1..TEXT.len() {
+            let mut parser = ChunkedParser::new();
+            let mut rest = TEXT;
+
+            while !rest.is_empty() {
+                let mut end = size.min(rest.len());
+                while !rest.is_char_boundary(end) {
+                    end += 1;
+                }
+                parser.feed(&rest[..end]).unwrap();
+                rest = &rest[end..];
+            }
+
+            assert_eq!(parser.finish().unwrap(), expected, "{}", size);
+        }
+    }
+
+    #[test]
+    fn take_items() {
+        let mut parser = ChunkedParser::new();
+
+        parser.feed("a 1\nb [").unwrap();
+        assert_eq!(parser.take_items(), SuperConf::parse("a 1").unwrap().items);
+        parser.feed("2\n]\n").unwrap();
+        assert_eq!(
+            parser.take_items(),
+            SuperConf::parse("b [2]").unwrap().items
+        );
+        assert_eq!(parser.finish().unwrap().items, []);
+
+        let mut parser = ChunkedParser::new();
+        assert_eq!(parser.feed("a [1}\n"), Err(SuperError::Unbalanced));
+
+        let mut parser = ChunkedParser::new();
+        parser.feed("a ]").unwrap();
+        assert_eq!(parser.finish(), Err(SuperError::Unbalanced));
+    }
+
+    #[test]
+    fn lines() {
+        let conf = SuperConf::parse_lines(TEXT.lines()).unwrap();
+        assert_eq!(conf, SuperConf::parse(TEXT).unwrap());
+        assert!(matches!(conf.items[0].key, Cow::Borrowed("port")));
+
+        let conf = SuperConf::parse_lines(["a 1\n", "b [\n", "c]\n"]).unwrap();
+        assert_eq!(conf, SuperConf::parse("a 1\nb [\nc]").unwrap());
+
+        assert_eq!(
+            SuperConf::parse_lines(["a [", "1"]),
+            Err(SuperError::Unbalanced)
+        );
+        assert_eq!(
+            SuperConf::parse_lines(["a 1", "b ]"]),
+            Err(SuperError::Unbalanced)
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! Reading of colors written in hex like `\#ff8800` or `color:ff8800cc`,
+//! where the `#` of an untagged color is escaped as it'd start a comment
+//! otherwise
+
+use super::convert::ConvertError;
+use super::SuperValue;
+
+impl SuperValue<'_> {
+    /// Reads a color as its red, green, blue and alpha parts, written as
+    /// `#rrggbb` or `#rrggbbaa` where a missing alpha is fully opaque
+    ///
+    /// Colors are either escaped names like `\#ff8800` or names tagged with
+    /// `color:`, where the `#` is optional, as a plain `#` starts a comment.
+    ///
+    /// ```
+    /// use superconf::{Parse, SuperConf};
+    ///
+    /// let conf = SuperConf::parse("accent \\#ff8800\nshadow color:00000080").unwrap();
+    ///
+    /// assert_eq!(conf.get("accent").unwrap().as_rgba(), Ok([0xff, 0x88, 0x00, 0xff]));
+    /// assert_eq!(conf.get("shadow").unwrap().as_rgba(), Ok([0x00, 0x00, 0x00, 0x80]));
+    /// ```
+    pub fn as_rgba(&self) -> Result<[u8; 4], ConvertError> {
+        let rgba = match self {
+            Self::Name(name) => match name.strip_prefix("color:") {
+                Some(tagged) => rgba(tagged),
+                None => name.strip_prefix('#').and_then(rgba),
+            },
+            _ => None,
+        };
+        rgba.ok_or_else(|| ConvertError::expected("a color"))
+    }
+}
+
+/// Reads the hex digits of a color, which can have a `#` before them
+pub(crate) fn rgba(digits: &str) -> Option<[u8; 4]> {
+    let digits = digits.strip_prefix('#').unwrap_or(digits);
+    if !matches!(digits.len(), 6 | 8) || !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let mut rgba = [0xff; 4];
+    for (part, pair) in rgba.iter_mut().zip(digits.as_bytes().chunks(2)) {
+        *part = u8::from_str_radix(core::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(rgba)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+
+    #[test]
+    fn colors() {
+        for (text, rgba) in [
+            ("\\#102030", Some([0x10, 0x20, 0x30, 0xff])),
+            ("\\#AbCdEf01", Some([0xab, 0xcd, 0xef, 0x01])),
+            ("color:\\#102030", Some([0x10, 0x20, 0x30, 0xff])),
+            ("color:10203040", Some([0x10, 0x20, 0x30, 0x40])),
+            ("102030", None),
+            ("\\#fff", None),
+            ("\\#10203", None),
+            ("\\#1020304g", None),
+            ("\\#+1020304", None),
+            ("color:", None),
+            ("x:#102030", None),
+        ] {
+            let value = SuperValue::parse(text).unwrap();
+            assert_eq!(
+                value.as_rgba(),
+                rgba.ok_or(ConvertError::expected("a color")),
+                "{}",
+                text
+            );
+        }
+        assert_eq!(
+            SuperValue::Integer(102030).as_rgba(),
+            Err(ConvertError::expected("a color"))
+        );
+    }
+}
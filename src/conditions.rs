@@ -0,0 +1,151 @@
+//! Conditional blocks written like `@if target=embedded {buffer 64}`, which
+//! are kept or dropped by [SuperConf::resolve_conditions] depending on the
+//! flags an application gives it
+
+use super::merge::merge_items;
+use super::{Group, Growable, Parse, SuperConf, SuperError, SuperItem, SuperValue};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+/// Key of a conditional block
+const IF_KEY: &str = "@if";
+
+impl SuperConf<'_> {
+    /// Merges the items of every conditional block whose condition holds for
+    /// `flags` into the group it's in, removing every conditional block
+    ///
+    /// Conditions are a single flag like `debug` or `target=embedded` which
+    /// holds if it's in `flags`, or the opposite with `!debug` or
+    /// `target!=embedded`. Blocks can be inside of any group or list and can
+    /// contain other blocks, and their items replace any which are already in
+    /// the group like with [SuperConf::merge].
+    ///
+    /// The items are read again from the text of the block, so any escapes
+    /// inside of it have to be escaped a second time.
+    ///
+    /// ```
+    /// use superconf::{Parse, SuperConf};
+    ///
+    /// let text = "buffer 1024\n@if target=embedded {buffer 64, heap false}\n@if !debug {log warn}";
+    /// let mut conf = SuperConf::parse(text).unwrap();
+    ///
+    /// conf.resolve_conditions(&["target=embedded"]).unwrap();
+    /// assert_eq!(conf, SuperConf::parse("buffer 64\nheap false\nlog warn").unwrap());
+    /// ```
+    pub fn resolve_conditions(&mut self, flags: &[&str]) -> Result<(), SuperError> {
+        resolve_items(&mut self.items, flags)
+    }
+}
+
+fn resolve_items<'a>(items: &mut impl Growable<'a>, flags: &[&str]) -> Result<(), SuperError> {
+    let mut blocks = Vec::new();
+    for item in core::mem::take(items) {
+        match item.key == IF_KEY {
+            true => blocks.extend(block(item.value, flags)?),
+            false => items.push(item),
+        }
+    }
+
+    for item in items.iter_mut() {
+        resolve_value(&mut item.value, flags)?;
+    }
+    for mut block in blocks {
+        resolve_items(&mut block, flags)?;
+        merge_items(items, block);
+    }
+    Ok(())
+}
+
+fn resolve_value(value: &mut SuperValue<'_>, flags: &[&str]) -> Result<(), SuperError> {
+    match value {
+        SuperValue::Group(items) => resolve_items(&mut **items, flags),
+        SuperValue::List(values) => values
+            .iter_mut()
+            .try_for_each(|value| resolve_value(value, flags)),
+        _ => Ok(()),
+    }
+}
+
+/// Reads the items of a conditional block, which are nothing if its
+/// condition doesn't hold
+fn block<'a>(value: SuperValue<'a>, flags: &[&str]) -> Result<Option<Group<'a>>, SuperError> {
+    match value {
+        SuperValue::Name(Cow::Borrowed(text)) => block_text(text, flags),
+        SuperValue::Name(Cow::Owned(text)) => Ok(block_text(&text, flags)?
+            .map(|items| items.into_iter().map(SuperItem::into_owned).collect())),
+        _ => Err(SuperError::InvalidCondition),
+    }
+}
+
+fn block_text<'a>(text: &'a str, flags: &[&str]) -> Result<Option<Group<'a>>, SuperError> {
+    let start = text.find('{').ok_or(SuperError::InvalidCondition)?;
+    let condition = text[..start].trim();
+    if condition.is_empty() || condition.contains(char::is_whitespace) {
+        return Err(SuperError::InvalidCondition);
+    }
+
+    let items = match SuperValue::parse(&text[start..]) {
+        Ok(SuperValue::Group(items)) => *items,
+        _ => return Err(SuperError::InvalidCondition),
+    };
+    Ok(holds(condition, flags).then_some(items))
+}
+
+fn holds(condition: &str, flags: &[&str]) -> bool {
+    if let Some(flag) = condition.strip_prefix('!') {
+        return !flags.contains(&flag);
+    }
+
+    match condition.split_once("!=") {
+        Some((key, value)) => !flags
+            .iter()
+            .any(|flag| flag.split_once('=') == Some((key, value))),
+        None => flags.contains(&condition),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(text: &str, flags: &[&str]) -> Result<SuperConf<'static>, SuperError> {
+        let mut conf = SuperConf::parse(text).unwrap().into_owned();
+        conf.resolve_conditions(flags).map(|_| conf)
+    }
+
+    #[test]
+    fn conditions() {
+        const TEXT: &str =
+            "a 1\n@if x {a 2, b 1}\nc {@if !x {d 1}, e [{@if os!=mac {f 1}}]}\n@if y {@if x {g 1}}";
+        let conf = |text| SuperConf::parse(text).unwrap();
+
+        assert_eq!(
+            resolve(TEXT, &["x", "os=linux"]),
+            Ok(conf("a 2\nc {e [{f 1}]}\nb 1"))
+        );
+        assert_eq!(resolve(TEXT, &["os=mac"]), Ok(conf("a 1\nc {e [{}], d 1}")));
+        assert_eq!(
+            resolve(TEXT, &["x", "y", "os=mac"]),
+            Ok(conf("a 2\nc {e [{}]}\nb 1\ng 1"))
+        );
+        assert_eq!(resolve("@if x {a b\\\\ c}", &["x"]), Ok(conf("a b\\ c")));
+    }
+
+    #[test]
+    fn invalid() {
+        for text in [
+            "@if x",
+            "@if {a 1}",
+            "@if a b {c 1}",
+            "@if x {a} y",
+            "@if [1]",
+        ] {
+            assert_eq!(
+                resolve(text, &[]),
+                Err(SuperError::InvalidCondition),
+                "{}",
+                text
+            );
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! Parsing of small, flat configs in const contexts with [parse_const], so a
+//! config embedded with `include_str!` can become a `static` without going
+//! through a proc-macro
+//!
+//! Only a subset of the format is supported: every item has to be a scalar
+//! which borrows straight from the input, so lists, groups, floats and
+//! anything escaped with a backslash are rejected. Only ASCII whitespace is
+//! trimmed, unlike the full parser.
+
+use super::SuperError;
+#[cfg(feature = "alloc")]
+use super::{SuperConf, SuperItem, SuperValue};
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
+use core::{fmt, str};
+
+/// Scalar value parsed by [parse_const], which converts into a [SuperValue]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConstValue<'a> {
+    Nothing,
+    Name(&'a str),
+    Bool(bool),
+    Integer(i64),
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> From<ConstValue<'a>> for SuperValue<'a> {
+    fn from(value: ConstValue<'a>) -> Self {
+        match value {
+            ConstValue::Nothing => SuperValue::Nothing,
+            ConstValue::Name(name) => SuperValue::Name(Cow::Borrowed(name)),
+            ConstValue::Bool(boolean) => SuperValue::Bool(boolean),
+            ConstValue::Integer(integer) => SuperValue::Integer(integer),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ConstItem<'a> {
+    pub key: &'a str,
+    pub value: ConstValue<'a>,
+}
+
+/// Config parsed by [parse_const], holding up to `N` items
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct ConstConf<'a, const N: usize> {
+    items: [ConstItem<'a>; N],
+    len: usize,
+}
+
+impl<'a, const N: usize> ConstConf<'a, N> {
+    /// The items which were parsed, in the order they were written
+    pub const fn items(&self) -> &[ConstItem<'a>] {
+        self.items.split_at(self.len).0
+    }
+
+    /// Gets the value of the first item with `key`, which is only ever a
+    /// single key rather than a [Path](crate::Path)
+    pub const fn get(&self, key: &str) -> Option<ConstValue<'a>> {
+        let mut ind = 0;
+
+        while ind < self.len {
+            if bytes_eq(self.items[ind].key.as_bytes(), key.as_bytes()) {
+                return Some(self.items[ind].value);
+            }
+            ind += 1;
+        }
+
+        None
+    }
+
+    /// Converts into a normal config, still borrowing from the input
+    #[cfg(feature = "alloc")]
+    pub fn to_conf(&self) -> SuperConf<'a> {
+        SuperConf {
+            items: self
+                .items()
+                .iter()
+                .map(|item| SuperItem {
+                    key: Cow::Borrowed(item.key),
+                    value: item.value.into(),
+                    meta: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<const N: usize> fmt::Debug for ConstConf<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConstConf")
+            .field("items", &self.items())
+            .finish()
+    }
+}
+
+/// Parses a config with at most `N` scalar items, which can be done at
+/// compile time
+///
+/// Anything outside of the supported subset gives [SuperError::NotConst] and
+/// having more than `N` items gives [SuperError::TooManyItems]. Unwrapping
+/// can't be done in a const context, so use a `match` instead:
+///
+/// ```
+/// use superconf::{parse_const, ConstConf, ConstValue};
+///
+/// static CONF: ConstConf<'static, 4> = match parse_const("port 8080\nhost example # main\n") {
+///     Ok(conf) => conf,
+///     Err(_) => panic!("invalid config"),
+/// };
+///
+/// assert_eq!(CONF.get("port"), Some(ConstValue::Integer(8080)));
+/// assert_eq!(CONF.get("host"), Some(ConstValue::Name("example")));
+/// ```
+pub const fn parse_const<const N: usize>(input: &str) -> Result<ConstConf<'_, N>, SuperError> {
+    let mut items = [ConstItem {
+        key: "",
+        value: ConstValue::Nothing,
+    }; N];
+    let mut len = 0;
+    let mut rest = input.as_bytes();
+
+    while !rest.is_empty() {
+        // errors are given at the start of the line they're on
+        let start = input.len() - rest.len();
+        let line = match split_once(rest, b'\n') {
+            Some((line, next)) => {
+                rest = next;
+                line
+            }
+            None => {
+                let line = rest;
+                rest = &[];
+                line
+            }
+        };
+        let line = match split_once(line, b'#') {
+            Some((content, _)) => content,
+            None => line,
+        }
+        .trim_ascii();
+
+        if line.is_empty() {
+            continue;
+        } else if len == N {
+            return Err(SuperError::TooManyItems.at(start));
+        }
+
+        items[len] = match item(line) {
+            Ok(item) => item,
+            Err(err) => return Err(err.at(start)),
+        };
+        len += 1;
+    }
+
+    Ok(ConstConf { items, len })
+}
+
+const fn item(line: &[u8]) -> Result<ConstItem<'_>, SuperError> {
+    let mut ind = 0;
+    while ind < line.len() {
+        if matches!(line[ind], b'\\' | b'[' | b']' | b'{' | b'}') {
+            return Err(SuperError::NotConst);
+        }
+        ind += 1;
+    }
+
+    let (key, value) = match split_once(line, b' ') {
+        Some((key, value)) => (key, value.trim_ascii()),
+        None => (line, &[] as &[u8]),
+    };
+    let value = match value {
+        b"" => ConstValue::Nothing,
+        b"true" => ConstValue::Bool(true),
+        b"false" => ConstValue::Bool(false),
+        _ => match i64::from_str_radix(text(value), 10) {
+            Ok(integer) => ConstValue::Integer(integer),
+            Err(_) if looks_float(value) => return Err(SuperError::NotConst),
+            Err(_) => ConstValue::Name(text(value)),
+        },
+    };
+
+    Ok(ConstItem {
+        key: text(key),
+        value,
+    })
+}
+
+/// Checks if the full parser might read `value` as a float, with the same
+/// rules as it uses
+const fn looks_float(value: &[u8]) -> bool {
+    if matches!(value, b".inf" | b"+.inf" | b"-.inf" | b".nan") {
+        return true;
+    }
+
+    let mut digit = false;
+    let mut ind = 0;
+    while ind < value.len() {
+        match value[ind] {
+            b'0'..=b'9' => digit = true,
+            b'+' | b'-' | b'.' | b'e' | b'E' => (),
+            _ => return false,
+        }
+        ind += 1;
+    }
+
+    digit
+}
+
+/// Converts back into text, which can't fail as `bytes` is only ever split
+/// from a [str] on ASCII characters
+const fn text(bytes: &[u8]) -> &str {
+    match str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(_) => unreachable!(),
+    }
+}
+
+const fn split_once(bytes: &[u8], sep: u8) -> Option<(&[u8], &[u8])> {
+    let mut ind = 0;
+
+    while ind < bytes.len() {
+        if bytes[ind] == sep {
+            let (before, after) = bytes.split_at(ind);
+            return Some((before, after.split_at(1).1));
+        }
+        ind += 1;
+    }
+
+    None
+}
+
+const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut ind = 0;
+    while ind < a.len() {
+        if a[ind] != b[ind] {
+            return false;
+        }
+        ind += 1;
+    }
+
+    true
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::Parse;
+
+    const TEXT: &str =
+        "# defaults\nport 8080\nname very verbose\n\nenabled false # off\nbackup\noffset -3\n";
+    const CONF: ConstConf<'static, 8> = match parse_const(TEXT) {
+        Ok(conf) => conf,
+        Err(_) => panic!(),
+    };
+
+    #[test]
+    fn matches_parse() {
+        assert_eq!(CONF.items().len(), 5);
+        assert_eq!(CONF.get("name"), Some(ConstValue::Name("very verbose")));
+        assert_eq!(CONF.get("missing"), None);
+        assert_eq!(CONF.to_conf(), SuperConf::parse(TEXT).unwrap());
+    }
+
+    #[test]
+    fn unsupported() {
+        assert_eq!(
+            parse_const::<1>("a 1\nb 2").unwrap_err(),
+            SuperError::TooManyItems
+        );
+
+        for text in [
+            "list [1]",
+            "float 1.5",
+            "odd 1-2",
+            "special .inf",
+            "a\\ b c",
+        ] {
+            assert_eq!(parse_const::<1>(text).unwrap_err(), SuperError::NotConst);
+        }
+    }
+}
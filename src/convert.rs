@@ -0,0 +1,527 @@
+//! Conversions between values and plain Rust types without needing serde,
+//! which `#[derive(SuperConfig)]` from the `derive` feature builds upon
+
+use super::{Path, Segment, SuperConf, SuperItem, SuperValue};
+use alloc::string::String;
+use core::convert::TryFrom;
+use core::fmt;
+
+// used by the code which `#[derive(SuperConfig)]` generates
+#[doc(hidden)]
+pub use alloc::{borrow::Cow, boxed::Box, vec, vec::Vec};
+
+/// Types which can be made from a [SuperValue], borrowing from its text
+/// where possible
+pub trait FromSuperValue<'a>: Sized {
+    fn from_value(value: &SuperValue<'a>) -> Result<Self, ConvertError>;
+
+    /// Used when an item's key is missing, which is an error unless this is
+    /// overridden like [Option] does
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+/// Types which can be made into a [SuperValue], borrowing from themselves
+/// where possible
+pub trait ToSuperValue {
+    fn to_value(&self) -> SuperValue<'_>;
+}
+
+/// Structs which map onto the items of a whole config, usually implemented
+/// with `#[derive(SuperConfig)]` from the `derive` feature
+pub trait SuperConfig<'a>: Sized {
+    /// Makes this from the items of a group or config
+    fn from_items(items: &[SuperItem<'a>]) -> Result<Self, ConvertError>;
+
+    /// Makes the items for a group or config from this
+    fn to_items(&self) -> Vec<SuperItem<'_>>;
+
+    fn from_conf(conf: &SuperConf<'a>) -> Result<Self, ConvertError> {
+        Self::from_items(&conf.items)
+    }
+
+    fn to_conf(&self) -> SuperConf<'_> {
+        SuperConf {
+            items: self.to_items(),
+        }
+    }
+}
+
+/// Error from [FromSuperValue], containing the [Path] of the value which
+/// couldn't be converted
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ConvertError {
+    /// Where the error happened, which is empty for the value itself
+    pub path: Path<'static>,
+    pub kind: ConvertErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConvertErrorKind {
+    /// When a value was the wrong type, containing a description of the type
+    /// which was expected such as `"an integer"`
+    Expected(&'static str),
+    /// When an integer doesn't fit into the type it's being converted to
+    OutOfRange,
+    /// When a required item is missing from a group
+    Missing,
+    /// When a number was written with a unit which isn't known, such as the
+    /// `x` of `10x`
+    UnknownUnit,
+}
+
+impl ConvertError {
+    pub fn new(kind: ConvertErrorKind) -> Self {
+        Self {
+            path: Path::new(),
+            kind,
+        }
+    }
+
+    /// Shortcut for an error of kind [ConvertErrorKind::Expected]
+    pub fn expected(what: &'static str) -> Self {
+        Self::new(ConvertErrorKind::Expected(what))
+    }
+
+    pub(crate) fn within(mut self, segment: Segment<'static>) -> Self {
+        self.path.segments.insert(0, segment);
+        self
+    }
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+
+        match self.kind {
+            ConvertErrorKind::Expected(what) => write!(f, "expected {}", what),
+            ConvertErrorKind::OutOfRange => f.write_str("integer out of range"),
+            ConvertErrorKind::Missing => f.write_str("missing item"),
+            ConvertErrorKind::UnknownUnit => f.write_str("unknown unit"),
+        }
+    }
+}
+
+impl core::error::Error for ConvertError {}
+
+/// Converts the item with `key` from `items`, using [FromSuperValue::from_missing]
+/// if there isn't one
+pub fn from_item<'a, T: FromSuperValue<'a>>(
+    items: &[SuperItem<'a>],
+    key: &'static str,
+) -> Result<T, ConvertError> {
+    match items.iter().find(|item| item.key == key) {
+        Some(item) => T::from_value(&item.value),
+        None => T::from_missing().ok_or_else(|| ConvertError::new(ConvertErrorKind::Missing)),
+    }
+    .map_err(|err| err.within(Segment::Key(Cow::Borrowed(key))))
+}
+
+/// Converts the item with `key` from `items` like [from_item], using `default`
+/// if there isn't one
+pub fn from_item_or<'a, T: FromSuperValue<'a>>(
+    items: &[SuperItem<'a>],
+    key: &'static str,
+    default: impl FnOnce() -> T,
+) -> Result<T, ConvertError> {
+    match items.iter().any(|item| item.key == key) {
+        true => from_item(items, key),
+        false => Ok(default()),
+    }
+}
+
+impl<'a> FromSuperValue<'a> for SuperValue<'a> {
+    fn from_value(value: &SuperValue<'a>) -> Result<Self, ConvertError> {
+        Ok(value.clone())
+    }
+}
+
+impl ToSuperValue for SuperValue<'_> {
+    fn to_value(&self) -> SuperValue<'_> {
+        self.clone()
+    }
+}
+
+impl<'a> FromSuperValue<'a> for bool {
+    fn from_value(value: &SuperValue<'a>) -> Result<Self, ConvertError> {
+        match value {
+            SuperValue::Bool(boolean) => Ok(*boolean),
+            _ => Err(ConvertError::expected("a bool")),
+        }
+    }
+}
+
+impl ToSuperValue for bool {
+    fn to_value(&self) -> SuperValue<'_> {
+        SuperValue::Bool(*self)
+    }
+}
+
+macro_rules! integers {
+    ($($ty:ty),*) => {$(
+        impl<'a> FromSuperValue<'a> for $ty {
+            fn from_value(value: &SuperValue<'a>) -> Result<Self, ConvertError> {
+                match value {
+                    SuperValue::Integer(integer) => <$ty>::try_from(*integer)
+                        .map_err(|_| ConvertError::new(ConvertErrorKind::OutOfRange)),
+                    _ => Err(ConvertError::expected("an integer")),
+                }
+            }
+        }
+    )*};
+}
+
+integers!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! small_integers {
+    ($($ty:ty),*) => {$(
+        impl ToSuperValue for $ty {
+            fn to_value(&self) -> SuperValue<'_> {
+                SuperValue::Integer((*self).into())
+            }
+        }
+    )*};
+}
+
+small_integers!(i8, i16, i32, i64, u8, u16, u32);
+
+impl ToSuperValue for isize {
+    fn to_value(&self) -> SuperValue<'_> {
+        SuperValue::Integer(*self as i64)
+    }
+}
+
+impl<'a> FromSuperValue<'a> for f64 {
+    fn from_value(value: &SuperValue<'a>) -> Result<Self, ConvertError> {
+        match value {
+            SuperValue::Float(float) => Ok(*float),
+            SuperValue::Integer(integer) => Ok(*integer as f64),
+            _ => Err(ConvertError::expected("a number")),
+        }
+    }
+}
+
+impl ToSuperValue for f64 {
+    fn to_value(&self) -> SuperValue<'_> {
+        SuperValue::Float(*self)
+    }
+}
+
+impl<'a> FromSuperValue<'a> for f32 {
+    fn from_value(value: &SuperValue<'a>) -> Result<Self, ConvertError> {
+        f64::from_value(value).map(|float| float as f32)
+    }
+}
+
+impl ToSuperValue for f32 {
+    fn to_value(&self) -> SuperValue<'_> {
+        SuperValue::Float((*self).into())
+    }
+}
+
+impl<'a> FromSuperValue<'a> for Cow<'a, str> {
+    fn from_value(value: &SuperValue<'a>) -> Result<Self, ConvertError> {
+        match value {
+            SuperValue::Name(name) => Ok(name.clone()),
+            _ => Err(ConvertError::expected("a name")),
+        }
+    }
+}
+
+impl<'a> FromSuperValue<'a> for &'a str {
+    fn from_value(value: &SuperValue<'a>) -> Result<Self, ConvertError> {
+        match value {
+            SuperValue::Name(Cow::Borrowed(name)) => Ok(name),
+            _ => Err(ConvertError::expected("a name without escapes")),
+        }
+    }
+}
+
+impl<'a> FromSuperValue<'a> for String {
+    fn from_value(value: &SuperValue<'a>) -> Result<Self, ConvertError> {
+        Cow::from_value(value).map(Cow::into_owned)
+    }
+}
+
+impl ToSuperValue for str {
+    fn to_value(&self) -> SuperValue<'_> {
+        SuperValue::Name(Cow::Borrowed(self))
+    }
+}
+
+impl ToSuperValue for String {
+    fn to_value(&self) -> SuperValue<'_> {
+        self.as_str().to_value()
+    }
+}
+
+impl ToSuperValue for Cow<'_, str> {
+    fn to_value(&self) -> SuperValue<'_> {
+        self.as_ref().to_value()
+    }
+}
+
+impl<T: ToSuperValue + ?Sized> ToSuperValue for &T {
+    fn to_value(&self) -> SuperValue<'_> {
+        (**self).to_value()
+    }
+}
+
+impl<'a, T: FromSuperValue<'a>> FromSuperValue<'a> for Option<T> {
+    fn from_value(value: &SuperValue<'a>) -> Result<Self, ConvertError> {
+        match value {
+            SuperValue::Nothing => Ok(None),
+            _ => T::from_value(value).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: ToSuperValue> ToSuperValue for Option<T> {
+    fn to_value(&self) -> SuperValue<'_> {
+        match self {
+            Some(value) => value.to_value(),
+            None => SuperValue::Nothing,
+        }
+    }
+}
+
+impl<'a, T: FromSuperValue<'a>> FromSuperValue<'a> for Vec<T> {
+    fn from_value(value: &SuperValue<'a>) -> Result<Self, ConvertError> {
+        match value {
+            SuperValue::List(values) => values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    T::from_value(value).map_err(|err| err.within(Segment::Index(index)))
+                })
+                .collect(),
+            _ => Err(ConvertError::expected("a list")),
+        }
+    }
+}
+
+impl<T: ToSuperValue> ToSuperValue for [T] {
+    fn to_value(&self) -> SuperValue<'_> {
+        SuperValue::List(Box::new(self.iter().map(ToSuperValue::to_value).collect()))
+    }
+}
+
+impl<T: ToSuperValue> ToSuperValue for Vec<T> {
+    fn to_value(&self) -> SuperValue<'_> {
+        self.as_slice().to_value()
+    }
+}
+
+impl From<bool> for SuperValue<'_> {
+    fn from(boolean: bool) -> Self {
+        Self::Bool(boolean)
+    }
+}
+
+impl From<i64> for SuperValue<'_> {
+    fn from(integer: i64) -> Self {
+        Self::Integer(integer)
+    }
+}
+
+impl From<f64> for SuperValue<'_> {
+    fn from(float: f64) -> Self {
+        Self::Float(float)
+    }
+}
+
+impl<'a> From<&'a str> for SuperValue<'a> {
+    fn from(name: &'a str) -> Self {
+        Self::Name(Cow::Borrowed(name))
+    }
+}
+
+/// Lenient conversions for configs written by hand, which accept values of
+/// other types when what they mean is clear, unlike [FromSuperValue]
+impl SuperValue<'_> {
+    /// Gives a bool from a bool, `1` or `0`, or a name like `true`, `yes` or
+    /// `off` in any case
+    ///
+    /// ```
+    /// use superconf::SuperValue;
+    ///
+    /// assert_eq!(SuperValue::Integer(1).coerce_bool(), Ok(true));
+    /// assert_eq!(SuperValue::Name("Off".into()).coerce_bool(), Ok(false));
+    /// assert!(SuperValue::Integer(2).coerce_bool().is_err());
+    /// ```
+    pub fn coerce_bool(&self) -> Result<bool, ConvertError> {
+        const NAMES: [(&str, bool); 8] = [
+            ("true", true),
+            ("false", false),
+            ("yes", true),
+            ("no", false),
+            ("on", true),
+            ("off", false),
+            ("1", true),
+            ("0", false),
+        ];
+
+        match self {
+            Self::Bool(boolean) => Ok(*boolean),
+            Self::Integer(1) => Ok(true),
+            Self::Integer(0) => Ok(false),
+            Self::Name(name) => NAMES
+                .iter()
+                .find(|(found, _)| found.eq_ignore_ascii_case(name.trim()))
+                .map(|(_, boolean)| *boolean)
+                .ok_or_else(|| ConvertError::expected("a bool")),
+            _ => Err(ConvertError::expected("a bool")),
+        }
+    }
+
+    /// Gives an integer from an integer, a float without a fraction, a bool
+    /// as `1` or `0`, or a name containing any of these numbers like `"10"`
+    ///
+    /// ```
+    /// use superconf::SuperValue;
+    ///
+    /// assert_eq!(SuperValue::Name(" 10 ".into()).coerce_int(), Ok(10));
+    /// assert_eq!(SuperValue::Float(3.0).coerce_int(), Ok(3));
+    /// assert!(SuperValue::Float(3.5).coerce_int().is_err());
+    /// ```
+    pub fn coerce_int(&self) -> Result<i64, ConvertError> {
+        match self {
+            Self::Integer(integer) => Ok(*integer),
+            Self::Bool(boolean) => Ok(*boolean as i64),
+            Self::Float(float) => float_to_int(*float),
+            Self::Name(name) => {
+                let name = name.trim();
+                match name.parse() {
+                    Ok(integer) => Ok(integer),
+                    Err(_) => name
+                        .parse()
+                        .map_err(|_| ConvertError::expected("an integer"))
+                        .and_then(float_to_int),
+                }
+            }
+            _ => Err(ConvertError::expected("an integer")),
+        }
+    }
+
+    /// Gives a float from a float, an integer, or a name containing a number
+    /// like `"1.5"`
+    pub fn coerce_float(&self) -> Result<f64, ConvertError> {
+        match self {
+            Self::Float(float) => Ok(*float),
+            Self::Integer(integer) => Ok(*integer as f64),
+            Self::Name(name) => name
+                .trim()
+                .parse()
+                .map_err(|_| ConvertError::expected("a number")),
+            _ => Err(ConvertError::expected("a number")),
+        }
+    }
+}
+
+/// Converts a float without a fraction into an integer, which has to be in
+/// the range of an [i64]
+fn float_to_int(float: f64) -> Result<i64, ConvertError> {
+    // the upper bound is 2^63, which can't be stored as an integer
+    if !(-9_223_372_036_854_775_808.0..9_223_372_036_854_775_808.0).contains(&float) {
+        return match float.is_nan() {
+            true => Err(ConvertError::expected("an integer")),
+            false => Err(ConvertError::new(ConvertErrorKind::OutOfRange)),
+        };
+    }
+
+    let integer = float as i64;
+    match integer as f64 == float {
+        true => Ok(integer),
+        false => Err(ConvertError::expected("an integer")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn scalars() {
+        let value = SuperValue::parse("[1, 300, x\\ y, z]").unwrap();
+
+        assert_eq!(
+            Vec::<u16>::from_value(&value),
+            Err(ConvertError::expected("an integer").within(Segment::Index(2)))
+        );
+        assert_eq!(
+            u8::from_value(&SuperValue::Integer(300)),
+            Err(ConvertError::new(ConvertErrorKind::OutOfRange))
+        );
+        assert_eq!(Option::<bool>::from_value(&SuperValue::Nothing), Ok(None));
+        assert_eq!(f32::from_value(&SuperValue::Integer(2)), Ok(2.0));
+
+        let names = SuperValue::parse("[x\\ y, z]").unwrap();
+        assert_eq!(Vec::<String>::from_value(&names).unwrap(), vec!["x y", "z"]);
+        assert!(Vec::<&str>::from_value(&names).is_err());
+        assert_eq!(names.to_value(), names);
+        assert_eq!(vec!["x y", "z"].to_value(), names);
+    }
+
+    #[test]
+    fn items() {
+        let conf = SuperConf::parse("port 8080\nhosts [a, b]").unwrap();
+
+        assert_eq!(from_item::<u16>(&conf.items, "port"), Ok(8080));
+        assert_eq!(from_item::<Option<u16>>(&conf.items, "timeout"), Ok(None));
+        assert_eq!(
+            from_item::<u16>(&conf.items, "timeout")
+                .unwrap_err()
+                .to_string(),
+            "timeout: missing item"
+        );
+        assert_eq!(
+            from_item::<Vec<u8>>(&conf.items, "hosts")
+                .unwrap_err()
+                .to_string(),
+            "hosts[0]: expected an integer"
+        );
+    }
+
+    #[test]
+    fn coerced() {
+        let name = |name| SuperValue::Name(Cow::Borrowed(name));
+
+        assert_eq!(name("YES").coerce_bool(), Ok(true));
+        assert_eq!(name("0").coerce_bool(), Ok(false));
+        assert_eq!(SuperValue::Integer(0).coerce_bool(), Ok(false));
+        assert_eq!(
+            name("maybe").coerce_bool(),
+            Err(ConvertError::expected("a bool"))
+        );
+
+        assert_eq!(name("-7").coerce_int(), Ok(-7));
+        assert_eq!(name("2e3").coerce_int(), Ok(2000));
+        assert_eq!(SuperValue::Bool(true).coerce_int(), Ok(1));
+        assert_eq!(
+            name("99999999999999999999").coerce_int(),
+            Err(ConvertError::new(ConvertErrorKind::OutOfRange))
+        );
+        assert_eq!(
+            SuperValue::Float(f64::NAN).coerce_int(),
+            Err(ConvertError::expected("an integer"))
+        );
+        assert_eq!(SuperValue::Float(-0.0).coerce_int(), Ok(0));
+
+        assert_eq!(name("1.5").coerce_float(), Ok(1.5));
+        assert_eq!(SuperValue::Integer(2).coerce_float(), Ok(2.0));
+        assert!(SuperValue::Bool(true).coerce_float().is_err());
+        assert!(SuperValue::Nothing.coerce_int().is_err());
+    }
+}
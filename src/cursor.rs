@@ -0,0 +1,626 @@
+//! Building of the tree which [Parse](crate::Parse) gives out of [Tokens],
+//! in a single forward pass where brackets are checked as they're found
+
+use super::bytes::literal;
+use super::meta::{attrs_of, Attr};
+use super::tokens::{Raw, Span, Token, Tokens};
+use super::{num_or_name, number, Group, List, Meta, SuperError, SuperItem, SuperValue};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// Text which is being parsed into a tree
+pub(crate) struct Cursor<'a> {
+    input: &'a str,
+}
+
+/// Item split off by [Cursor::raw_conf], where the value is only parsed
+/// already if it couldn't be parsed from the raw text on its own
+pub(crate) struct RawItem<'a> {
+    pub(crate) key: Cow<'a, str>,
+    pub(crate) raw: &'a str,
+    pub(crate) value: Option<SuperValue<'a>>,
+    pub(crate) meta: Option<Box<Meta>>,
+}
+
+/// List or group which is still being built by [Cursor::value]
+struct Open<'a> {
+    /// Where the value started, for if it turns out to be a name
+    start: usize,
+    /// Key and documentation of the item which the value belongs to, if it's
+    /// inside of a group
+    key: Option<(Raw<'a>, Option<Box<Meta>>)>,
+    nested: Nested<'a>,
+}
+
+/// Items of a group or of the config itself which are still being built,
+/// where lines of attributes are held back until the item they're above
+#[derive(Debug, Clone)]
+pub(crate) struct Items<T, V = Vec<T>> {
+    items: V,
+    /// Lines of attributes above the next item, kept as items in case there
+    /// isn't one
+    held: Vec<T>,
+    attrs: Vec<Attr>,
+}
+
+/// Item which [Items] can hold back if it's a line of attributes
+pub(crate) trait Entry {
+    /// Reads the attributes of the item if it's a line of only attributes
+    fn attrs(&self) -> Option<Vec<Attr>>;
+
+    fn meta_mut(&mut self) -> &mut Option<Box<Meta>>;
+}
+
+/// `##` comments which are straight above whatever token comes next, which
+/// is told about every token so that it knows when they aren't anymore
+#[derive(Debug, Clone)]
+pub(crate) struct Doc<'a> {
+    lines: Vec<Cow<'a, str>>,
+    /// If nothing but whitespace has been found on the current line so far
+    line_start: bool,
+}
+
+/// Documentation and lines of attributes at the end of one piece of a config
+/// which aren't above any item yet, carried over to the next piece by
+/// [Cursor::conf_piece]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Held {
+    doc: Doc<'static>,
+    items: Vec<SuperItem<'static>>,
+    attrs: Vec<Attr>,
+    /// If a piece has been parsed already, so there was a newline before the
+    /// next one
+    started: bool,
+    /// Where the next piece starts in the whole text, so that errors are
+    /// given where they are in it rather than in the piece
+    offset: usize,
+}
+
+enum Nested<'a> {
+    List(List<'a>),
+    Group(Items<SuperItem<'a>, Group<'a>>),
+}
+
+impl<'a> Cursor<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        Self { input }
+    }
+
+    /// Parses every item of a whole config, one per line
+    pub(crate) fn conf(self) -> Result<Vec<SuperItem<'a>>, SuperError> {
+        // every line could be an item, which saves growing the vector many
+        // times over for large configs at the cost of some spare room
+        let lines = count_newlines(self.input.as_bytes()) + 1;
+        self.items(Tokens::new(self.input), lines)
+    }
+
+    /// Parses every item of one piece of a config which was split at
+    /// newlines outside of any brackets, where `held` carries whatever is
+    /// waiting for an item at the end of one piece over to the next
+    pub(crate) fn conf_piece(self, held: &mut Held) -> Result<Vec<SuperItem<'a>>, SuperError> {
+        let mut doc: Doc<'a> = core::mem::take(&mut held.doc);
+        if held.started {
+            // the newline which the pieces were split at
+            doc.next(&Token::Newline);
+            held.offset += 1;
+        }
+        held.started = true;
+
+        let mut items = Items {
+            items: Vec::with_capacity(count_newlines(self.input.as_bytes()) + 1),
+            held: core::mem::take(&mut held.items),
+            attrs: core::mem::take(&mut held.attrs),
+        };
+        self.fill(Tokens::new(self.input), &mut items, &mut doc)
+            .map_err(|err| err.moved(held.offset))?;
+        held.offset += self.input.len();
+
+        held.doc = doc.into_owned();
+        held.items = items.held.into_iter().map(SuperItem::into_owned).collect();
+        held.attrs = items.attrs;
+        Ok(items.items)
+    }
+
+    /// Parses the whole text as one item
+    pub(crate) fn item_only(self) -> Result<SuperItem<'a>, SuperError> {
+        let tokens = Tokens::item(self.input);
+
+        // a closing bracket before anything else means there's no key at all
+        let mut ahead = tokens.clone();
+        let first =
+            ahead.find(|found| !matches!(found, Ok((Token::Comment(_) | Token::Newline, _))));
+        if first == Some(Err(SuperError::Unbalanced)) && ahead.depth() == 0 {
+            return Err(SuperError::EmptyItem);
+        }
+
+        self.items(tokens, 1)?.pop().ok_or(SuperError::EmptyItem)
+    }
+
+    /// Parses the whole text as one value
+    pub(crate) fn value_only(self) -> Result<SuperValue<'a>, SuperError> {
+        let mut tokens = Tokens::value(self.input);
+        let (mut value, span) = self.value(&mut tokens, &mut Doc::new())?;
+
+        for found in tokens {
+            if let (Token::Trailing(_), trailing) = found? {
+                value = num_or_name(&self.input[span.start..trailing.end]);
+            }
+        }
+        Ok(value)
+    }
+
+    /// Parses the items at the top level, up to the end of the text
+    fn items(&self, tokens: Tokens<'a>, capacity: usize) -> Result<Vec<SuperItem<'a>>, SuperError> {
+        let mut items = Items::new(Vec::with_capacity(capacity));
+        self.fill(tokens, &mut items, &mut Doc::new())?;
+        Ok(items.finish())
+    }
+
+    /// Adds the items at the top level to `items`, up to the end of the text
+    fn fill(
+        &self,
+        mut tokens: Tokens<'a>,
+        items: &mut Items<SuperItem<'a>>,
+        doc: &mut Doc<'a>,
+    ) -> Result<(), SuperError> {
+        let mut start = 0;
+
+        while let Some(found) = tokens.next() {
+            let found = found?;
+            let meta = doc.next(&found.0);
+            match found {
+                (Token::Key(raw), _) => {
+                    let (value, span) = self.value(&mut tokens, doc)?;
+                    start = span.start;
+                    let item = SuperItem {
+                        key: raw.to_key(),
+                        value,
+                        meta,
+                    };
+                    items.push(item, raw);
+                }
+                (Token::Trailing(_), span) => {
+                    if let Some(item) = items.items.last_mut() {
+                        item.value = num_or_name(&self.input[start..span.end]);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Splits a whole config into keys and the text of their values, which
+    /// still has its brackets checked without parsing any of the values
+    pub(crate) fn raw_conf(self) -> Result<Vec<RawItem<'a>>, SuperError> {
+        let mut items = Items::new(Vec::with_capacity(
+            count_newlines(self.input.as_bytes()) + 1,
+        ));
+        let mut tokens = Tokens::new(self.input);
+        let mut start = 0;
+        let mut doc = Doc::new();
+
+        while let Some(found) = tokens.next() {
+            let found = found?;
+            let meta = doc.next(&found.0);
+            match found {
+                (Token::Key(raw), _) => {
+                    let key = raw.to_key();
+                    let item = match tokens.depth() {
+                        // skipping the value leaves the documentation where
+                        // the key did, as it ends with something other than a
+                        // newline too
+                        0 => {
+                            let span = skip_value(&mut tokens)?;
+                            let raw = &self.input[span.start..span.end];
+                            start = span.start;
+                            RawItem {
+                                key,
+                                raw,
+                                value: raw.is_empty().then_some(SuperValue::Nothing),
+                                meta,
+                            }
+                        }
+                        // the value depends on brackets left open by the key,
+                        // so it can't be parsed on its own later on
+                        _ => {
+                            let (value, span) = self.value(&mut tokens, &mut doc)?;
+                            RawItem {
+                                key,
+                                raw: &self.input[span.start..span.end],
+                                value: Some(value),
+                                meta,
+                            }
+                        }
+                    };
+                    items.push(item, raw);
+                }
+                (Token::Trailing(_), span) => {
+                    if let Some(item) = items.items.last_mut() {
+                        item.raw = &self.input[start..span.end];
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        Ok(items.finish())
+    }
+
+    /// Builds the value which `tokens` are up to, giving where it was written
+    ///
+    /// Lists and groups are kept on a stack of their own rather than built
+    /// by recursing, so brackets nested up to
+    /// [MAX_DEPTH](crate::reader::MAX_DEPTH) deep can't overflow the call
+    /// stack. A list or group which turns out to be a name because of text
+    /// after it is left for whoever holds the value to finish.
+    fn value(
+        &self,
+        tokens: &mut Tokens<'a>,
+        doc: &mut Doc<'a>,
+    ) -> Result<(SuperValue<'a>, Span), SuperError> {
+        let mut stack: Vec<Open<'a>> = Vec::new();
+        let mut key = None;
+        // where the list or group which was closed last started
+        let mut closed = 0;
+
+        for found in tokens.by_ref() {
+            let (token, span) = found?;
+            let meta = doc.next(&token);
+            let (value, start) = match token {
+                Token::Key(raw) => {
+                    key = Some((raw, meta));
+                    continue;
+                }
+                Token::Scalar(raw) => (scalar(raw), span.start),
+                Token::ListStart | Token::GroupStart => {
+                    let nested = match token {
+                        Token::ListStart => Nested::List(List::new()),
+                        _ => Nested::Group(Items::new(Group::new())),
+                    };
+                    stack.push(Open {
+                        start: span.start,
+                        key: key.take(),
+                        nested,
+                    });
+                    continue;
+                }
+                Token::ListEnd | Token::GroupEnd => {
+                    let Some(open) = stack.pop() else {
+                        unreachable!()
+                    };
+                    key = open.key;
+                    closed = open.start;
+                    let value = match open.nested {
+                        Nested::List(values) => SuperValue::List(Box::new(values)),
+                        Nested::Group(items) => SuperValue::Group(Box::new(items.finish())),
+                    };
+                    (value, open.start)
+                }
+                Token::Trailing(_) => {
+                    let last = match stack.last_mut().map(|open| &mut open.nested) {
+                        Some(Nested::List(values)) => values.last_mut(),
+                        Some(Nested::Group(items)) => {
+                            items.items.last_mut().map(|item| &mut item.value)
+                        }
+                        None => None,
+                    };
+                    if let Some(last) = last {
+                        *last = num_or_name(&self.input[closed..span.end]);
+                    }
+                    continue;
+                }
+                Token::Comma | Token::Newline | Token::Comment(_) => continue,
+            };
+
+            match stack.last_mut().map(|open| &mut open.nested) {
+                Some(Nested::List(values)) => values.push(value),
+                Some(Nested::Group(items)) => {
+                    let (raw, meta) = key.take().unwrap_or((Raw::new(""), None));
+                    let item = SuperItem {
+                        key: raw.to_key(),
+                        value,
+                        meta,
+                    };
+                    items.push(item, raw)
+                }
+                None => {
+                    return Ok((
+                        value,
+                        Span {
+                            start,
+                            end: span.end,
+                        },
+                    ))
+                }
+            }
+        }
+
+        // every key is followed by a value unless there's an error first
+        unreachable!()
+    }
+}
+
+impl<'a> Doc<'a> {
+    pub(crate) fn new() -> Self {
+        Self {
+            lines: Vec::new(),
+            line_start: true,
+        }
+    }
+
+    /// Moves past a token, giving the documentation which was straight above
+    /// it if it's the key of an item
+    pub(crate) fn next(&mut self, token: &Token<'a>) -> Option<Box<Meta>> {
+        match token {
+            Token::Newline => {
+                // a blank line means the comments aren't above anything
+                if self.line_start {
+                    self.lines.clear();
+                }
+                self.line_start = true;
+                None
+            }
+            Token::Comment(comment) if self.line_start && comment.starts_with("##") => {
+                let line = &comment[2..];
+                self.lines.push(Cow::Borrowed(
+                    line.strip_prefix(' ').unwrap_or(line).trim_end(),
+                ));
+                self.line_start = false;
+                None
+            }
+            Token::Key(_) => {
+                self.line_start = false;
+                if self.lines.is_empty() {
+                    return None;
+                }
+                let doc = self.lines.join("\n");
+                self.lines.clear();
+                Some(Box::new(Meta {
+                    doc: Some(doc),
+                    attrs: Vec::new(),
+                }))
+            }
+            _ => {
+                self.lines.clear();
+                self.line_start = false;
+                None
+            }
+        }
+    }
+
+    /// Copies any borrowed documentation, so it can be kept after the text
+    fn into_owned(self) -> Doc<'static> {
+        Doc {
+            lines: self
+                .lines
+                .into_iter()
+                .map(|line| Cow::Owned(line.into_owned()))
+                .collect(),
+            line_start: self.line_start,
+        }
+    }
+}
+
+impl Default for Doc<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Held {
+    /// Gives the lines of attributes which were never above any item, which
+    /// are kept as items themselves
+    pub(crate) fn finish(self) -> Vec<SuperItem<'static>> {
+        self.items
+    }
+}
+
+impl<T: Entry, V: Extend<T>> Items<T, V> {
+    pub(crate) fn new(items: V) -> Self {
+        Self {
+            items,
+            held: Vec::new(),
+            attrs: Vec::new(),
+        }
+    }
+
+    /// Adds an item, or holds it back if it's a line of attributes, where
+    /// `key` is how its key was written as only an `@` which isn't escaped
+    /// starts one
+    pub(crate) fn push(&mut self, mut item: T, key: Raw<'_>) {
+        if key.as_str().starts_with('@') {
+            if let Some(attrs) = item.attrs() {
+                self.attrs.extend(attrs);
+                self.held.push(item);
+                return;
+            }
+        }
+
+        if !self.held.is_empty() {
+            let meta = item.meta_mut().get_or_insert_with(Default::default);
+            meta.attrs = core::mem::take(&mut self.attrs);
+            // documentation can be written above the attributes too
+            if meta.doc.is_none() {
+                meta.doc = self
+                    .held
+                    .iter_mut()
+                    .find_map(|held| held.meta_mut().take()?.doc);
+            }
+            self.held.clear();
+        }
+        self.items.extend(Some(item));
+    }
+
+    /// Gives every item, where attributes which weren't above any item are
+    /// kept as items themselves
+    pub(crate) fn finish(mut self) -> V {
+        self.items.extend(self.held);
+        self.items
+    }
+}
+
+impl Entry for SuperItem<'_> {
+    fn attrs(&self) -> Option<Vec<Attr>> {
+        attrs_of(&self.key, &self.value)
+    }
+
+    fn meta_mut(&mut self) -> &mut Option<Box<Meta>> {
+        &mut self.meta
+    }
+}
+
+impl Entry for RawItem<'_> {
+    fn attrs(&self) -> Option<Vec<Attr>> {
+        match &self.value {
+            Some(value) => attrs_of(&self.key, value),
+            None => attrs_of(&self.key, &Cursor::new(self.raw).value_only().ok()?),
+        }
+    }
+
+    fn meta_mut(&mut self) -> &mut Option<Box<Meta>> {
+        &mut self.meta
+    }
+}
+
+/// Moves past the value which `tokens` are up to without building it, giving
+/// where it was written
+fn skip_value(tokens: &mut Tokens<'_>) -> Result<Span, SuperError> {
+    let mut depth = 0;
+    let mut start = None;
+
+    for found in tokens.by_ref() {
+        let (token, span) = found?;
+        let start = *start.get_or_insert(span.start);
+
+        match token {
+            Token::ListStart | Token::GroupStart => depth += 1,
+            Token::ListEnd | Token::GroupEnd => depth -= 1,
+            _ => (),
+        }
+        if depth == 0 {
+            return Ok(Span {
+                start,
+                end: span.end,
+            });
+        }
+    }
+
+    unreachable!()
+}
+
+/// Reads a value which isn't a list or group from its trimmed text
+pub(crate) fn scalar(raw: Raw<'_>) -> SuperValue<'_> {
+    match raw.as_str() {
+        "" => SuperValue::Nothing,
+        "true" => SuperValue::Bool(true),
+        "false" => SuperValue::Bool(false),
+        text => match literal(text) {
+            Some(data) => SuperValue::Bytes(Box::new(Cow::Borrowed(data))),
+            None => number(text).unwrap_or_else(|| SuperValue::Name(raw.to_key())),
+        },
+    }
+}
+
+/// Counts every newline, using memchr if the `memchr` feature is enabled
+fn count_newlines(bytes: &[u8]) -> usize {
+    #[cfg(feature = "memchr")]
+    return memchr::memchr_iter(b'\n', bytes).count();
+
+    #[cfg(not(feature = "memchr"))]
+    bytes.iter().filter(|byte| **byte == b'\n').count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parse, SuperConf, SuperError, SuperItem, SuperValue};
+    use alloc::vec;
+
+    #[test]
+    fn whole_values() {
+        let conf = SuperConf::parse("a [1] x\nb {c 1} # d\ne [1,\n 2]").unwrap();
+
+        assert_eq!(conf.get("a"), Some(&SuperValue::Name("[1] x".into())));
+        assert_eq!(conf.get("b.c"), Some(&SuperValue::Integer(1)));
+        assert_eq!(
+            conf.get("e"),
+            Some(&SuperValue::list(vec![
+                SuperValue::Integer(1),
+                SuperValue::Integer(2)
+            ]))
+        );
+    }
+
+    #[test]
+    fn keys() {
+        assert_eq!(
+            SuperItem::parse("a\t b").unwrap(),
+            SuperItem {
+                key: "a\t".into(),
+                value: SuperValue::Name("b".into()),
+                meta: None,
+            }
+        );
+        assert_eq!(SuperItem::parse(" a\t  # c").unwrap().key, "a");
+        assert_eq!(SuperItem::parse("  # c"), Err(SuperError::EmptyItem));
+    }
+
+    #[test]
+    fn docs() {
+        let conf = SuperConf::parse(
+            "## a\n##  b  \na 1 ## c\n## d\n\ne 2\nf {\n  ## g\n  h [{\n## i\n  j 3}]\n}\n# k\nl",
+        )
+        .unwrap();
+        let doc = |path: &str| {
+            let (group, key) = path.rsplit_once('.').unwrap_or(("", path));
+            let items = match group {
+                "" => &conf.items[..],
+                group => match conf.get(group) {
+                    Some(SuperValue::Group(items)) => &items[..],
+                    _ => unreachable!(),
+                },
+            };
+            items.iter().find(|item| item.key == key).unwrap().doc()
+        };
+
+        assert_eq!(doc("a"), Some("a\n b"));
+        assert_eq!(doc("e"), None);
+        assert_eq!(doc("f"), None);
+        assert_eq!(doc("f.h"), Some("g"));
+        assert_eq!(doc("f.h[0].j"), Some("i"));
+        assert_eq!(doc("l"), None);
+    }
+
+    #[test]
+    fn deep() {
+        let text = ["a ", &"[{b ".repeat(1_000_000), "1"].concat();
+        assert_eq!(SuperConf::parse(&text), Err(SuperError::TooDeep));
+
+        let text = ["[{b ".repeat(64), "1".into(), "}]".repeat(64)].concat();
+        let mut value = &SuperValue::parse(&text).unwrap();
+        for _ in 0..64 {
+            value = match value {
+                SuperValue::List(values) => match &values[..] {
+                    [SuperValue::Group(items)] => &items[0].value,
+                    _ => panic!("expected a group in {:?}", value),
+                },
+                _ => panic!("expected a list in {:?}", value),
+            };
+        }
+        assert_eq!(value, &SuperValue::Integer(1));
+    }
+
+    #[test]
+    fn unbalanced() {
+        for input in ["[1", "a]", "{a [1}]", "[1] {"] {
+            assert_eq!(
+                SuperValue::parse(input),
+                Err(SuperError::Unbalanced),
+                "{}",
+                input
+            );
+        }
+    }
+}
@@ -0,0 +1,433 @@
+//! Decoding of typed structs from configs using serde, behind the `serde`
+//! feature
+//!
+//! Groups and the top level decode as maps or structs, lists as sequences and
+//! names as strings, with nothing also decoding as an empty string. Enum
+//! variants are written as a name for unit variants or a group with a single
+//! item for the rest.
+
+use super::{Parse, Path, Segment, SuperConf, SuperError, SuperItem, SuperValue};
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::iter::Enumerate;
+use core::slice;
+use serde::de::{
+    self, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess, Unexpected,
+    VariantAccess, Visitor,
+};
+use serde::{forward_to_deserialize_any, Deserialize};
+
+/// Parses `input` and decodes it into `T`, borrowing strings from `input`
+/// where they didn't need unescaping
+pub fn from_str<'de, T: Deserialize<'de>>(input: &'de str) -> Result<T, SerdeError> {
+    from_conf(&SuperConf::parse(input)?)
+}
+
+/// Decodes an already parsed config into `T`
+pub fn from_conf<'de, T: Deserialize<'de>>(conf: &SuperConf<'de>) -> Result<T, SerdeError> {
+    T::deserialize(conf)
+}
+
+/// Decodes a single value into `T`
+pub fn from_value<'de, T: Deserialize<'de>>(value: &SuperValue<'de>) -> Result<T, SerdeError> {
+    T::deserialize(value)
+}
+
+/// Error whilst encoding or decoding with serde, containing the [Path] of the value that
+/// couldn't be decoded
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SerdeError {
+    /// Where the error happened, which is empty for the top level
+    pub path: Path<'static>,
+    pub message: String,
+}
+
+impl SerdeError {
+    pub(crate) fn within(mut self, segment: Segment<'static>) -> Self {
+        self.path.segments.insert(0, segment);
+        self
+    }
+}
+
+impl From<SuperError> for SerdeError {
+    fn from(err: SuperError) -> Self {
+        de::Error::custom(err)
+    }
+}
+
+impl fmt::Display for SerdeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path.is_empty() {
+            true => f.write_str(&self.message),
+            false => write!(f, "{}: {}", self.path, self.message),
+        }
+    }
+}
+
+impl core::error::Error for SerdeError {}
+
+impl de::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self {
+            path: Path::new(),
+            message: msg.to_string(),
+        }
+    }
+}
+
+fn unexpected<'b>(value: &'b SuperValue) -> Unexpected<'b> {
+    match value {
+        SuperValue::Nothing => Unexpected::Unit,
+        SuperValue::Name(name) => Unexpected::Str(name),
+        SuperValue::Bool(boolean) => Unexpected::Bool(*boolean),
+        SuperValue::Integer(integer) => Unexpected::Signed(*integer),
+        SuperValue::Float(float) => Unexpected::Float(*float),
+        SuperValue::Bytes(_) => Unexpected::Other("bytes"),
+        SuperValue::List(_) => Unexpected::Seq,
+        SuperValue::Group(_) => Unexpected::Map,
+    }
+}
+
+fn visit_cow<'de, V: Visitor<'de>>(
+    text: &Cow<'de, str>,
+    visitor: V,
+) -> Result<V::Value, SerdeError> {
+    match text {
+        Cow::Borrowed(text) => visitor.visit_borrowed_str(text),
+        Cow::Owned(text) => visitor.visit_str(text),
+    }
+}
+
+impl<'de> Deserializer<'de> for &SuperConf<'de> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_map(Items::new(&self.items))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> Deserializer<'de> for &SuperValue<'de> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            SuperValue::Nothing => visitor.visit_unit(),
+            SuperValue::Name(name) => visit_cow(name, visitor),
+            SuperValue::Bool(boolean) => visitor.visit_bool(*boolean),
+            SuperValue::Integer(integer) => visitor.visit_i64(*integer),
+            SuperValue::Float(float) => visitor.visit_f64(*float),
+            SuperValue::Bytes(_) => visitor.visit_byte_buf(self.decode_bytes().map_err(|_| {
+                <SerdeError as de::Error>::invalid_value(unexpected(self), &"base64 data")
+            })?),
+            SuperValue::List(values) => visitor.visit_seq(Values {
+                iter: values.iter().enumerate(),
+            }),
+            SuperValue::Group(items) => visitor.visit_map(Items::new(items)),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            SuperValue::Nothing => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            SuperValue::Nothing => visitor.visit_borrowed_str(""),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self {
+            SuperValue::Name(variant) => visitor.visit_enum(Enum {
+                variant,
+                value: None,
+            }),
+            SuperValue::Group(items) if items.len() == 1 => visitor.visit_enum(Enum {
+                variant: &items[0].key,
+                value: Some(&items[0].value),
+            }),
+            _ => Err(de::Error::invalid_type(unexpected(self), &visitor)),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char bytes
+        byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier
+    }
+}
+
+struct Values<'b, 'de> {
+    iter: Enumerate<slice::Iter<'b, SuperValue<'de>>>,
+}
+
+impl<'b, 'de> SeqAccess<'de> for Values<'b, 'de> {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SerdeError> {
+        match self.iter.next() {
+            Some((index, value)) => seed
+                .deserialize(value)
+                .map(Some)
+                .map_err(|err| err.within(Segment::Index(index))),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct Items<'b, 'de> {
+    iter: slice::Iter<'b, SuperItem<'de>>,
+    current: Option<&'b SuperItem<'de>>,
+}
+
+impl<'b, 'de> Items<'b, 'de> {
+    fn new(items: &'b [SuperItem<'de>]) -> Self {
+        Self {
+            iter: items.iter(),
+            current: None,
+        }
+    }
+}
+
+impl<'b, 'de> MapAccess<'de> for Items<'b, 'de> {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeError> {
+        self.current = self.iter.next();
+
+        match self.current {
+            Some(item) => seed.deserialize(Key(&item.key)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerdeError> {
+        let item = self
+            .current
+            .take()
+            .ok_or_else(|| <SerdeError as de::Error>::custom("value requested before key"))?;
+
+        seed.deserialize(&item.value)
+            .map_err(|err| err.within(Segment::Key(Cow::Owned(item.key.to_string()))))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// Deserializer for keys and enum variants, which are always strings
+struct Key<'b, 'de>(&'b Cow<'de, str>);
+
+impl<'b, 'de> Deserializer<'de> for Key<'b, 'de> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visit_cow(self.0, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct Enum<'b, 'de> {
+    variant: &'b Cow<'de, str>,
+    value: Option<&'b SuperValue<'de>>,
+}
+
+impl<'b, 'de> EnumAccess<'de> for Enum<'b, 'de> {
+    type Error = SerdeError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), SerdeError> {
+        Ok((seed.deserialize(Key(self.variant))?, self))
+    }
+}
+
+impl<'b, 'de> Enum<'b, 'de> {
+    fn value(&self) -> Result<&'b SuperValue<'de>, SerdeError> {
+        self.value.ok_or_else(|| {
+            de::Error::invalid_type(Unexpected::UnitVariant, &"a group with the variant's value")
+        })
+    }
+
+    fn within<T>(&self, result: Result<T, SerdeError>) -> Result<T, SerdeError> {
+        result.map_err(|err| err.within(Segment::Key(Cow::Owned(self.variant.to_string()))))
+    }
+}
+
+impl<'b, 'de> VariantAccess<'de> for Enum<'b, 'de> {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        match self.value {
+            None | Some(SuperValue::Nothing) => Ok(()),
+            Some(value) => Err(de::Error::invalid_type(
+                unexpected(value),
+                &"a unit variant",
+            )),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        self.within(seed.deserialize(self.value()?))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.within(self.value()?.deserialize_tuple(len, visitor))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.within(self.value()?.deserialize_struct("", fields, visitor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Config<'a> {
+        name: &'a str,
+        ratio: f32,
+        server: Server,
+        tags: Vec<String>,
+        backup: Option<Server>,
+        mode: Mode,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Server {
+        host: String,
+        port: u16,
+        tls: Option<bool>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Mode {
+        Fast,
+        Retry(u8),
+        Timeout { secs: u32 },
+    }
+
+    #[test]
+    fn structs() {
+        let config: Config = from_str(
+            "name example\nratio 0.5\nserver {host local\\ host, port 8080}\ntags [a, b]\nbackup\nmode fast",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                name: "example",
+                ratio: 0.5,
+                server: Server {
+                    host: "local host".into(),
+                    port: 8080,
+                    tls: None
+                },
+                tags: vec!["a".into(), "b".into()],
+                backup: None,
+                mode: Mode::Fast
+            }
+        );
+    }
+
+    #[test]
+    fn enums() {
+        let modes: Vec<Mode> =
+            from_value(&SuperValue::parse("[fast, {retry 3}, {timeout {secs 10}}]").unwrap())
+                .unwrap();
+
+        assert_eq!(
+            modes,
+            vec![Mode::Fast, Mode::Retry(3), Mode::Timeout { secs: 10 }]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = from_str::<Server>("host a\nport 70000").unwrap_err();
+        assert_eq!(err.path.to_string(), "port");
+        assert_eq!(
+            err.to_string(),
+            "port: invalid value: integer `70000`, expected u16"
+        );
+
+        let err = from_value::<Vec<Server>>(
+            &SuperValue::parse("[{host a, port 1}, {host b, port x}]").unwrap(),
+        )
+        .unwrap_err();
+        assert_eq!(err.path.to_string(), "[1].port");
+
+        let err = from_str::<Server>("host a").unwrap_err();
+        assert_eq!(err.to_string(), "missing field `port`");
+    }
+}
@@ -0,0 +1,484 @@
+//! Lints for configs which are invalid or likely not what was meant, given by
+//! [lint] as [Diagnostic]s which editors and tools can show as they are
+
+use super::schema::closest;
+use super::tokens::{Span, Token, Tokens};
+use super::{Path, SuperError, SuperValue};
+use alloc::borrow::Cow;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Problem which was found by [lint], at the [Path] of the key or value it's
+/// about
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    /// How bad the problem is, which is always [DiagnosticKind::severity]
+    pub severity: Severity,
+    pub path: Path<'static>,
+    /// Where the key or value it's about was written
+    pub span: Span,
+    pub kind: DiagnosticKind,
+}
+
+/// How bad a [Diagnostic] is, which are ordered from the mildest up so the
+/// worst of some diagnostics is their maximum
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Severity {
+    /// When the text is fine but could be written more consistently
+    Hint,
+    /// When the text parses but likely not into what was meant
+    Warning,
+    /// When the text can't be parsed at all
+    Error,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum DiagnosticKind {
+    /// When the text couldn't be parsed past this point, which ends the
+    /// diagnostics
+    Parse(SuperError),
+    /// When a key is in a different naming style to most of the keys which
+    /// have one
+    MixedStyle { found: Style, expected: Style },
+    /// When a key starts or ends with escaped whitespace, which is easy to
+    /// miss when reading it
+    Whitespace,
+    /// When a key is repeated inside of the same group or at the top level,
+    /// where only the first is ever read
+    Duplicate {
+        /// Where the key was first written
+        first: Span,
+    },
+    /// When a name is close enough to another value that it was likely
+    /// meant, like `ture` for `true`, containing that value
+    Suspicious { meant: &'static str },
+    /// When a list or group has text straight after it, which makes the
+    /// whole of it a name instead
+    Trailing,
+}
+
+/// Naming style of a key, which keys of only one lowercase word don't have as
+/// they fit most styles
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Style {
+    /// Like `max_workers`
+    Snake,
+    /// Like `MAX_WORKERS`
+    ScreamingSnake,
+    /// Like `max-workers`
+    Kebab,
+    /// Like `maxWorkers`
+    Camel,
+    /// Like `MaxWorkers`
+    Pascal,
+    /// Like `max workers`
+    Spaced,
+}
+
+/// List or group which is open, alongside the keys found in it so far
+struct Open<'a> {
+    /// Where its opening bracket is
+    start: usize,
+    /// Index of the next element, if it's a list
+    list: Option<usize>,
+    keys: Vec<(Cow<'a, str>, Span)>,
+}
+
+/// Values which names are checked against for [DiagnosticKind::Suspicious]
+const MEANT: [&str; 2] = ["true", "false"];
+
+/// Lints `text`, giving every [Diagnostic] in the order it was written
+///
+/// Keys in the minority naming style are found after going over all of the
+/// text, as only then is it known which style most keys are in. Text which
+/// can't be parsed gives a [Severity::Error] where it goes wrong, after the
+/// diagnostics for the text before it.
+///
+/// ```
+/// use superconf::diagnostics::{DiagnosticKind, Severity, Style};
+/// use superconf::lint;
+/// use superconf::tokens::Span;
+///
+/// let diagnostics = lint("max_workers 4\nlogLevel info\nmax_workers 8\ntls ture");
+/// assert_eq!(diagnostics[0].severity, Severity::Hint);
+///
+/// let kinds: Vec<_> = diagnostics.into_iter().map(|found| found.kind).collect();
+///
+/// assert_eq!(
+///     kinds,
+///     [
+///         DiagnosticKind::MixedStyle {
+///             found: Style::Camel,
+///             expected: Style::Snake
+///         },
+///         DiagnosticKind::Duplicate { first: Span { start: 0, end: 11 } },
+///         DiagnosticKind::Suspicious { meant: "true" },
+///     ]
+/// );
+///
+/// let diagnostics = lint("a [1, {b 2}");
+/// assert_eq!(diagnostics[0].kind, DiagnosticKind::Parse(superconf::SuperError::Unbalanced));
+/// assert_eq!(diagnostics[0].span, Span { start: 2, end: 3 });
+/// ```
+pub fn lint(text: &str) -> Vec<Diagnostic> {
+    let mut findings = Vec::new();
+    let mut styles = Vec::new();
+    let mut path = Path::new();
+    let mut stack = vec![Open {
+        start: 0,
+        list: None,
+        keys: Vec::new(),
+    }];
+    let mut tokens = Tokens::new(text);
+    // list or group which was just closed, in case there's text after it
+    let mut closed = None;
+
+    while let Some(found) = tokens.next() {
+        let (token, span) = match found {
+            Ok(found) => found,
+            Err(err) => {
+                findings.push(Diagnostic::new(
+                    path.clone(),
+                    error_span(text, tokens.pos(), &stack),
+                    DiagnosticKind::Parse(err),
+                ));
+                break;
+            }
+        };
+        match token {
+            Token::Key(raw) => {
+                let key = raw.to_key();
+                path.push_key(key.clone().into_owned());
+
+                let mut finding = |kind| findings.push(Diagnostic::new(path.clone(), span, kind));
+                if key.trim() != key {
+                    finding(DiagnosticKind::Whitespace);
+                }
+                if let Some(style) = Style::of(&key) {
+                    styles.push((path.clone(), span, style));
+                }
+
+                let Some(open) = stack.last_mut() else {
+                    unreachable!()
+                };
+                match open.keys.iter().find(|(found, _)| *found == key) {
+                    Some(&(_, first)) => finding(DiagnosticKind::Duplicate { first }),
+                    None => open.keys.push((key, span)),
+                }
+            }
+            Token::Scalar(_) | Token::ListStart | Token::GroupStart => {
+                if let Some(Open {
+                    list: Some(next), ..
+                }) = stack.last_mut()
+                {
+                    path.push_index(*next);
+                    *next += 1;
+                }
+
+                match token {
+                    Token::Scalar(raw) => {
+                        if let SuperValue::Name(name) = raw.to_value() {
+                            if let Some(meant) = suspicious(&name) {
+                                let kind = DiagnosticKind::Suspicious { meant };
+                                findings.push(Diagnostic::new(path.clone(), span, kind));
+                            }
+                        }
+                        path.pop();
+                    }
+                    _ => stack.push(Open {
+                        start: span.start,
+                        list: (token == Token::ListStart).then_some(0),
+                        keys: Vec::new(),
+                    }),
+                }
+            }
+            Token::ListEnd | Token::GroupEnd => {
+                if let Some(open) = stack.pop() {
+                    closed = Some((path.clone(), open.start));
+                }
+                path.pop();
+            }
+            Token::Trailing(_) => {
+                if let Some((path, start)) = closed.take() {
+                    let span = Span { start, ..span };
+                    findings.push(Diagnostic::new(path, span, DiagnosticKind::Trailing));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    if let Some(expected) = most_common(&styles) {
+        for (path, span, found) in styles {
+            if found != expected {
+                let kind = DiagnosticKind::MixedStyle { found, expected };
+                findings.push(Diagnostic::new(path, span, kind));
+            }
+        }
+        findings.sort_by_key(|finding| finding.span.start);
+    }
+
+    findings
+}
+
+/// Where a parse error is, which is the bracket that's never closed if it
+/// got to the end of the text and otherwise the char it stopped at
+fn error_span(text: &str, pos: usize, stack: &[Open]) -> Span {
+    let start = match stack {
+        [_, .., open] if pos >= text.len() => open.start,
+        _ => pos.min(text.len()),
+    };
+    let len = text[start..].chars().next().map_or(0, char::len_utf8);
+
+    Span {
+        start,
+        end: start + len,
+    }
+}
+
+/// Value which `name` was likely meant to be, if it's not quite one of them
+fn suspicious(name: &str) -> Option<&'static str> {
+    MEANT
+        .iter()
+        .find(|meant| name.eq_ignore_ascii_case(meant))
+        .or_else(|| closest(name, &MEANT))
+        .copied()
+}
+
+/// Style which the most keys are in, going with the one found first if
+/// there's a tie
+fn most_common(styles: &[(Path<'static>, Span, Style)]) -> Option<Style> {
+    let mut counts = [0; 6];
+    for &(_, _, style) in styles {
+        counts[style as usize] += 1;
+    }
+
+    let mut best: Option<Style> = None;
+    for &(_, _, style) in styles {
+        if best.is_none_or(|most| counts[style as usize] > counts[most as usize]) {
+            best = Some(style);
+        }
+    }
+    best
+}
+
+impl Style {
+    /// Finds the style of `key`, if it has one
+    pub fn of(key: &str) -> Option<Self> {
+        let lower = key.chars().all(|c| !c.is_alphabetic() || c.is_lowercase());
+        let upper = key.chars().all(|c| !c.is_alphabetic() || c.is_uppercase());
+        let only = |sep: char| {
+            key.split(sep)
+                .all(|word| !word.is_empty() && word.chars().all(char::is_alphanumeric))
+        };
+
+        if key.contains('_') && only('_') {
+            match (lower, upper) {
+                (true, _) => Some(Self::Snake),
+                (_, true) => Some(Self::ScreamingSnake),
+                _ => None,
+            }
+        } else if key.contains('-') && only('-') {
+            lower.then_some(Self::Kebab)
+        } else if key.contains(' ') && only(' ') {
+            lower.then_some(Self::Spaced)
+        } else if !key.chars().all(char::is_alphanumeric) || lower || upper {
+            None
+        } else {
+            match key.starts_with(char::is_uppercase) {
+                true => Some(Self::Pascal),
+                false => Some(Self::Camel),
+            }
+        }
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Snake => "snake_case",
+            Self::ScreamingSnake => "SCREAMING_SNAKE_CASE",
+            Self::Kebab => "kebab-case",
+            Self::Camel => "camelCase",
+            Self::Pascal => "PascalCase",
+            Self::Spaced => "spaced words",
+        })
+    }
+}
+
+impl Diagnostic {
+    fn new(path: Path<'static>, span: Span, kind: DiagnosticKind) -> Self {
+        Self {
+            severity: kind.severity(),
+            path,
+            span,
+            kind,
+        }
+    }
+}
+
+impl DiagnosticKind {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::Parse(_) => Severity::Error,
+            Self::Whitespace
+            | Self::Duplicate { .. }
+            | Self::Suspicious { .. }
+            | Self::Trailing => Severity::Warning,
+            Self::MixedStyle { .. } => Severity::Hint,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Hint => "hint",
+        })
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.severity)?;
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+
+        match &self.kind {
+            DiagnosticKind::Parse(err) => write!(f, "{}", err),
+            DiagnosticKind::MixedStyle { found, expected } => {
+                write!(f, "key is in {} while most keys are in {}", found, expected)
+            }
+            DiagnosticKind::Whitespace => f.write_str("key starts or ends with whitespace"),
+            DiagnosticKind::Duplicate { .. } => {
+                f.write_str("key is repeated, so only the first is read")
+            }
+            DiagnosticKind::Suspicious { meant } => {
+                write!(f, "name is likely meant to be {}", meant)
+            }
+            DiagnosticKind::Trailing => {
+                f.write_str("text after the closing bracket makes this a name")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::{String, ToString};
+
+    fn findings(text: &str) -> Vec<(String, &str)> {
+        lint(text)
+            .into_iter()
+            .map(|finding| {
+                (
+                    finding.to_string(),
+                    &text[finding.span.start..finding.span.end],
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn styles() {
+        assert_eq!(Style::of("max_workers"), Some(Style::Snake));
+        assert_eq!(Style::of("MAX_WORKERS2"), Some(Style::ScreamingSnake));
+        assert_eq!(Style::of("max-workers"), Some(Style::Kebab));
+        assert_eq!(Style::of("maxWorkers"), Some(Style::Camel));
+        assert_eq!(Style::of("MaxWorkers"), Some(Style::Pascal));
+        assert_eq!(Style::of("max workers"), Some(Style::Spaced));
+        assert_eq!(Style::of("workers"), None);
+        assert_eq!(Style::of("URL"), None);
+        assert_eq!(Style::of("max_Workers"), None);
+        assert_eq!(Style::of("max__workers"), None);
+        assert_eq!(Style::of("max-workers_2"), None);
+    }
+
+    #[test]
+    fn every_finding() {
+        assert_eq!(
+            findings(
+                "a_b 1\nc_d {e_f [True, x], gH flase}\n\\ a 2\nc_d {}\nhosts [{port 1, port 2}]"
+            ),
+            [
+                (
+                    "warning: c_d.e_f[0]: name is likely meant to be true".into(),
+                    "True"
+                ),
+                (
+                    "hint: c_d.gH: key is in camelCase while most keys are in snake_case".into(),
+                    "gH"
+                ),
+                (
+                    "warning: c_d.gH: name is likely meant to be false".into(),
+                    "flase"
+                ),
+                (
+                    "warning:  a: key starts or ends with whitespace".into(),
+                    "\\ a"
+                ),
+                (
+                    "warning: c_d: key is repeated, so only the first is read".into(),
+                    "c_d"
+                ),
+                (
+                    "warning: hosts[0].port: key is repeated, so only the first is read".into(),
+                    "port"
+                ),
+            ]
+        );
+        assert_eq!(
+            findings("a [1, {b 2} x]\nc {d 3}  e f"),
+            [
+                (
+                    "warning: a[1]: text after the closing bracket makes this a name".into(),
+                    "{b 2} x"
+                ),
+                (
+                    "warning: c: text after the closing bracket makes this a name".into(),
+                    "{d 3}  e f"
+                ),
+            ]
+        );
+        assert_eq!(findings("a {b 1}\nb 2\nname trust"), []);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            findings("a_b {c [1}]"),
+            [("error: a_b.c: brackets are unbalanced".into(), "}")]
+        );
+        assert_eq!(
+            findings("fooBar 1\nfoo_bar [1, {x_y 2}\nbaz_qux 2"),
+            [
+                (
+                    "hint: fooBar: key is in camelCase while most keys are in snake_case".into(),
+                    "fooBar"
+                ),
+                ("error: foo_bar: brackets are unbalanced".into(), "["),
+                (
+                    "warning: foo_bar[1]: text after the closing bracket makes this a name".into(),
+                    "{x_y 2}\nbaz_qux 2"
+                ),
+            ]
+        );
+        assert_eq!(
+            findings("]"),
+            [("error: brackets are unbalanced".into(), "]")]
+        );
+        assert_eq!(Severity::Error.max(Severity::Hint), Severity::Error);
+        assert_eq!(
+            lint("a_b 1\ncD 2\ne [1] x")
+                .iter()
+                .map(|d| d.severity)
+                .max(),
+            Some(Severity::Warning)
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! Structural differences between two configs with [Diff], such as between
+//! the config an application was running with and the one it just reloaded
+
+use super::{Path, SuperConf, SuperItem, SuperValue};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Every change between two configs, found by [SuperConf::diff]
+///
+/// ```
+/// use superconf::{Parse, SuperConf};
+///
+/// let old = SuperConf::parse("port 80\nhosts [a, b]\ndebug true").unwrap();
+/// let new = SuperConf::parse("port 8080\nhosts [a]\ntls {cert x.pem}").unwrap();
+///
+/// assert_eq!(
+///     old.diff(&new).render(),
+///     "~ port 80 → 8080\n- hosts[1] b\n- debug true\n+ tls {cert x.pem}"
+/// );
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Diff<'b> {
+    /// Changes in the order they're found in the old config, followed by
+    /// anything added in the order it's found in the new one
+    pub changes: Vec<Change<'b>>,
+}
+
+/// Value which was added, removed or changed, alongside its path
+///
+/// Lists and groups which are in both configs are looked inside of instead of
+/// being given as changed, where list elements are matched up by index and
+/// items by key.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Change<'b> {
+    Added {
+        path: Path<'b>,
+        value: &'b SuperValue<'b>,
+    },
+    Removed {
+        path: Path<'b>,
+        value: &'b SuperValue<'b>,
+    },
+    Changed {
+        path: Path<'b>,
+        old: &'b SuperValue<'b>,
+        new: &'b SuperValue<'b>,
+    },
+}
+
+impl<'a> SuperConf<'a> {
+    /// Finds every change needed to turn this config into `new`
+    pub fn diff<'b>(&'b self, new: &'b SuperConf<'_>) -> Diff<'b> {
+        let mut diff = Diff::default();
+        diff_items(&mut diff, &mut Path::new(), &self.items, &new.items);
+        diff
+    }
+}
+
+impl Diff<'_> {
+    /// Checks if both configs were the same
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Writes each change on its own line, as `+ path value` when it was
+    /// added, `- path value` when it was removed and `~ path old → new` when
+    /// it was changed, for logging what changed when a config is reloaded
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+}
+
+fn diff_items<'b>(
+    diff: &mut Diff<'b>,
+    path: &mut Path<'b>,
+    old: &'b [SuperItem<'b>],
+    new: &'b [SuperItem<'b>],
+) {
+    // repeated keys are matched up in the order they're found in
+    let mut matched = alloc::vec![false; new.len()];
+
+    for item in old {
+        path.push_key(item.key.clone());
+        let found = new
+            .iter()
+            .enumerate()
+            .position(|(ind, found)| !matched[ind] && found.key == item.key);
+        match found {
+            Some(ind) => {
+                matched[ind] = true;
+                diff_values(diff, path, &item.value, &new[ind].value);
+            }
+            None => diff.changes.push(Change::Removed {
+                path: path.clone(),
+                value: &item.value,
+            }),
+        }
+        path.pop();
+    }
+
+    for (item, _) in new.iter().zip(matched).filter(|(_, matched)| !matched) {
+        path.push_key(item.key.clone());
+        diff.changes.push(Change::Added {
+            path: path.clone(),
+            value: &item.value,
+        });
+        path.pop();
+    }
+}
+
+fn diff_values<'b>(
+    diff: &mut Diff<'b>,
+    path: &mut Path<'b>,
+    old: &'b SuperValue<'b>,
+    new: &'b SuperValue<'b>,
+) {
+    match (old, new) {
+        (SuperValue::Group(old), SuperValue::Group(new)) => diff_items(diff, path, old, new),
+        (SuperValue::List(old), SuperValue::List(new)) => {
+            for index in 0..old.len().max(new.len()) {
+                path.push_index(index);
+                let change = match (old.get(index), new.get(index)) {
+                    (Some(old), Some(new)) => {
+                        diff_values(diff, path, old, new);
+                        None
+                    }
+                    (Some(value), None) => Some(Change::Removed {
+                        path: path.clone(),
+                        value,
+                    }),
+                    (None, Some(value)) => Some(Change::Added {
+                        path: path.clone(),
+                        value,
+                    }),
+                    (None, None) => unreachable!(),
+                };
+                diff.changes.extend(change);
+                path.pop();
+            }
+        }
+        _ if old != new => diff.changes.push(Change::Changed {
+            path: path.clone(),
+            old,
+            new,
+        }),
+        _ => (),
+    }
+}
+
+impl fmt::Display for Diff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (ind, change) in self.changes.iter().enumerate() {
+            if ind != 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Change<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (sign, path, old, value) = match self {
+            Self::Added { path, value } => ('+', path, None, value),
+            Self::Removed { path, value } => ('-', path, None, value),
+            Self::Changed { path, old, new } => ('~', path, Some(old), new),
+        };
+
+        write!(f, "{} {}", sign, path)?;
+        if let Some(old) = old {
+            write_value(f, old)?;
+            f.write_str(" →")?;
+        }
+        write_value(f, value)
+    }
+}
+
+/// Writes a value after a space, apart from nothing which is written as no
+/// text at all
+fn write_value(f: &mut fmt::Formatter<'_>, value: &SuperValue<'_>) -> fmt::Result {
+    match value {
+        SuperValue::Nothing => Ok(()),
+        value => write!(f, " {}", value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+
+    fn render(old: &str, new: &str) -> String {
+        let old = SuperConf::parse(old).unwrap();
+        let new = SuperConf::parse(new).unwrap();
+        old.diff(&new).render()
+    }
+
+    #[test]
+    fn changes() {
+        assert_eq!(render("a 1\nb {c 2}", "a 1\nb {c 2}"), "");
+        assert_eq!(
+            render(
+                "a {b 1, c [1, 2]}\nd x\nd y\ne",
+                "a {b 2, c [1, 2, 3], f}\nd y\nd y\ne [1]"
+            ),
+            "~ a.b 1 → 2\n+ a.c[2] 3\n+ a.f\n~ d x → y\n~ e → [1]"
+        );
+        assert_eq!(render("a [{b 1}]", "a []"), "- a[0] {b 1}");
+    }
+
+    #[test]
+    fn paths() {
+        let old = SuperConf::parse("a {b 1}").unwrap();
+        let new = SuperConf::parse("a {b 2}\nc 3").unwrap();
+        let diff = old.diff(&new);
+
+        assert!(!diff.is_empty());
+        assert_eq!(
+            diff.changes,
+            [
+                Change::Changed {
+                    path: Path::parse("a.b").unwrap(),
+                    old: &SuperValue::Integer(1),
+                    new: &SuperValue::Integer(2),
+                },
+                Change::Added {
+                    path: Path::parse("c").unwrap(),
+                    value: &SuperValue::Integer(3),
+                },
+            ]
+        );
+    }
+}
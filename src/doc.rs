@@ -0,0 +1,1002 @@
+use super::cursor::{scalar, Doc, Items};
+use super::tokens::{Raw, Span, Token, Tokens};
+use super::{
+    unescape, Emit, List, Parse, Path, Segment, SuperConf, SuperError, SuperItem, SuperValue,
+};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+/// Lossless alternative to [SuperConf] which keeps hold of every byte of the
+/// original text, so that a config can be edited and then saved again with
+/// its spacing, ordering and blank lines left untouched
+///
+/// ```
+/// use superconf::{Document, Parse, SuperValue};
+///
+/// let mut doc = Document::parse("port   80\n\ntls {enabled false}\n").unwrap();
+/// doc.set("tls.enabled", &SuperValue::Bool(true)).unwrap();
+///
+/// assert_eq!(doc.to_string(), "port   80\n\ntls {enabled true}\n");
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Document<'a> {
+    items: Seq<'a, ItemNode<'a>>,
+}
+
+/// Pieces of text which were split on separators, with `seps[i]` sitting
+/// between `pieces[i]` and `pieces[i + 1]`
+#[derive(Debug, PartialEq, Clone)]
+struct Seq<'a, T> {
+    pieces: Vec<Piece<'a, T>>,
+    seps: Vec<char>,
+}
+
+/// One node surrounded by its trivia, or just trivia if it's blank
+#[derive(Debug, PartialEq, Clone)]
+struct Piece<'a, T> {
+    lead: Cow<'a, str>,
+    node: Option<T>,
+    trail: Cow<'a, str>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct ItemNode<'a> {
+    /// Key as written, still containing any escapes
+    key: Cow<'a, str>,
+    /// Whitespace between the key and its value, empty if there's no value
+    gap: Cow<'a, str>,
+    value: ValueNode<'a>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum ValueNode<'a> {
+    /// Any value which isn't a list or group, as written
+    Scalar(Cow<'a, str>),
+    List(Box<Seq<'a, ValueNode<'a>>>),
+    Group(Box<Seq<'a, ItemNode<'a>>>),
+}
+
+impl<'a> Parse<'a> for Document<'a> {
+    fn parse(input: &'a str) -> Result<Self, SuperError> {
+        Ok(Self {
+            items: item_seq(&mut Tokens::new(input), 0)?.0,
+        })
+    }
+}
+
+impl<'a> Document<'a> {
+    /// Converts into a standard [SuperConf], giving the same result as
+    /// parsing the original text with it directly
+    pub fn to_conf(&self) -> SuperConf<'_> {
+        SuperConf {
+            items: items_of(&self.items, &mut Doc::new()),
+        }
+    }
+
+    /// Gets the value at `path`, see [SuperConf::get]
+    pub fn get(&self, path: &str) -> Option<SuperValue<'_>> {
+        let path = Path::parse(path).ok()?;
+        Some(value_of(
+            node_at(&self.items, &path.segments)?,
+            &mut Doc::new(),
+        ))
+    }
+
+    /// Gets the comments attached to the item at `path`, which are the lines
+    /// of comments directly above it up until a blank line, with the `#` and
+    /// a single space after it removed
+    pub fn comments(&self, path: &str) -> Option<Vec<&str>> {
+        let path = Path::parse(path).ok()?;
+        let (key, parents) = match path.segments.split_last()? {
+            (Segment::Key(key), parents) => (key, parents),
+            (Segment::Index(_), _) => return None,
+        };
+        let items = match parents {
+            [] => &self.items,
+            _ => match node_at(&self.items, parents)? {
+                ValueNode::Group(items) => &**items,
+                _ => return None,
+            },
+        };
+
+        let ind = item_position(items, key)?;
+        let mut lines: Vec<&str> = comment_lines(&items.pieces[ind].lead).rev().collect();
+
+        for piece in items.pieces[..ind].iter().rev() {
+            let len = lines.len();
+
+            if piece.node.is_none() {
+                lines.extend(comment_lines(&piece.lead).rev());
+            }
+            if lines.len() == len {
+                break;
+            }
+        }
+
+        lines.reverse();
+        Some(lines)
+    }
+
+    /// Replaces the comments attached to the item at `path`, see
+    /// [Document::comments], with new lines of comments
+    pub fn set_comments(&mut self, path: &str, lines: &[&str]) -> Result<(), SuperError> {
+        let path = Path::parse(path)?;
+        let (key, parents) = match path.segments.split_last().unwrap() {
+            (Segment::Key(key), parents) => (key, parents),
+            (Segment::Index(_), _) => return Err(SuperError::PathNotFound),
+        };
+        let items = match parents {
+            [] => &mut self.items,
+            _ => match node_mut(&mut self.items, parents)? {
+                ValueNode::Group(items) => &mut **items,
+                _ => return Err(SuperError::PathNotFound),
+            },
+        };
+
+        let mut ind = item_position(items, key).ok_or(SuperError::PathNotFound)?;
+
+        while ind > 0
+            && items.pieces[ind - 1].node.is_none()
+            && comment_lines(&items.pieces[ind - 1].lead).next().is_some()
+        {
+            remove_at(items, ind - 1);
+            ind -= 1;
+        }
+
+        let indent = String::from(items.pieces[ind].lead.rsplit('\n').next().unwrap());
+        let comments = lines.iter().flat_map(|line| line.split('\n')).map(|line| {
+            let mut comment = indent.clone();
+            comment.push('#');
+            if !line.is_empty() {
+                comment.push(' ');
+                comment.push_str(line);
+            }
+            comment
+        });
+
+        if (ind == 0 && parents.is_empty())
+            || ind.checked_sub(1).map(|prev| items.seps[prev]) == Some('\n')
+        {
+            for comment in comments {
+                items.pieces.insert(
+                    ind,
+                    Piece {
+                        lead: Cow::Owned(comment),
+                        node: None,
+                        trail: Cow::Borrowed(""),
+                    },
+                );
+                items.seps.insert(ind, '\n');
+                ind += 1;
+            }
+            items.pieces[ind].lead = Cow::Owned(indent);
+        } else {
+            let mut lead = String::new();
+            for comment in comments {
+                lead.push_str(&comment);
+                lead.push('\n');
+            }
+            lead.push_str(&indent);
+            items.pieces[ind].lead = Cow::Owned(lead);
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the value at `path` with `value`, leaving all of the text
+    /// around it as it was
+    ///
+    /// If `path` ends in a key which doesn't exist yet inside of an existing
+    /// group, a new item is added to the end of that group and laid out like
+    /// the items before it.
+    pub fn set(&mut self, path: &str, value: &SuperValue) -> Result<(), SuperError> {
+        let path = Path::parse(path)?;
+        let (last, parents) = path.segments.split_last().unwrap();
+        let new = owned_node(value);
+
+        let (items, default_sep) = match parents {
+            [] => (&mut self.items, '\n'),
+            _ => match node_mut(&mut self.items, parents)? {
+                ValueNode::Group(items) => (&mut **items, ','),
+                ValueNode::List(values) => {
+                    return match last {
+                        Segment::Index(index) => set_element(values, *index, new),
+                        Segment::Key(_) => Err(SuperError::PathNotFound),
+                    }
+                }
+                ValueNode::Scalar(_) => return Err(SuperError::PathNotFound),
+            },
+        };
+
+        let key = match last {
+            Segment::Key(key) => key,
+            Segment::Index(_) => return Err(SuperError::PathNotFound),
+        };
+
+        match find_item_mut(items, key) {
+            Some(item) => {
+                item.gap = match (&*item.gap, &new) {
+                    (_, ValueNode::Scalar(text)) if text.is_empty() => Cow::Borrowed(""),
+                    ("", _) => Cow::Borrowed(" "),
+                    _ => core::mem::take(&mut item.gap),
+                };
+                item.value = new;
+            }
+            None => push_item(items, key, new, default_sep),
+        }
+
+        Ok(())
+    }
+
+    /// Removes the item or list element at `path` along with one of the
+    /// separators next to it, returning if anything was removed
+    pub fn remove(&mut self, path: &str) -> bool {
+        let path = match Path::parse(path) {
+            Ok(path) => path,
+            Err(_) => return false,
+        };
+        let (last, parents) = path.segments.split_last().unwrap();
+
+        let parent = match parents {
+            [] => None,
+            _ => match node_mut(&mut self.items, parents) {
+                Ok(node) => Some(node),
+                Err(_) => return false,
+            },
+        };
+
+        match (parent, last) {
+            (None, Segment::Key(key)) => remove_item(&mut self.items, key),
+            (Some(ValueNode::Group(items)), Segment::Key(key)) => remove_item(items, key),
+            (Some(ValueNode::List(values)), Segment::Index(index)) => {
+                let found = element_positions(values).nth(*index);
+                match found {
+                    Some(ind) => {
+                        remove_at(values, ind);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    fn write<W: Write>(&self, w: &mut W) -> fmt::Result {
+        self.items.write(w)
+    }
+}
+
+impl fmt::Display for Document<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f)
+    }
+}
+
+impl<'a, T> Seq<'a, T> {
+    fn nodes(&self) -> impl Iterator<Item = &T> {
+        self.pieces.iter().filter_map(|piece| piece.node.as_ref())
+    }
+}
+
+impl<'a, T: Node<'a>> Seq<'a, T> {
+    fn write<W: Write>(&self, w: &mut W) -> fmt::Result {
+        for (ind, piece) in self.pieces.iter().enumerate() {
+            if ind != 0 {
+                w.write_char(self.seps[ind - 1])?;
+            }
+
+            w.write_str(&piece.lead)?;
+            if let Some(node) = &piece.node {
+                node.write(w)?;
+            }
+            w.write_str(&piece.trail)?;
+        }
+
+        Ok(())
+    }
+
+    fn into_owned(self) -> Seq<'static, T::Owned> {
+        Seq {
+            pieces: self
+                .pieces
+                .into_iter()
+                .map(|piece| Piece {
+                    lead: owned(piece.lead),
+                    node: piece.node.map(T::into_owned),
+                    trail: owned(piece.trail),
+                })
+                .collect(),
+            seps: self.seps,
+        }
+    }
+}
+
+/// Shared behaviour of the nodes which can be put inside of a [Seq]
+trait Node<'a> {
+    type Owned;
+
+    fn write<W: Write>(&self, w: &mut W) -> fmt::Result;
+
+    fn into_owned(self) -> Self::Owned;
+}
+
+impl<'a> Node<'a> for ItemNode<'a> {
+    type Owned = ItemNode<'static>;
+
+    fn write<W: Write>(&self, w: &mut W) -> fmt::Result {
+        w.write_str(&self.key)?;
+        w.write_str(&self.gap)?;
+        self.value.write(w)
+    }
+
+    fn into_owned(self) -> ItemNode<'static> {
+        ItemNode {
+            key: owned(self.key),
+            gap: owned(self.gap),
+            value: self.value.into_owned(),
+        }
+    }
+}
+
+impl<'a> Node<'a> for ValueNode<'a> {
+    type Owned = ValueNode<'static>;
+
+    fn write<W: Write>(&self, w: &mut W) -> fmt::Result {
+        match self {
+            Self::Scalar(text) => w.write_str(text),
+            Self::List(values) => {
+                w.write_char('[')?;
+                values.write(w)?;
+                w.write_char(']')
+            }
+            Self::Group(items) => {
+                w.write_char('{')?;
+                items.write(w)?;
+                w.write_char('}')
+            }
+        }
+    }
+
+    fn into_owned(self) -> ValueNode<'static> {
+        match self {
+            Self::Scalar(text) => ValueNode::Scalar(owned(text)),
+            Self::List(values) => ValueNode::List(Box::new(values.into_owned())),
+            Self::Group(items) => ValueNode::Group(Box::new(items.into_owned())),
+        }
+    }
+}
+
+impl ItemNode<'_> {
+    fn key_is(&self, key: &str) -> bool {
+        unescape(&self.key) == key
+    }
+}
+
+fn owned(text: Cow<'_, str>) -> Cow<'static, str> {
+    Cow::Owned(text.into_owned())
+}
+
+/// [Seq] which is being built out of tokens, one piece at a time
+struct Pieces<'a, T> {
+    input: &'a str,
+    seq: Seq<'a, T>,
+    /// Where the current piece starts
+    start: usize,
+    /// Node of the current piece, along with where it was written
+    node: Option<(T, Span)>,
+}
+
+impl<'a, T> Pieces<'a, T> {
+    fn new(input: &'a str, start: usize) -> Self {
+        Self {
+            input,
+            seq: Seq {
+                pieces: Vec::new(),
+                seps: Vec::new(),
+            },
+            start,
+            node: None,
+        }
+    }
+
+    /// Ends the current piece at the separator `sep`, which was written at
+    /// `span`, starting the next one after it
+    fn split(&mut self, sep: char, span: Span) {
+        self.end(span.start);
+        self.seq.seps.push(sep);
+        self.start = span.end;
+    }
+
+    fn finish(mut self, end: usize) -> Seq<'a, T> {
+        self.end(end);
+        self.seq
+    }
+
+    /// Ends the current piece at `end`, where anything around its node is
+    /// trivia
+    fn end(&mut self, end: usize) {
+        let input = self.input;
+        let piece = match self.node.take() {
+            Some((node, span)) => Piece {
+                lead: Cow::Borrowed(&input[self.start..span.start]),
+                node: Some(node),
+                trail: Cow::Borrowed(&input[span.end..end]),
+            },
+            None => Piece {
+                lead: Cow::Borrowed(&input[self.start..end]),
+                node: None,
+                trail: Cow::Borrowed(""),
+            },
+        };
+        self.seq.pieces.push(piece);
+    }
+}
+
+/// Builds the items which `tokens` are up to, which start at `start`, until
+/// the end of the group they're in or of the text, giving where they ended
+fn item_seq<'a>(
+    tokens: &mut Tokens<'a>,
+    start: usize,
+) -> Result<(Seq<'a, ItemNode<'a>>, usize), SuperError> {
+    let input = tokens.input();
+    let mut pieces = Pieces::new(input, start);
+
+    while let Some(found) = tokens.next() {
+        let (token, span) = found?;
+        match token {
+            Token::Key(raw) => {
+                let Some(first) = skip_trivia(tokens) else {
+                    unreachable!()
+                };
+                let (value, value_span) = value_node(tokens, first?)?;
+                let node = ItemNode {
+                    key: Cow::Borrowed(raw.as_str()),
+                    gap: Cow::Borrowed(&input[span.end..value_span.start]),
+                    value,
+                };
+                pieces.node = Some((
+                    node,
+                    Span {
+                        start: span.start,
+                        end: value_span.end,
+                    },
+                ));
+            }
+            Token::Newline => pieces.split('\n', span),
+            Token::Comma => pieces.split(',', span),
+            Token::GroupEnd => return Ok((pieces.finish(span.start), span.end)),
+            _ => (),
+        }
+    }
+
+    Ok((pieces.finish(input.len()), input.len()))
+}
+
+/// Builds the elements of the list which `tokens` are up to, which start at
+/// `start`, giving where the list ended
+fn list_seq<'a>(
+    tokens: &mut Tokens<'a>,
+    start: usize,
+) -> Result<(Seq<'a, ValueNode<'a>>, usize), SuperError> {
+    let mut pieces = Pieces::new(tokens.input(), start);
+
+    // a list which isn't closed is an error before the tokens run out
+    while let Some(found) = tokens.next() {
+        let (token, span) = found?;
+        match token {
+            Token::Scalar(_) | Token::ListStart | Token::GroupStart => {
+                pieces.node = Some(value_node(tokens, (token, span))?);
+            }
+            Token::Comma => pieces.split(',', span),
+            Token::ListEnd => return Ok((pieces.finish(span.start), span.end)),
+            _ => (),
+        }
+    }
+
+    unreachable!()
+}
+
+/// Builds the value which starts with `first`, giving where it was written
+///
+/// A list or group with text after it is kept as a scalar of all of it, as
+/// it's a name.
+fn value_node<'a>(
+    tokens: &mut Tokens<'a>,
+    (first, span): (Token<'a>, Span),
+) -> Result<(ValueNode<'a>, Span), SuperError> {
+    let input = tokens.input();
+    let (node, end) = match first {
+        Token::ListStart => {
+            let (values, end) = list_seq(tokens, span.end)?;
+            (ValueNode::List(Box::new(values)), end)
+        }
+        Token::GroupStart => {
+            let (items, end) = item_seq(tokens, span.end)?;
+            (ValueNode::Group(Box::new(items)), end)
+        }
+        _ => {
+            return Ok((
+                ValueNode::Scalar(Cow::Borrowed(&input[span.start..span.end])),
+                span,
+            ))
+        }
+    };
+
+    let mut ahead = tokens.clone();
+    let span = match skip_trivia(&mut ahead) {
+        Some(Ok((Token::Trailing(_), trailing))) => {
+            *tokens = ahead;
+            Span {
+                start: span.start,
+                end: trailing.end,
+            }
+        }
+        _ => {
+            let span = Span {
+                start: span.start,
+                end,
+            };
+            return Ok((node, span));
+        }
+    };
+    Ok((
+        ValueNode::Scalar(Cow::Borrowed(&input[span.start..span.end])),
+        span,
+    ))
+}
+
+/// Gives the next token which isn't a comment or a newline
+fn skip_trivia<'a>(tokens: &mut Tokens<'a>) -> Option<Result<(Token<'a>, Span), SuperError>> {
+    tokens.find(|found| !matches!(found, Ok((Token::Comment(_) | Token::Newline, _))))
+}
+
+/// Emits `value` and parses it back into a node which owns its text
+fn owned_node(value: &SuperValue) -> ValueNode<'static> {
+    let text = value.emit();
+    let mut tokens = Tokens::value(&text);
+    let first = skip_trivia(&mut tokens).expect("values always have a token");
+
+    first
+        .and_then(|first| value_node(&mut tokens, first))
+        .expect("emitted values are valid")
+        .0
+        .into_owned()
+}
+
+/// Indices of the pieces in a list which are actually elements, as a blank
+/// last piece is only trivia
+fn element_positions<'b>(values: &'b Seq<ValueNode>) -> impl Iterator<Item = usize> + 'b {
+    values
+        .pieces
+        .iter()
+        .enumerate()
+        .filter(|(_, piece)| piece.node.is_some())
+        .map(|(ind, _)| ind)
+}
+
+fn element<'b, 'a>(values: &'b Seq<'a, ValueNode<'a>>, index: usize) -> Option<&'b ValueNode<'a>> {
+    let ind = element_positions(values).nth(index)?;
+    values.pieces[ind].node.as_ref()
+}
+
+fn element_mut<'b, 'a>(
+    values: &'b mut Seq<'a, ValueNode<'a>>,
+    index: usize,
+) -> Option<&'b mut ValueNode<'a>> {
+    let ind = element_positions(values).nth(index)?;
+    values.pieces[ind].node.as_mut()
+}
+
+/// Replaces the element at `index`, adding a comma after it if it's a final
+/// nothing as it'd be skipped when parsing otherwise, the same as [Emit]
+fn set_element<'a>(
+    values: &mut Seq<'a, ValueNode<'a>>,
+    index: usize,
+    new: ValueNode<'a>,
+) -> Result<(), SuperError> {
+    let ind = element_positions(values)
+        .nth(index)
+        .ok_or(SuperError::PathNotFound)?;
+    let blank = matches!(&new, ValueNode::Scalar(text) if text.is_empty());
+    values.pieces[ind].node = Some(new);
+
+    if blank && ind + 1 == values.pieces.len() {
+        values.seps.push(',');
+        values.pieces.push(Piece {
+            lead: Cow::Borrowed(""),
+            node: None,
+            trail: Cow::Borrowed(""),
+        });
+    }
+    Ok(())
+}
+
+fn find_item<'b, 'a>(items: &'b Seq<'a, ItemNode<'a>>, key: &str) -> Option<&'b ItemNode<'a>> {
+    items.nodes().find(|node| node.key_is(key))
+}
+
+fn item_position(items: &Seq<ItemNode>, key: &str) -> Option<usize> {
+    items
+        .pieces
+        .iter()
+        .position(|piece| piece.node.as_ref().is_some_and(|node| node.key_is(key)))
+}
+
+/// Text of each comment inside of some trivia, which is every line in it
+/// starting with a `#` once indentation is ignored
+fn comment_lines(trivia: &str) -> impl DoubleEndedIterator<Item = &str> {
+    trivia.split('\n').filter_map(|line| {
+        let comment = line.trim().strip_prefix('#')?;
+        Some(comment.strip_prefix(' ').unwrap_or(comment))
+    })
+}
+
+fn find_item_mut<'b, 'a>(
+    items: &'b mut Seq<'a, ItemNode<'a>>,
+    key: &str,
+) -> Option<&'b mut ItemNode<'a>> {
+    items
+        .pieces
+        .iter_mut()
+        .filter_map(|piece| piece.node.as_mut())
+        .find(|node| node.key_is(key))
+}
+
+fn node_at<'b, 'a>(
+    items: &'b Seq<'a, ItemNode<'a>>,
+    segments: &[Segment],
+) -> Option<&'b ValueNode<'a>> {
+    let (first, rest) = segments.split_first()?;
+    let mut node = match first {
+        Segment::Key(key) => &find_item(items, key)?.value,
+        Segment::Index(_) => return None,
+    };
+
+    for segment in rest {
+        node = match (segment, node) {
+            (Segment::Key(key), ValueNode::Group(items)) => &find_item(items, key)?.value,
+            (Segment::Index(index), ValueNode::List(values)) => element(values, *index)?,
+            _ => return None,
+        };
+    }
+
+    Some(node)
+}
+
+fn node_mut<'b, 'a>(
+    items: &'b mut Seq<'a, ItemNode<'a>>,
+    segments: &[Segment],
+) -> Result<&'b mut ValueNode<'a>, SuperError> {
+    let (first, rest) = segments.split_first().ok_or(SuperError::PathNotFound)?;
+    let mut node = match first {
+        Segment::Key(key) => {
+            &mut find_item_mut(items, key)
+                .ok_or(SuperError::PathNotFound)?
+                .value
+        }
+        Segment::Index(_) => return Err(SuperError::PathNotFound),
+    };
+
+    for segment in rest {
+        node = match (segment, node) {
+            (Segment::Key(key), ValueNode::Group(items)) => {
+                &mut find_item_mut(items, key)
+                    .ok_or(SuperError::PathNotFound)?
+                    .value
+            }
+            (Segment::Index(index), ValueNode::List(values)) => {
+                element_mut(values, *index).ok_or(SuperError::PathNotFound)?
+            }
+            _ => return Err(SuperError::PathNotFound),
+        };
+    }
+
+    Ok(node)
+}
+
+/// Adds a new item after the last existing one, copying the separator and
+/// indentation used before it or falling back to `default_sep` if there was
+/// only one item before
+fn push_item<'a>(
+    items: &mut Seq<'a, ItemNode<'a>>,
+    key: &str,
+    value: ValueNode<'static>,
+    default_sep: char,
+) {
+    let mut key_text = String::new();
+    SuperItem {
+        key: Cow::Borrowed(key),
+        value: SuperValue::Nothing,
+        meta: None,
+    }
+    .emit_to(&mut key_text)
+    .unwrap();
+
+    let gap = match &value {
+        ValueNode::Scalar(text) if text.is_empty() => "",
+        _ => " ",
+    };
+    let node = ItemNode {
+        key: Cow::Owned(key_text),
+        gap: Cow::Borrowed(gap),
+        value,
+    };
+
+    let last = items.pieces.iter().rposition(|piece| piece.node.is_some());
+
+    match last {
+        None => {
+            items.pieces.clear();
+            items.seps.clear();
+            items.pieces.push(Piece {
+                lead: Cow::Borrowed(""),
+                node: Some(node),
+                trail: Cow::Borrowed(""),
+            });
+        }
+        Some(last) => {
+            let (sep, lead) = match last.checked_sub(1).map(|prev| items.seps[prev]) {
+                Some(sep) => (sep, items.pieces[last].lead.clone()),
+                None if default_sep == '\n' || items.seps.contains(&'\n') => {
+                    ('\n', items.pieces[last].lead.clone())
+                }
+                None => (',', Cow::Borrowed(" ")),
+            };
+
+            items.pieces.insert(
+                last + 1,
+                Piece {
+                    lead,
+                    node: Some(node),
+                    trail: Cow::Borrowed(""),
+                },
+            );
+            items.seps.insert(last, sep);
+        }
+    }
+}
+
+fn remove_item(items: &mut Seq<ItemNode>, key: &str) -> bool {
+    match item_position(items, key) {
+        Some(ind) => {
+            remove_at(items, ind);
+            true
+        }
+        None => false,
+    }
+}
+
+fn remove_at<T>(items: &mut Seq<T>, ind: usize) {
+    items.pieces.remove(ind);
+
+    if ind < items.seps.len() {
+        items.seps.remove(ind);
+    } else if ind != 0 {
+        items.seps.remove(ind - 1);
+    }
+}
+
+/// Gives the items with their documentation and attributes, which are found
+/// by telling `doc` about the tokens of everything in the order it was
+/// written, the same as when parsing
+fn items_of<'b>(items: &'b Seq<ItemNode>, doc: &mut Doc<'b>) -> Vec<SuperItem<'b>> {
+    let mut found = Items::new(Vec::new());
+
+    for (ind, piece) in items.pieces.iter().enumerate() {
+        if ind != 0 {
+            doc.next(&match items.seps[ind - 1] {
+                '\n' => Token::Newline,
+                _ => Token::Comma,
+            });
+        }
+
+        trivia(doc, &piece.lead);
+        if let Some(node) = &piece.node {
+            let raw = Raw::new(&node.key);
+            let meta = doc.next(&Token::Key(raw));
+            trivia(doc, &node.gap);
+            let item = SuperItem {
+                key: unescape(&node.key),
+                value: value_of(&node.value, doc),
+                meta,
+            };
+            found.push(item, raw);
+        }
+        trivia(doc, &piece.trail);
+    }
+
+    found.finish()
+}
+
+fn value_of<'b>(node: &'b ValueNode, doc: &mut Doc<'b>) -> SuperValue<'b> {
+    match node {
+        ValueNode::Scalar(text) => {
+            let raw = Raw::new(text);
+            doc.next(&Token::Scalar(raw));
+            scalar(raw)
+        }
+        ValueNode::List(values) => {
+            let mut found = List::new();
+            doc.next(&Token::ListStart);
+
+            for (ind, piece) in values.pieces.iter().enumerate() {
+                if ind != 0 {
+                    doc.next(&Token::Comma);
+                }
+                trivia(doc, &piece.lead);
+                found.extend(piece.node.as_ref().map(|node| value_of(node, doc)));
+                trivia(doc, &piece.trail);
+            }
+
+            doc.next(&Token::ListEnd);
+            SuperValue::List(Box::new(found))
+        }
+        ValueNode::Group(items) => {
+            doc.next(&Token::GroupStart);
+            let items = items_of(items, doc);
+            doc.next(&Token::GroupEnd);
+            SuperValue::group(items)
+        }
+    }
+}
+
+/// Tells `doc` about the comments and newlines in some trivia
+fn trivia<'b>(doc: &mut Doc<'b>, trivia: &'b str) {
+    for (token, _) in Tokens::new(trivia).flatten() {
+        doc.next(&token);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    const INPUT: &str = "port   80\n\n  hosts [a,  b ,c,]\ntls {\n    enabled false\n    cert\n}\n\nescaped\\ key  x\\,y  \n";
+
+    #[test]
+    fn lossless() {
+        for input in [
+            INPUT,
+            "",
+            "\n\n",
+            "a {}\nb []\nc [ , ]",
+            "x {a 1,b [1,{c}]}  ",
+            "x {a} {b}",
+            "x [1] [2]\ny [[1] # c\n z, {a} b]",
+            "## a\n##\n@b(c) @d\na 1\n## e\n\nf {\n  ## g\n  @h\n  g [{\n## i\n  i 3}]\n}\n@j",
+        ] {
+            let doc = Document::parse(input).unwrap();
+
+            assert_eq!(doc.to_string(), input);
+            assert_eq!(doc.to_conf(), SuperConf::parse(input).unwrap());
+        }
+        for input in ["a {b]", "a [1", "]"] {
+            assert_eq!(
+                Document::parse(input),
+                Err(SuperError::Unbalanced),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn set_existing() {
+        let mut doc = Document::parse(INPUT).unwrap();
+
+        doc.set("tls.enabled", &SuperValue::Bool(true)).unwrap();
+        doc.set("hosts[1]", &SuperValue::Name("new host".into()))
+            .unwrap();
+        doc.set("tls.cert", &SuperValue::Name("a.pem".into()))
+            .unwrap();
+        doc.set("port", &SuperValue::Nothing).unwrap();
+
+        assert_eq!(
+            doc.to_string(),
+            "port\n\n  hosts [a,  new host ,c,]\ntls {\n    enabled true\n    cert a.pem\n}\n\nescaped\\ key  x\\,y  \n"
+        );
+        assert_eq!(
+            doc.get("hosts[1]"),
+            Some(SuperValue::Name("new host".into()))
+        );
+        assert_eq!(doc.get("escaped key"), Some(SuperValue::Name("x,y".into())));
+
+        let mut doc = Document::parse("a [x, y]").unwrap();
+        doc.set("a[1]", &SuperValue::Nothing).unwrap();
+        doc.set("a[1]", &SuperValue::Nothing).unwrap();
+        assert_eq!(doc.to_string(), "a [x, ,]");
+        assert_eq!(doc.to_conf(), SuperConf::parse("a [x, ,]").unwrap());
+        assert_eq!(doc.get("a[1]"), Some(SuperValue::Nothing));
+    }
+
+    #[test]
+    fn set_new() {
+        let mut doc = Document::parse(INPUT).unwrap();
+
+        doc.set("tls.key", &SuperValue::Name("b.pem".into()))
+            .unwrap();
+        doc.set("new key", &SuperValue::Integer(1)).unwrap();
+        assert!(doc.to_string().contains("    cert\n    key b.pem\n}"));
+        assert!(doc.to_string().ends_with("x\\,y  \nnew\\ key 1\n"));
+
+        let mut doc = Document::parse("a {x 1, y 2}\nb {}").unwrap();
+        doc.set("a.z", &SuperValue::Integer(3)).unwrap();
+        doc.set("b.w", &SuperValue::group(alloc::vec![])).unwrap();
+        doc.set("b.w.v", &SuperValue::Bool(true)).unwrap();
+        assert_eq!(doc.to_string(), "a {x 1, y 2, z 3}\nb {w {v true}}");
+
+        let mut doc = Document::parse("a 1").unwrap();
+        doc.set("@x", &SuperValue::Nothing).unwrap();
+        doc.set("b", &SuperValue::Integer(2)).unwrap();
+        assert_eq!(doc.to_string(), "a 1\n\\@x\nb 2");
+        assert_eq!(doc.to_conf().items[2].attrs(), []);
+
+        assert_eq!(
+            doc.set("a.x.y", &SuperValue::Nothing),
+            Err(SuperError::PathNotFound)
+        );
+        assert_eq!(
+            doc.set("c.d", &SuperValue::Nothing),
+            Err(SuperError::PathNotFound)
+        );
+    }
+
+    const COMMENTED: &str = "# the port to listen on\n#\n#   padded\nport 80 # trailing, [not a list\n\n# unattached\n\ntls {\n    # inline {\n    enabled true\n}\nlist [a, # first\n    b]\n";
+
+    #[test]
+    fn comments() {
+        let doc = Document::parse(COMMENTED).unwrap();
+
+        assert_eq!(doc.to_string(), COMMENTED);
+        assert_eq!(doc.to_conf(), SuperConf::parse(COMMENTED).unwrap());
+        assert_eq!(
+            doc.comments("port"),
+            Some(alloc::vec!["the port to listen on", "", "  padded"])
+        );
+        assert_eq!(doc.comments("tls"), Some(alloc::vec![]));
+        assert_eq!(doc.comments("tls.enabled"), Some(alloc::vec!["inline {"]));
+        assert_eq!(doc.comments("missing"), None);
+        assert_eq!(doc.get("list[1]"), Some(SuperValue::Name("b".into())));
+    }
+
+    #[test]
+    fn set_comments() {
+        let mut doc = Document::parse(COMMENTED).unwrap();
+
+        doc.set_comments("port", &["new"]).unwrap();
+        doc.set_comments("tls.enabled", &["one", "two"]).unwrap();
+        doc.set_comments("tls", &["group"]).unwrap();
+        doc.set("port", &SuperValue::Integer(8080)).unwrap();
+
+        assert_eq!(
+            doc.to_string(),
+            "# new\nport 8080 # trailing, [not a list\n\n# unattached\n\n# group\ntls {\n    # one\n    # two\n    enabled true\n}\nlist [a, # first\n    b]\n"
+        );
+
+        let mut doc = Document::parse("a {x 1, y 2}").unwrap();
+        doc.set_comments("a.y", &["why"]).unwrap();
+        assert_eq!(doc.comments("a.y"), Some(alloc::vec!["why"]));
+        assert_eq!(doc.to_conf(), SuperConf::parse("a {x 1, y 2}").unwrap());
+    }
+
+    #[test]
+    fn remove() {
+        let mut doc = Document::parse(INPUT).unwrap();
+
+        assert!(doc.remove("tls.cert"));
+        assert!(doc.remove("hosts[0]"));
+        assert!(doc.remove("port"));
+        assert!(!doc.remove("port"));
+        assert!(!doc.remove("hosts[2]"));
+
+        assert_eq!(
+            doc.to_string(),
+            "\n  hosts [  b ,c,]\ntls {\n    enabled false\n}\n\nescaped\\ key  x\\,y  \n"
+        );
+    }
+}
@@ -0,0 +1,1018 @@
+use super::bytes::literal;
+use super::{num_or_name, Meta, Path, SuperConf, SuperItem, SuperValue};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+/// Conversion of parsed nodes back into superconf text, which is the inverse
+/// of [Parse](crate::Parse) so that emitting and then parsing gives an equal
+/// node back
+pub trait Emit {
+    /// Streams text laid out according to the given [EmitOptions] into any
+    /// [Write], so that no intermediate [String] has to be built
+    fn emit_to_with<W: Write>(&self, w: &mut W, options: &EmitOptions) -> fmt::Result;
+
+    /// Streams text with the [default](EmitOptions::default) layout into any
+    /// [Write], such as a fixed-size buffer on `no_std` targets
+    fn emit_to<W: Write>(&self, w: &mut W) -> fmt::Result {
+        self.emit_to_with(w, &EmitOptions::default())
+    }
+
+    /// Writes text with the [default](EmitOptions::default) layout into the
+    /// start of `buf`, returning how many bytes were written
+    ///
+    /// If `buf` is too small then [EmitError::BufferTooSmall] gives how many
+    /// bytes would've been needed, so that a larger buffer can be tried.
+    fn emit_into(&self, buf: &mut [u8]) -> Result<usize, EmitError> {
+        let mut slice = Slice { buf, len: 0 };
+        self.emit_to(&mut slice).map_err(|_| EmitError::Formatter)?;
+
+        match slice.len <= slice.buf.len() {
+            true => Ok(slice.len),
+            false => Err(EmitError::BufferTooSmall {
+                required: slice.len,
+            }),
+        }
+    }
+
+    /// Emits text laid out according to the given [EmitOptions]
+    fn emit_with(&self, options: &EmitOptions) -> String {
+        let mut output = String::new();
+        self.emit_to_with(&mut output, options).unwrap();
+        output
+    }
+
+    /// Emits text with the [default](EmitOptions::default) layout, which keeps
+    /// every group and list on one line unless an item inside of it has
+    /// documentation or attributes, which are written on the lines above it
+    fn emit(&self) -> String {
+        self.emit_with(&EmitOptions::default())
+    }
+
+    /// Streams text with the [default](EmitOptions::default) layout into an
+    /// [std::io::Write] such as a file or socket
+    #[cfg(feature = "std")]
+    fn emit_to_writer<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        let mut adapter = IoAdapter {
+            inner: w,
+            error: None,
+        };
+
+        match self.emit_to(&mut adapter) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(adapter
+                .error
+                .unwrap_or_else(|| std::io::Error::other("formatter error"))),
+        }
+    }
+
+    /// Saves text with the [default](EmitOptions::default) layout to the file
+    /// at `path`, creating or truncating it as needed
+    #[cfg(feature = "std")]
+    fn write_to_file<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.emit_to_writer(&mut file)?;
+        std::io::Write::flush(&mut file)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EmitError {
+    /// When the buffer given to [Emit::emit_into] was too small, holding how
+    /// many bytes it needed to be in total
+    BufferTooSmall { required: usize },
+    /// When formatting failed for another reason, which only happens if a
+    /// [Display](fmt::Display) implementation errors
+    Formatter,
+}
+
+/// Checks if any item inside of `value` has documentation or attributes
+fn has_meta(value: &SuperValue) -> bool {
+    match value {
+        SuperValue::List(values) => values.iter().any(has_meta),
+        SuperValue::Group(items) => items
+            .iter()
+            .any(|item| item.meta.is_some() || has_meta(&item.value)),
+        _ => false,
+    }
+}
+
+/// Writes into a byte buffer, counting how long it would need to be
+/// instead of failing once it's full
+struct Slice<'b> {
+    buf: &'b mut [u8],
+    len: usize,
+}
+
+impl Write for Slice<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+
+        if let Some(dest) = self.buf.get_mut(self.len..end) {
+            dest.copy_from_slice(s.as_bytes());
+        }
+
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Keeps track of how far into the current line the writer is
+struct Column<'w, W: Write> {
+    inner: &'w mut W,
+    column: usize,
+}
+
+impl<W: Write> Write for Column<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.column = match s.rfind('\n') {
+            Some(ind) => s[ind + 1..].chars().count(),
+            None => self.column + s.chars().count(),
+        };
+        self.inner.write_str(s)
+    }
+}
+
+/// Counts the characters written to it, discarding them
+struct Counter(usize);
+
+impl Write for Counter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.chars().count();
+        Ok(())
+    }
+}
+
+/// Bridges [Write] onto [std::io::Write], keeping hold of the io error which
+/// [fmt::Error] has no room for
+#[cfg(feature = "std")]
+struct IoAdapter<'w, W: std::io::Write> {
+    inner: &'w mut W,
+    error: Option<std::io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+impl Emit for SuperValue<'_> {
+    fn emit_to_with<W: Write>(&self, w: &mut W, options: &EmitOptions) -> fmt::Result {
+        Emitter::new(w, options).value(self)
+    }
+}
+
+impl Emit for SuperItem<'_> {
+    fn emit_to_with<W: Write>(&self, w: &mut W, options: &EmitOptions) -> fmt::Result {
+        Emitter::new(w, options).item(self)
+    }
+}
+
+impl Emit for SuperConf<'_> {
+    fn emit_to_with<W: Write>(&self, w: &mut W, options: &EmitOptions) -> fmt::Result {
+        Emitter::new(w, options).conf(self)
+    }
+}
+
+impl fmt::Display for SuperValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.emit_to(f)
+    }
+}
+
+impl fmt::Display for SuperItem<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.emit_to(f)
+    }
+}
+
+impl fmt::Display for SuperConf<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.emit_to(f)
+    }
+}
+
+/// Layout used when emitting, which never changes what the text parses into
+#[derive(Debug, Clone)]
+pub struct EmitOptions {
+    /// Number of spaces used for each level of indentation
+    pub indent: usize,
+    /// Writes the items of non-empty groups on their own lines instead of
+    /// keeping the whole group inline
+    pub expand_groups: bool,
+    /// Puts a space after each comma separating inline items or elements
+    pub space_after_comma: bool,
+    /// Puts spaces just inside the brackets of non-empty inline groups and
+    /// lists, like `{ a 1 }`
+    pub pad_brackets: bool,
+    /// Lists with more elements than this are wrapped with each element on
+    /// its own line
+    pub list_wrap: usize,
+    /// Writes the items of every group, and of the config itself, sorted by
+    /// key; repeated keys keep their original order between each other
+    pub sort_keys: bool,
+    /// How the digits of floats are written
+    pub float_format: FloatFormat,
+    /// Non-empty lists and groups which would go past this column if written
+    /// inline are expanded onto multiple lines instead
+    pub max_width: Option<usize>,
+    /// Picks the layout of the list or group at a given path, which takes
+    /// priority over the other options apart from everything inside of an
+    /// inline list or group always being inline too
+    pub layout_for: Option<fn(&Path) -> Option<Layout>>,
+    /// Replaces every value whose path this returns true for with
+    /// `<redacted>`, so that secrets don't leak into logs or bug reports
+    pub redact: Option<fn(&Path) -> bool>,
+    /// Also replaces the value of every item with a `@secret`
+    /// [attribute](crate::Attr) with `<redacted>`
+    pub redact_secrets: bool,
+}
+
+/// Whether a list or group is written on one line or across many
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Layout {
+    /// Written on one line, like `{a 1, b 2}`
+    Inline,
+    /// Written with each of its children on their own line
+    Expanded,
+}
+
+/// Precision used when emitting floats, where every format makes sure that
+/// the float parses back into the exact same value
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FloatFormat {
+    /// Fewest digits which still parse back into the same value, using an
+    /// exponent for very large or small values like `1e300`
+    Shortest,
+    /// At least this many decimal places (and always at least one), with more
+    /// being written if they're needed to not lose any precision, so `1.5`
+    /// becomes `1.500` with three decimals but `0.1 + 0.2` stays exact
+    Decimals(usize),
+}
+
+impl Default for EmitOptions {
+    fn default() -> Self {
+        Self {
+            indent: 4,
+            expand_groups: false,
+            space_after_comma: true,
+            pad_brackets: false,
+            list_wrap: usize::MAX,
+            sort_keys: false,
+            float_format: FloatFormat::Shortest,
+            max_width: None,
+            layout_for: None,
+            redact: None,
+            redact_secrets: false,
+        }
+    }
+}
+
+impl EmitOptions {
+    /// Deterministic layout with sorted keys and fixed spacing, so that equal
+    /// configs always emit identical, diff-friendly text
+    pub fn canonical() -> Self {
+        Self {
+            sort_keys: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// Writes nodes out to any [Write], stopping at the first error it gives
+pub(crate) struct Emitter<'w, W: Write> {
+    out: Column<'w, W>,
+    options: &'w EmitOptions,
+    depth: usize,
+    /// Keeps everything inline, used when measuring how wide a value is
+    inline: bool,
+    /// Path of the value currently being written, only tracked if one of the
+    /// options needs it
+    path: Option<Path<'static>>,
+}
+
+impl<'w, W: Write> Emitter<'w, W> {
+    pub(crate) fn new(out: &'w mut W, options: &'w EmitOptions) -> Self {
+        Self {
+            out: Column {
+                inner: out,
+                column: 0,
+            },
+            options,
+            depth: 0,
+            inline: false,
+            path: match (options.layout_for, options.redact) {
+                (None, None) => None,
+                _ => Some(Path::new()),
+            },
+        }
+    }
+
+    fn conf(&mut self, conf: &SuperConf) -> fmt::Result {
+        for item in self.ordered(&conf.items) {
+            self.item(item)?;
+            self.out.write_char('\n')?;
+        }
+
+        Ok(())
+    }
+
+    fn item(&mut self, item: &SuperItem) -> fmt::Result {
+        // anything with metadata is never inline, apart from when measuring
+        if let Some(meta) = item.meta.as_deref().filter(|_| !self.inline) {
+            self.meta(meta)?;
+        }
+        self.key(&item.key)?;
+
+        if let Some(path) = &mut self.path {
+            path.push_key(String::from(&*item.key));
+        }
+
+        let result = match &item.value {
+            SuperValue::Nothing => Ok(()),
+            _ if self.options.redact_secrets && item.attr("secret").is_some() => {
+                self.out.write_str(" <redacted>")
+            }
+            value => {
+                self.out.write_char(' ')?;
+                self.child(value)
+            }
+        };
+
+        self.path.as_mut().map(Path::pop);
+        result
+    }
+
+    /// Writes the documentation and then the attributes of an item on the
+    /// lines above it
+    fn meta(&mut self, meta: &Meta) -> fmt::Result {
+        for line in meta.doc.iter().flat_map(|doc| doc.split('\n')) {
+            match line {
+                "" => self.out.write_str("##")?,
+                line => write!(self.out, "## {}", line)?,
+            }
+            self.newline()?;
+        }
+
+        for (ind, attr) in meta.attrs.iter().enumerate() {
+            if ind != 0 {
+                self.out.write_char(' ')?;
+            }
+            write!(self.out, "@{}", attr.name)?;
+            if let Some(arg) = &attr.arg {
+                write!(self.out, "({})", arg)?;
+            }
+        }
+        if !meta.attrs.is_empty() {
+            self.newline()?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn value(&mut self, value: &SuperValue) -> fmt::Result {
+        match value {
+            SuperValue::Nothing => Ok(()),
+            SuperValue::Name(name) => self.name(name),
+            SuperValue::Bytes(data) => write!(self.out, "b64\"{}\"", data),
+            SuperValue::Bool(boolean) => write!(self.out, "{}", boolean),
+            SuperValue::Integer(integer) => write!(self.out, "{}", integer),
+            SuperValue::Float(float) => self.float(*float),
+            SuperValue::List(values) if self.expanded(value)? => {
+                self.out.write_char('[')?;
+                self.depth += 1;
+
+                for (ind, value) in values.iter().enumerate() {
+                    self.newline()?;
+                    self.element(ind, value)?;
+                    self.out.write_char(',')?;
+                }
+
+                self.depth -= 1;
+                self.newline()?;
+                self.out.write_char(']')
+            }
+            SuperValue::List(values) => {
+                self.out.write_char('[')?;
+                self.pad(values.is_empty())?;
+
+                // children of something inline are always inline too
+                let inline = core::mem::replace(&mut self.inline, true);
+                for (ind, value) in values.iter().enumerate() {
+                    if ind != 0 {
+                        self.comma()?;
+                    }
+                    self.element(ind, value)?;
+                }
+                self.inline = inline;
+
+                // a trailing blank element is skipped when parsing, so keep
+                // the final nothing by adding its comma explicitly
+                if let Some(SuperValue::Nothing) = values.last() {
+                    self.out.write_char(',')?;
+                }
+
+                self.pad(values.is_empty())?;
+                self.out.write_char(']')
+            }
+            SuperValue::Group(items) if self.expanded(value)? => {
+                self.out.write_char('{')?;
+                self.depth += 1;
+
+                for item in self.ordered(items) {
+                    self.newline()?;
+                    self.item(item)?;
+                }
+
+                self.depth -= 1;
+                self.newline()?;
+                self.out.write_char('}')
+            }
+            SuperValue::Group(items) => {
+                self.out.write_char('{')?;
+                self.pad(items.is_empty())?;
+
+                let inline = core::mem::replace(&mut self.inline, true);
+                for (ind, item) in self.ordered(items).into_iter().enumerate() {
+                    if ind != 0 {
+                        self.comma()?;
+                    }
+                    self.item(item)?;
+                }
+                self.inline = inline;
+
+                self.pad(items.is_empty())?;
+                self.out.write_char('}')
+            }
+        }
+    }
+
+    fn element(&mut self, ind: usize, value: &SuperValue) -> fmt::Result {
+        if let Some(path) = &mut self.path {
+            path.push_index(ind);
+        }
+
+        let result = self.child(value);
+        self.path.as_mut().map(Path::pop);
+        result
+    }
+
+    /// Writes a value inside of an item or list, which is where the current
+    /// path might be redacted
+    fn child(&mut self, value: &SuperValue) -> fmt::Result {
+        match (self.options.redact, &self.path) {
+            (Some(redact), Some(path)) if *value != SuperValue::Nothing && redact(path) => {
+                self.out.write_str("<redacted>")
+            }
+            _ => self.value(value),
+        }
+    }
+
+    /// Decides if a non-empty list or group should have each of its children
+    /// on their own lines
+    fn expanded(&mut self, value: &SuperValue) -> Result<bool, fmt::Error> {
+        let (empty, forced) = match value {
+            SuperValue::List(values) => (values.is_empty(), values.len() > self.options.list_wrap),
+            SuperValue::Group(items) => (items.is_empty(), self.options.expand_groups),
+            _ => return Ok(false),
+        };
+
+        if empty {
+            return Ok(false);
+        } else if has_meta(value) {
+            // comments run to the end of the line, so they can't be inline
+            return Ok(true);
+        } else if self.inline {
+            return Ok(false);
+        }
+
+        if let (Some(layout_for), Some(path)) = (self.options.layout_for, &self.path) {
+            if let Some(layout) = layout_for(path) {
+                return Ok(layout == Layout::Expanded);
+            }
+        }
+
+        match self.options.max_width {
+            _ if forced => Ok(true),
+            Some(max_width) => Ok(self.out.column + self.measure(value)? > max_width),
+            None => Ok(false),
+        }
+    }
+
+    /// Number of characters `value` takes up when written inline
+    fn measure(&self, value: &SuperValue) -> Result<usize, fmt::Error> {
+        let mut counter = Counter(0);
+        let mut measuring = Emitter {
+            out: Column {
+                inner: &mut counter,
+                column: 0,
+            },
+            options: self.options,
+            depth: self.depth,
+            inline: true,
+            path: None,
+        };
+
+        measuring.value(value)?;
+        Ok(counter.0)
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.out.write_str(s)
+    }
+
+    fn float(&mut self, float: f64) -> fmt::Result {
+        if float.is_nan() {
+            return self.out.write_str(".nan");
+        } else if float.is_infinite() {
+            return self
+                .out
+                .write_str(if float > 0.0 { ".inf" } else { "-.inf" });
+        }
+
+        if let FloatFormat::Decimals(decimals) = self.options.float_format {
+            let fixed = format!("{:.*}", decimals.max(1), float);
+            if fixed.parse() == Ok(float) {
+                return self.out.write_str(&fixed);
+            }
+        }
+
+        // debug formatting is the shortest which round-trips and always has
+        // either a dot or an exponent, so it can't be mistaken for an integer
+        write!(self.out, "{:?}", float)
+    }
+
+    /// Items in the order they should be written in
+    fn ordered<'i, 'a>(&self, items: &'i [SuperItem<'a>]) -> Vec<&'i SuperItem<'a>> {
+        let mut ordered: Vec<_> = items.iter().collect();
+
+        if self.options.sort_keys {
+            ordered.sort_by(|a, b| a.key.cmp(&b.key));
+        }
+
+        ordered
+    }
+
+    fn newline(&mut self) -> fmt::Result {
+        self.out.write_char('\n')?;
+
+        for _ in 0..self.depth * self.options.indent {
+            self.out.write_char(' ')?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn comma(&mut self) -> fmt::Result {
+        match self.options.space_after_comma {
+            true => self.out.write_str(", "),
+            false => self.out.write_char(','),
+        }
+    }
+
+    pub(crate) fn pad(&mut self, empty: bool) -> fmt::Result {
+        match self.options.pad_brackets && !empty {
+            true => self.out.write_char(' '),
+            false => Ok(()),
+        }
+    }
+
+    /// Writes a key with backslashes in front of anything which would end the
+    /// key early or be confused for structure, so `hello there` becomes
+    /// `hello\ there` and `@secret` becomes `\@secret` rather than a line of
+    /// attributes
+    pub(crate) fn key(&mut self, key: &str) -> fmt::Result {
+        if key.starts_with('@') {
+            self.out.write_char('\\')?;
+        }
+        self.escaped(key, |c| c == ' ')
+    }
+
+    /// Writes a name with backslashes in front of anything which would
+    /// otherwise be parsed as structure or as a different type of value
+    fn name(&mut self, name: &str) -> fmt::Result {
+        if name.is_empty()
+            || name == "true"
+            || name == "false"
+            || !matches!(num_or_name(name), SuperValue::Name(_))
+            || literal(name).is_some()
+        {
+            self.out.write_char('\\')?;
+        }
+
+        self.escaped(name, |_| false)
+    }
+
+    /// Writes `text` escaping whitespace at either end, the characters which
+    /// are always special, and any others which `extra` picks out
+    fn escaped(&mut self, text: &str, extra: fn(char) -> bool) -> fmt::Result {
+        for (ind, c) in text.char_indices() {
+            let edge = ind == 0 || ind + c.len_utf8() == text.len();
+
+            if matches!(c, '\\' | ',' | '[' | ']' | '{' | '}' | '#' | '\n' | '\r')
+                || (edge && c.is_whitespace())
+                || extra(c)
+            {
+                self.out.write_char('\\')?;
+            }
+            self.out.write_char(c)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Parse, Segment};
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    fn round_trip(value: SuperValue) {
+        let emitted = value.emit();
+        assert_eq!(SuperValue::parse(&emitted).unwrap(), value, "{}", emitted);
+    }
+
+    #[test]
+    fn scalars() {
+        round_trip(SuperValue::Bool(true));
+        round_trip(SuperValue::Integer(-42));
+        round_trip(SuperValue::Name("hello there".into()));
+        assert_eq!(SuperValue::Integer(7).emit(), "7");
+    }
+
+    #[test]
+    fn ambiguous_names() {
+        for name in [
+            "true",
+            "false",
+            "123",
+            "-1",
+            "",
+            " spaced ",
+            "[a]",
+            "{",
+            "a, b",
+            "\\",
+            "x\\",
+            "b64\"Zm8=\"",
+            "b64\"\"",
+        ] {
+            round_trip(SuperValue::Name(name.into()));
+        }
+    }
+
+    #[test]
+    fn nested() {
+        round_trip(SuperValue::list(vec![]));
+        round_trip(SuperValue::list(vec![SuperValue::Nothing]));
+        round_trip(SuperValue::list(vec![
+            SuperValue::Integer(1),
+            SuperValue::Nothing,
+            SuperValue::Name("a]".into()),
+        ]));
+        round_trip(SuperValue::group(vec![
+            SuperItem {
+                key: "empty".into(),
+                value: SuperValue::Nothing,
+                meta: None,
+            },
+            SuperItem {
+                key: "inner".into(),
+                value: SuperValue::group(vec![SuperItem {
+                    key: "list".into(),
+                    value: SuperValue::list(vec![SuperValue::Bool(false)]),
+                    meta: None,
+                }]),
+                meta: None,
+            },
+        ]));
+    }
+
+    #[test]
+    fn conf() {
+        let input = "port 8080\nempty\nname a\\, b\ntls {enabled true, hosts [a, b]}\n";
+        let conf = SuperConf::parse(input).unwrap();
+
+        assert_eq!(conf.emit(), input);
+        assert_eq!(SuperConf::parse(&conf.emit()).unwrap(), conf);
+    }
+
+    #[test]
+    fn escaped_keys() {
+        for key in [
+            "hello there",
+            " edge ",
+            "a,b",
+            "#x",
+            "[k]",
+            "{g}",
+            "back\\slash",
+            "new\nline",
+        ] {
+            let item = SuperItem {
+                key: key.into(),
+                value: SuperValue::Integer(1),
+                meta: None,
+            };
+            let conf = SuperConf {
+                items: vec![item.clone(), item],
+            };
+
+            assert_eq!(SuperConf::parse(&conf.emit()).unwrap(), conf, "{}", key);
+            assert_eq!(
+                SuperValue::parse(&SuperValue::group(conf.items.clone()).emit()).unwrap(),
+                SuperValue::group(conf.items)
+            );
+        }
+
+        assert_eq!(
+            SuperItem {
+                key: "hello there".into(),
+                value: SuperValue::Bool(true),
+                meta: None,
+            }
+            .emit(),
+            "hello\\ there true"
+        );
+    }
+
+    #[test]
+    fn attribute_keys() {
+        let conf = SuperConf {
+            items: vec![
+                SuperItem {
+                    key: "@x".into(),
+                    value: SuperValue::Nothing,
+                    meta: None,
+                },
+                SuperItem {
+                    key: "a".into(),
+                    value: SuperValue::Integer(1),
+                    meta: None,
+                },
+            ],
+        };
+
+        assert_eq!(conf.emit(), "\\@x\na 1\n");
+        assert_eq!(SuperConf::parse(&conf.emit()).unwrap(), conf);
+        assert_eq!(
+            SuperValue::parse(&SuperValue::group(conf.items.clone()).emit()).unwrap(),
+            SuperValue::group(conf.items)
+        );
+    }
+
+    /// Fixed-size buffer like those found on `no_std` targets, which fails
+    /// once it's full
+    struct Fixed {
+        buf: [u8; 32],
+        len: usize,
+    }
+
+    impl Write for Fixed {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            let end = self.len + s.len();
+            self.buf
+                .get_mut(self.len..end)
+                .ok_or(fmt::Error)?
+                .copy_from_slice(s.as_bytes());
+            self.len = end;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn streaming() {
+        let conf = SuperConf::parse("port 8080\nhost local").unwrap();
+        let mut fixed = Fixed {
+            buf: [0; 32],
+            len: 0,
+        };
+
+        conf.emit_to(&mut fixed).unwrap();
+        assert_eq!(&fixed.buf[..fixed.len], b"port 8080\nhost local\n");
+        assert!(conf.emit_to(&mut fixed).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn writer_and_file() {
+        let conf = SuperConf::parse("port 8080\nhosts [a, b]").unwrap();
+        let mut buf = std::vec::Vec::new();
+
+        conf.emit_to_writer(&mut buf).unwrap();
+        assert_eq!(buf, conf.emit().as_bytes());
+
+        let path = std::env::temp_dir().join("superconf-write-to-file.conf");
+        conf.write_to_file(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), conf.emit());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn byte_buffer() {
+        let conf = SuperConf::parse("port 8080\nhost local").unwrap();
+        let mut buf = [0; 32];
+
+        assert_eq!(conf.emit_into(&mut buf), Ok(21));
+        assert_eq!(&buf[..21], b"port 8080\nhost local\n");
+        assert_eq!(
+            conf.emit_into(&mut buf[..8]),
+            Err(EmitError::BufferTooSmall { required: 21 })
+        );
+        assert_eq!(conf.emit_into(&mut buf[..21]), Ok(21));
+    }
+
+    #[test]
+    fn display() {
+        let conf = SuperConf::parse("a [1, x]\nb {c true}").unwrap();
+
+        assert_eq!(conf.to_string(), conf.emit());
+        assert_eq!(conf.items[1].to_string(), "b {c true}");
+        assert_eq!(conf.items[0].value.to_string(), "[1, x]");
+    }
+
+    #[test]
+    fn floats() {
+        let decimals = EmitOptions {
+            float_format: FloatFormat::Decimals(3),
+            ..EmitOptions::default()
+        };
+        let floats = [
+            1.5,
+            1.0,
+            -0.25,
+            0.1 + 0.2,
+            1e300,
+            5e-324,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ];
+
+        for float in floats {
+            for options in [EmitOptions::default(), decimals.clone()] {
+                let emitted = SuperValue::Float(float).emit_with(&options);
+                assert_eq!(
+                    SuperValue::parse(&emitted).unwrap(),
+                    SuperValue::Float(float),
+                    "{}",
+                    emitted
+                );
+            }
+        }
+
+        assert_eq!(SuperValue::Float(1.0).emit(), "1.0");
+        assert_eq!(SuperValue::Float(1e300).emit(), "1e300");
+        assert_eq!(SuperValue::Float(1.5).emit_with(&decimals), "1.500");
+        assert_eq!(
+            SuperValue::Float(0.1 + 0.2).emit_with(&decimals),
+            "0.30000000000000004"
+        );
+        assert_eq!(SuperValue::Float(f64::NAN).emit(), ".nan");
+
+        for name in ["1.5", ".inf", "-.nan", "2e3"] {
+            round_trip(SuperValue::Name(name.into()));
+        }
+    }
+
+    #[test]
+    fn width_heuristics() {
+        let conf = SuperConf::parse(
+            "small {a 1, b 2}\nlarge {first [1, 2, 3], second {nested true}, third name}",
+        )
+        .unwrap();
+        let narrow = EmitOptions {
+            max_width: Some(30),
+            ..EmitOptions::default()
+        };
+
+        assert_eq!(
+            conf.emit_with(&narrow),
+            "small {a 1, b 2}\nlarge {\n    first [1, 2, 3]\n    second {nested true}\n    third name\n}\n"
+        );
+
+        let overridden = EmitOptions {
+            layout_for: Some(|path| match path.to_string().as_str() {
+                "small" => Some(Layout::Expanded),
+                "large" => Some(Layout::Inline),
+                _ => None,
+            }),
+            ..narrow
+        };
+
+        assert_eq!(
+            conf.emit_with(&overridden),
+            "small {\n    a 1\n    b 2\n}\nlarge {first [1, 2, 3], second {nested true}, third name}\n"
+        );
+        assert_eq!(
+            SuperConf::parse(&conf.emit_with(&overridden)).unwrap(),
+            conf
+        );
+    }
+
+    #[test]
+    fn redaction() {
+        let conf = SuperConf::parse(
+            "user admin\npassword hunter2\ndb {password [1, 2], host local, token}\nkeys [a, b]",
+        )
+        .unwrap();
+        let options = EmitOptions {
+            redact: Some(|path| {
+                let last = path.segments.last();
+                last == Some(&Segment::Key("password".into())) || last == Some(&Segment::Index(1))
+            }),
+            ..EmitOptions::default()
+        };
+
+        assert_eq!(
+            conf.emit_with(&options),
+            "user admin\npassword <redacted>\ndb {password <redacted>, host local, token}\nkeys [a, <redacted>]\n"
+        );
+
+        let conf =
+            SuperConf::parse("@secret\ntoken abc\ndb {@secret\nkey [1], host local}").unwrap();
+        let options = EmitOptions {
+            redact_secrets: true,
+            ..EmitOptions::default()
+        };
+        assert_eq!(
+            conf.emit_with(&options),
+            "@secret\ntoken <redacted>\ndb {\n    @secret\n    key <redacted>\n    host local\n}\n"
+        );
+    }
+
+    #[test]
+    fn metadata() {
+        let conf = SuperConf::parse(
+            "## Port to listen on\n##\n## for http\n@unit(port) @deprecated\nport 80\nhosts [{\n## first\nname a}, b]\ntls {\n## cert\n@secret\ncert x, key y}\n@secret\ntoken abc",
+        )
+        .unwrap();
+
+        let expanded = EmitOptions {
+            expand_groups: true,
+            ..EmitOptions::default()
+        };
+        for options in [EmitOptions::default(), expanded] {
+            let emitted = conf.emit_with(&options);
+            assert_eq!(SuperConf::parse(&emitted).unwrap(), conf, "{}", emitted);
+        }
+        assert_eq!(
+            conf.emit(),
+            "## Port to listen on\n##\n## for http\n@unit(port) @deprecated\nport 80\nhosts [\n    {\n        ## first\n        name a\n    },\n    b,\n]\ntls {\n    ## cert\n    @secret\n    cert x\n    key y\n}\n@secret\ntoken abc\n"
+        );
+    }
+
+    #[test]
+    fn canonical() {
+        let shuffled = SuperConf::parse("b {z  1, y [true,false]}\na 1\nc x\na 2").unwrap();
+        let sorted = SuperConf::parse("a 1\na 2\nb {y [true, false], z 1}\nc x").unwrap();
+
+        assert_eq!(
+            shuffled.emit_with(&EmitOptions::canonical()),
+            "a 1\na 2\nb {y [true, false], z 1}\nc x\n"
+        );
+        assert_eq!(
+            shuffled.emit_with(&EmitOptions::canonical()),
+            sorted.emit_with(&EmitOptions::canonical())
+        );
+    }
+
+    #[test]
+    fn options() {
+        let conf =
+            SuperConf::parse("tls {enabled true, hosts [a, b], inner {x}}\nlist [1, 2]").unwrap();
+        let expanded = EmitOptions {
+            indent: 2,
+            expand_groups: true,
+            list_wrap: 1,
+            ..EmitOptions::default()
+        };
+        let compact = EmitOptions {
+            space_after_comma: false,
+            pad_brackets: true,
+            ..EmitOptions::default()
+        };
+
+        assert_eq!(
+            conf.emit_with(&expanded),
+            "tls {\n  enabled true\n  hosts [\n    a,\n    b,\n  ]\n  inner {\n    x\n  }\n}\nlist [\n  1,\n  2,\n]\n"
+        );
+        assert_eq!(
+            conf.emit_with(&compact),
+            "tls { enabled true,hosts [ a,b ],inner { x } }\nlist [ 1,2 ]\n"
+        );
+
+        for options in [expanded, compact] {
+            assert_eq!(SuperConf::parse(&conf.emit_with(&options)).unwrap(), conf);
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! Writing configs out in other, flatter formats such as shell environment
+//! statements or Java-style properties, so other tools can be fed from a
+//! single config
+
+use super::{Path, Segment, SuperConf, SuperValue};
+use alloc::string::{String, ToString};
+use core::fmt::Write;
+
+impl SuperConf<'_> {
+    /// Writes every scalar as an `export` statement for a POSIX shell, like
+    /// `export APP_SERVER_PORT=8080` for `server.port` with a prefix of `APP`
+    ///
+    /// Names are made by uppercasing each key and index, with anything other
+    /// than letters and digits becoming `_`, and joining them with `_`, so
+    /// they can be read back with [SuperConf::from_dotenv_nested]. Values
+    /// are put in single quotes unless they only have characters which are
+    /// safe in a shell, and empty lists and groups aren't written.
+    ///
+    /// ```
+    /// use superconf::{Parse, SuperConf};
+    ///
+    /// let conf = SuperConf::parse("server {port 8080, motd it's\\ me}").unwrap();
+    ///
+    /// assert_eq!(
+    ///     conf.to_shell("APP"),
+    ///     "export APP_SERVER_PORT=8080\nexport APP_SERVER_MOTD='it'\\''s me'\n"
+    /// );
+    /// ```
+    pub fn to_shell(&self, prefix: &str) -> String {
+        let mut output = String::new();
+
+        for (path, value) in self.leaves() {
+            output.push_str("export ");
+            output.push_str(prefix);
+
+            for (ind, segment) in path.segments.iter().enumerate() {
+                if ind != 0 || !prefix.is_empty() {
+                    output.push('_');
+                }
+                match segment {
+                    Segment::Key(key) => {
+                        output.extend(key.chars().map(|c| match c.is_ascii_alphanumeric() {
+                            true => c.to_ascii_uppercase(),
+                            false => '_',
+                        }))
+                    }
+                    Segment::Index(index) => write!(output, "{}", index).unwrap(),
+                }
+            }
+
+            output.push('=');
+            shell_quote(&mut output, &text(value));
+            output.push('\n');
+        }
+
+        output
+    }
+
+    /// Writes every scalar as a line of Java-style properties text, like
+    /// `server.hosts[0]=a`, which [SuperConf::from_properties] can read back
+    /// apart from list indexes becoming part of keys
+    ///
+    /// Characters which mean something in properties are escaped with a
+    /// backslash, and anything outside of ASCII is written as a `\uXXXX`
+    /// escape so the output is valid in the ISO 8859-1 encoding which
+    /// properties files default to. Empty lists and groups aren't written.
+    pub fn to_properties(&self) -> String {
+        let mut output = String::new();
+
+        for (path, value) in self.leaves() {
+            properties_escape(&mut output, &properties_key(&path), true);
+            output.push('=');
+            properties_escape(&mut output, &text(value), false);
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+/// Gets the text of a scalar without any of the escaping used by superconf
+fn text(value: &SuperValue) -> String {
+    match value {
+        SuperValue::Nothing => String::new(),
+        SuperValue::Name(name) => name.to_string(),
+        scalar => scalar.to_string(),
+    }
+}
+
+fn shell_quote(output: &mut String, text: &str) {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-./:@%+,=".contains(c);
+
+    match !text.is_empty() && text.chars().all(safe) {
+        true => output.push_str(text),
+        false => {
+            output.push('\'');
+            // single quotes can't be escaped inside of single quotes, so the
+            // quoting is closed around an escaped quote instead
+            output.push_str(&text.replace('\'', "'\\''"));
+            output.push('\'');
+        }
+    }
+}
+
+fn properties_key(path: &Path) -> String {
+    let mut key = String::new();
+
+    for (ind, segment) in path.segments.iter().enumerate() {
+        match segment {
+            Segment::Key(part) => {
+                if ind != 0 {
+                    key.push('.');
+                }
+                key.push_str(part);
+            }
+            Segment::Index(index) => write!(key, "[{}]", index).unwrap(),
+        }
+    }
+
+    key
+}
+
+/// Escapes `text` for properties, where keys also need any whitespace and
+/// separators escaping but values only need leading whitespace escaping
+fn properties_escape(output: &mut String, text: &str, key: bool) {
+    for (ind, c) in text.chars().enumerate() {
+        match c {
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\t' => output.push_str("\\t"),
+            '\r' => output.push_str("\\r"),
+            '\u{c}' => output.push_str("\\f"),
+            ' ' if key || ind == 0 => output.push_str("\\ "),
+            '=' | ':' | '#' | '!' if key || ind == 0 => {
+                output.push('\\');
+                output.push(c);
+            }
+            c if c.is_ascii() => output.push(c),
+            c => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    write!(output, "\\u{:04X}", unit).unwrap();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+
+    #[test]
+    fn shell() {
+        let conf =
+            SuperConf::parse("server {port 8080, log-level debug}\nhosts [a, b c]\nempty\nlist []")
+                .unwrap();
+
+        assert_eq!(
+            conf.to_shell("APP"),
+            "export APP_SERVER_PORT=8080\nexport APP_SERVER_LOG_LEVEL=debug\nexport APP_HOSTS_0=a\nexport APP_HOSTS_1='b c'\nexport APP_EMPTY=''\n"
+        );
+        assert_eq!(
+            conf.to_shell("").lines().next(),
+            Some("export SERVER_PORT=8080")
+        );
+
+        let text = conf.to_shell("");
+        let back = SuperConf::from_dotenv_nested(&text).unwrap();
+        assert_eq!(back.get("server.port"), Some(&SuperValue::Integer(8080)));
+        assert_eq!(back.get("hosts.1"), Some(&SuperValue::Name("b c".into())));
+    }
+
+    #[test]
+    fn properties() {
+        let conf = SuperConf::parse(
+            "server {host local\\ host, port 80}\ngreeting\\ hi= \\ café\nhosts [a]",
+        )
+        .unwrap();
+        let text = conf.to_properties();
+
+        assert_eq!(
+            text,
+            "server.host=local host\nserver.port=80\ngreeting\\ hi\\==\\ caf\\u00E9\nhosts[0]=a\n"
+        );
+
+        let back = SuperConf::from_properties(&text).unwrap();
+        assert_eq!(
+            back.get("server.host"),
+            Some(&SuperValue::Name("local host".into()))
+        );
+        assert_eq!(
+            back.get("greeting hi="),
+            Some(&SuperValue::Name(" café".into()))
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Inheritance between groups written like `worker2 {@extends worker, port
+//! 9002}`, which is resolved by [SuperConf::resolve_extends]
+
+use super::{
+    value, value_mut, Growable, Parse, Path, Segment, SuperConf, SuperError, SuperItem, SuperValue,
+};
+use alloc::vec::Vec;
+
+/// Key of the item naming which group a group extends
+const EXTENDS_KEY: &str = "@extends";
+
+impl SuperConf<'_> {
+    /// Copies every item which a group with an `@extends` item doesn't have
+    /// from the group it names, removing the `@extends` items
+    ///
+    /// The group being extended is named by its full path, like
+    /// `defaults.worker`, and can extend another group itself. Groups which
+    /// are in both are filled in item by item, and a group with more than
+    /// one `@extends` takes items from the first one it names first.
+    ///
+    /// ```
+    /// use superconf::{Parse, SuperConf};
+    ///
+    /// let text = "worker {threads 4, port 9000}\nworker2 {@extends worker, port 9002}";
+    /// let mut conf = SuperConf::parse(text).unwrap();
+    ///
+    /// conf.resolve_extends().unwrap();
+    /// assert_eq!(
+    ///     conf,
+    ///     SuperConf::parse("worker {threads 4, port 9000}\nworker2 {port 9002, threads 4}").unwrap()
+    /// );
+    /// ```
+    pub fn resolve_extends(&mut self) -> Result<(), SuperError> {
+        loop {
+            let mut pending = Vec::new();
+            find(&self.items, &mut Path::new(), &mut pending)?;
+            if pending.is_empty() {
+                return Ok(());
+            }
+
+            // groups can only be copied from once they've been resolved, as
+            // otherwise they'd be missing the items they inherit themselves
+            let mut resolved = false;
+            for (path, base) in pending {
+                let base = match value(&self.items, &base.segments) {
+                    Some(SuperValue::Group(items)) if !extends(items) => (**items).clone(),
+                    Some(SuperValue::Group(_)) => continue,
+                    _ => return Err(SuperError::PathNotFound),
+                };
+                let items = match value_mut(&mut self.items, &path.segments) {
+                    Some(SuperValue::Group(items)) => items,
+                    _ => unreachable!(),
+                };
+
+                let ind = items
+                    .iter()
+                    .position(|item| item.key == EXTENDS_KEY)
+                    .expect("group is still extending");
+                items.remove(ind);
+                fill(&mut **items, base);
+                resolved = true;
+            }
+
+            if !resolved {
+                return Err(SuperError::ExtendsCycle);
+            }
+        }
+    }
+}
+
+/// Finds every group which is still extending another, alongside the path of
+/// the first group it extends
+fn find(
+    items: &[SuperItem<'_>],
+    path: &mut Path<'static>,
+    pending: &mut Vec<(Path<'static>, Path<'static>)>,
+) -> Result<(), SuperError> {
+    for item in items {
+        path.push_key(item.key.clone().into_owned());
+        find_value(&item.value, path, pending)?;
+        path.pop();
+    }
+    Ok(())
+}
+
+fn find_value(
+    value: &SuperValue<'_>,
+    path: &mut Path<'static>,
+    pending: &mut Vec<(Path<'static>, Path<'static>)>,
+) -> Result<(), SuperError> {
+    match value {
+        SuperValue::Group(items) => {
+            if let Some(item) = items.iter().find(|item| item.key == EXTENDS_KEY) {
+                let base = match &item.value {
+                    SuperValue::Name(name) => Path::parse(name)?.into_owned(),
+                    _ => return Err(SuperError::InvalidPath),
+                };
+                pending.push((path.clone(), base));
+            }
+            find(items, path, pending)
+        }
+        SuperValue::List(values) => {
+            for (index, value) in values.iter().enumerate() {
+                path.segments.push(Segment::Index(index));
+                find_value(value, path, pending)?;
+                path.pop();
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn extends(items: &[SuperItem<'_>]) -> bool {
+    items.iter().any(|item| item.key == EXTENDS_KEY)
+}
+
+/// Adds every item from `base` which isn't in `items`, filling in groups
+/// which are in both
+fn fill<'a>(items: &mut impl Growable<'a>, base: impl IntoIterator<Item = SuperItem<'a>>) {
+    for item in base {
+        match items.iter_mut().find(|found| found.key == item.key) {
+            Some(found) => {
+                if let (SuperValue::Group(found), SuperValue::Group(base)) =
+                    (&mut found.value, item.value)
+                {
+                    fill(&mut **found, *base);
+                }
+            }
+            None => items.push(item),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(text: &str) -> Result<SuperConf<'_>, SuperError> {
+        let mut conf = SuperConf::parse(text).unwrap();
+        conf.resolve_extends().map(|_| conf)
+    }
+
+    #[test]
+    fn extended() {
+        assert_eq!(
+            resolve("a {x 1, y {z 2, w 3}}\nb {@extends c, v 0}\nc {@extends a, y {z 4}}\nd [{@extends b}]"),
+            Ok(SuperConf::parse(
+                "a {x 1, y {z 2, w 3}}\nb {v 0, y {z 4, w 3}, x 1}\nc {y {z 4, w 3}, x 1}\nd [{v 0, y {z 4, w 3}, x 1}]"
+            )
+            .unwrap())
+        );
+        assert_eq!(
+            resolve("a {x 1}\nb {x 2}\nc {@extends a, @extends b, y 3}"),
+            Ok(SuperConf::parse("a {x 1}\nb {x 2}\nc {y 3, x 1}").unwrap())
+        );
+        assert_eq!(
+            resolve("a {b {c 1}}\nd {@extends a.b}"),
+            Ok(SuperConf::parse("a {b {c 1}}\nd {c 1}").unwrap())
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(resolve("a {@extends b}"), Err(SuperError::PathNotFound));
+        assert_eq!(
+            resolve("a {@extends b}\nb 1"),
+            Err(SuperError::PathNotFound)
+        );
+        assert_eq!(resolve("a {@extends a}"), Err(SuperError::ExtendsCycle));
+        assert_eq!(
+            resolve("a {@extends b}\nb {@extends a}"),
+            Err(SuperError::ExtendsCycle)
+        );
+        assert_eq!(resolve("a {@extends [1]}"), Err(SuperError::InvalidPath));
+    }
+}
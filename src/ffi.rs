@@ -0,0 +1,331 @@
+//! C interface, behind the `ffi` feature, so C and C++ projects can read
+//! configs through this crate with the declarations in `include/superconf.h`
+//!
+//! Configs are parsed into an opaque pointer which has to be given back to
+//! [superconf_free], and every function gives a [Status] with any values
+//! written through out pointers. Building a library to link against is done
+//! with something like `cargo rustc --features ffi --crate-type staticlib`.
+
+use super::{Parse, SuperConf, SuperError, SuperErrorKind, SuperValue};
+use alloc::boxed::Box;
+use core::ffi::{c_char, CStr};
+use core::{ptr, slice, str};
+
+/// Result of every function, where anything other than [Status::Ok] means
+/// nothing was written to the out pointers
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Status {
+    Ok = 0,
+    /// When a required pointer was null
+    NullPointer = 1,
+    /// When the text or a path wasn't valid UTF-8
+    InvalidUtf8 = 2,
+    /// When parsing found unbalanced brackets, see [SuperError::Unbalanced]
+    Unbalanced = 3,
+    /// When a path couldn't be parsed, see [SuperError::InvalidPath]
+    InvalidPath = 4,
+    /// When there's no value at a path
+    NotFound = 5,
+    /// When the value at a path is a different type to the one asked for
+    WrongType = 6,
+    /// When a string doesn't fit in the buffer given for it, where the length
+    /// it needs is still written
+    BufferTooSmall = 7,
+    /// When the text couldn't be parsed for any other reason
+    Invalid = 8,
+}
+
+impl From<SuperError> for Status {
+    fn from(err: SuperError) -> Self {
+        match err.kind {
+            SuperErrorKind::Unbalanced => Self::Unbalanced,
+            SuperErrorKind::InvalidPath => Self::InvalidPath,
+            SuperErrorKind::PathNotFound => Self::NotFound,
+            _ => Self::Invalid,
+        }
+    }
+}
+
+/// Parses `len` bytes of `text`, which doesn't need to end in a nul, writing
+/// the new config to `out`
+///
+/// # Safety
+///
+/// `text` has to point to `len` readable bytes and `out` has to be writable.
+#[no_mangle]
+pub unsafe extern "C" fn superconf_parse(
+    text: *const c_char,
+    len: usize,
+    out: *mut *mut SuperConf<'static>,
+) -> Status {
+    if text.is_null() || out.is_null() {
+        return Status::NullPointer;
+    }
+
+    let text = match str::from_utf8(slice::from_raw_parts(text.cast(), len)) {
+        Ok(text) => text,
+        Err(_) => return Status::InvalidUtf8,
+    };
+
+    match SuperConf::parse(text) {
+        Ok(conf) => {
+            *out = Box::into_raw(Box::new(conf.into_owned()));
+            Status::Ok
+        }
+        Err(err) => err.into(),
+    }
+}
+
+/// Frees a config from [superconf_parse], doing nothing if it's null
+///
+/// # Safety
+///
+/// `conf` has to have come from [superconf_parse] and not been freed already.
+#[no_mangle]
+pub unsafe extern "C" fn superconf_free(conf: *mut SuperConf<'static>) {
+    if !conf.is_null() {
+        drop(Box::from_raw(conf));
+    }
+}
+
+/// Writes the integer at the nul-terminated `path` to `out`
+///
+/// # Safety
+///
+/// `conf` has to be a live config, `path` a nul-terminated string and `out`
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn superconf_get_int(
+    conf: *const SuperConf<'static>,
+    path: *const c_char,
+    out: *mut i64,
+) -> Status {
+    get(conf, path, out, |value| match value {
+        SuperValue::Integer(integer) => Some(*integer),
+        _ => None,
+    })
+}
+
+/// Writes the float at the nul-terminated `path` to `out`, where integers are
+/// converted too
+///
+/// # Safety
+///
+/// `conf` has to be a live config, `path` a nul-terminated string and `out`
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn superconf_get_float(
+    conf: *const SuperConf<'static>,
+    path: *const c_char,
+    out: *mut f64,
+) -> Status {
+    get(conf, path, out, |value| match value {
+        SuperValue::Float(float) => Some(*float),
+        SuperValue::Integer(integer) => Some(*integer as f64),
+        _ => None,
+    })
+}
+
+/// Writes the bool at the nul-terminated `path` to `out`
+///
+/// # Safety
+///
+/// `conf` has to be a live config, `path` a nul-terminated string and `out`
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn superconf_get_bool(
+    conf: *const SuperConf<'static>,
+    path: *const c_char,
+    out: *mut bool,
+) -> Status {
+    get(conf, path, out, |value| match value {
+        SuperValue::Bool(boolean) => Some(*boolean),
+        _ => None,
+    })
+}
+
+/// Copies the name at the nul-terminated `path` into `buf` with a nul on the
+/// end, where `len` starts as the size of `buf` and is set to the length of
+/// the name without the nul
+///
+/// If `buf` is too small nothing is copied, but `len` is still set so the
+/// call can be made again with a big enough buffer. Nothing counts as an
+/// empty string.
+///
+/// # Safety
+///
+/// `conf` has to be a live config, `path` a nul-terminated string, `len`
+/// writable and `buf` writable for as many bytes as `len` starts as.
+#[no_mangle]
+pub unsafe extern "C" fn superconf_get_string(
+    conf: *const SuperConf<'static>,
+    path: *const c_char,
+    buf: *mut c_char,
+    len: *mut usize,
+) -> Status {
+    if buf.is_null() || len.is_null() {
+        return Status::NullPointer;
+    }
+
+    let name = match find(conf, path) {
+        Ok(SuperValue::Name(name)) => &**name,
+        Ok(SuperValue::Nothing) => "",
+        Ok(_) => return Status::WrongType,
+        Err(status) => return status,
+    };
+    let capacity = *len;
+    *len = name.len();
+
+    if name.len() >= capacity {
+        return Status::BufferTooSmall;
+    }
+
+    ptr::copy_nonoverlapping(name.as_ptr(), buf.cast(), name.len());
+    *buf.add(name.len()) = 0;
+    Status::Ok
+}
+
+unsafe fn find<'c>(
+    conf: *const SuperConf<'static>,
+    path: *const c_char,
+) -> Result<&'c SuperValue<'static>, Status> {
+    if conf.is_null() || path.is_null() {
+        return Err(Status::NullPointer);
+    }
+
+    let path = CStr::from_ptr(path)
+        .to_str()
+        .map_err(|_| Status::InvalidUtf8)?;
+    let path = super::Path::parse(path)?;
+
+    (*conf).get_path(&path).ok_or(Status::NotFound)
+}
+
+unsafe fn get<T>(
+    conf: *const SuperConf<'static>,
+    path: *const c_char,
+    out: *mut T,
+    convert: impl FnOnce(&SuperValue) -> Option<T>,
+) -> Status {
+    if out.is_null() {
+        return Status::NullPointer;
+    }
+
+    match find(conf, path).map(convert) {
+        Ok(Some(found)) => {
+            *out = found;
+            Status::Ok
+        }
+        Ok(None) => Status::WrongType,
+        Err(status) => status,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_values() {
+        let text = "port 8080\nratio 0.5\ntls {enabled true, host example}\nempty";
+        let mut conf = ptr::null_mut();
+
+        unsafe {
+            assert_eq!(
+                superconf_parse(text.as_ptr().cast(), text.len(), &mut conf),
+                Status::Ok
+            );
+
+            let mut int = 0;
+            assert_eq!(
+                superconf_get_int(conf, b"port\0".as_ptr().cast(), &mut int),
+                Status::Ok
+            );
+            assert_eq!(int, 8080);
+
+            let mut float = 0.0;
+            assert_eq!(
+                superconf_get_float(conf, b"ratio\0".as_ptr().cast(), &mut float),
+                Status::Ok
+            );
+            assert_eq!(float, 0.5);
+
+            let mut boolean = false;
+            assert_eq!(
+                superconf_get_bool(conf, b"tls.enabled\0".as_ptr().cast(), &mut boolean),
+                Status::Ok
+            );
+            assert!(boolean);
+
+            let mut buf = [1 as c_char; 8];
+            let mut len = buf.len();
+            assert_eq!(
+                superconf_get_string(
+                    conf,
+                    b"tls.host\0".as_ptr().cast(),
+                    buf.as_mut_ptr(),
+                    &mut len
+                ),
+                Status::Ok
+            );
+            assert_eq!(CStr::from_ptr(buf.as_ptr()).to_str(), Ok("example"));
+            assert_eq!(len, 7);
+
+            let mut len = 4;
+            assert_eq!(
+                superconf_get_string(
+                    conf,
+                    b"tls.host\0".as_ptr().cast(),
+                    buf.as_mut_ptr(),
+                    &mut len
+                ),
+                Status::BufferTooSmall
+            );
+            assert_eq!(len, 7);
+
+            superconf_free(conf);
+        }
+    }
+
+    #[test]
+    fn statuses() {
+        let mut conf = ptr::null_mut();
+
+        unsafe {
+            assert_eq!(
+                superconf_parse(b"a [".as_ptr().cast(), 3, &mut conf),
+                Status::Unbalanced
+            );
+            assert_eq!(
+                superconf_parse(b"a \xff".as_ptr().cast(), 3, &mut conf),
+                Status::InvalidUtf8
+            );
+            assert_eq!(
+                superconf_parse(ptr::null(), 0, &mut conf),
+                Status::NullPointer
+            );
+            assert_eq!(
+                superconf_parse(b"a b".as_ptr().cast(), 3, &mut conf),
+                Status::Ok
+            );
+
+            let mut int = 0;
+            assert_eq!(
+                superconf_get_int(conf, b"a\0".as_ptr().cast(), &mut int),
+                Status::WrongType
+            );
+            assert_eq!(
+                superconf_get_int(conf, b"b\0".as_ptr().cast(), &mut int),
+                Status::NotFound
+            );
+            assert_eq!(
+                superconf_get_int(conf, b"a..b\0".as_ptr().cast(), &mut int),
+                Status::InvalidPath
+            );
+
+            superconf_free(conf);
+            superconf_free(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! Parsing into storage given by the caller with [parse_fixed], for targets
+//! which have no allocator at all
+//!
+//! Every list, group and scalar becomes one [FixedNode], laid out in the
+//! order they were written with the nodes nested inside of a list or group
+//! coming straight after it. Text is kept [Raw] so that nothing has to be
+//! copied to remove escapes.
+
+use super::reader::{Event, Raw, Reader, Unescaped, MAX_DEPTH};
+use super::SuperError;
+
+/// Key and value of one item or list element parsed by [parse_fixed]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct FixedNode<'a> {
+    /// Key of an item, which list elements don't have
+    pub key: Option<Raw<'a>>,
+    pub value: FixedValue<'a>,
+}
+
+impl Default for FixedNode<'_> {
+    fn default() -> Self {
+        Self {
+            key: None,
+            value: FixedValue::Scalar(Raw::new("")),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FixedValue<'a> {
+    /// Value which isn't a list or group, which is empty for nothing
+    Scalar(Raw<'a>),
+    /// List holding the number of nodes nested inside of it at any depth
+    List(usize),
+    /// Group holding the number of nodes nested inside of it at any depth
+    Group(usize),
+}
+
+impl FixedValue<'_> {
+    fn nested(self) -> usize {
+        match self {
+            Self::Scalar(_) => 0,
+            Self::List(nested) | Self::Group(nested) => nested,
+        }
+    }
+}
+
+/// Config parsed by [parse_fixed], borrowing the nodes it was parsed into
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct FixedConf<'n, 'a> {
+    nodes: &'n [FixedNode<'a>],
+}
+
+impl<'n, 'a> FixedConf<'n, 'a> {
+    /// Every node at any depth, in the order they were written
+    pub fn nodes(&self) -> &'n [FixedNode<'a>] {
+        self.nodes
+    }
+
+    /// Iterates over the items at the top level, or the elements or items
+    /// directly inside of a list or group given by [FixedConf::items]
+    pub fn items(&self) -> FixedItems<'n, 'a> {
+        FixedItems { nodes: self.nodes }
+    }
+
+    /// Gets the node at a path such as `server.hosts[2].port`, the same as
+    /// [SuperConf::get](crate::SuperConf::get) would
+    pub fn get(&self, path: &str) -> Option<&'n FixedNode<'a>> {
+        let mut found: Option<&FixedNode> = None;
+        let mut inside = *self;
+        let mut rest = path;
+
+        while found.is_none() || !rest.is_empty() {
+            let (step, next) = split_step(rest, found.is_none())?;
+            let (node, nested) = match (step, found.map(|node| node.value)) {
+                (Step::Key(key), None | Some(FixedValue::Group(_))) => {
+                    inside.items().find(|(node, _)| {
+                        node.key.is_some_and(|found| found.chars().eq(key.clone()))
+                    })?
+                }
+                (Step::Index(index), Some(FixedValue::List(_))) => inside.items().nth(index)?,
+                _ => return None,
+            };
+
+            found = Some(node);
+            inside = nested;
+            rest = next;
+        }
+
+        found
+    }
+}
+
+/// Iterator over the nodes directly inside of a [FixedConf], alongside the
+/// nodes nested inside of each one
+#[derive(Debug, Clone)]
+pub struct FixedItems<'n, 'a> {
+    nodes: &'n [FixedNode<'a>],
+}
+
+impl<'n, 'a> Iterator for FixedItems<'n, 'a> {
+    type Item = (&'n FixedNode<'a>, FixedConf<'n, 'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, rest) = self.nodes.split_first()?;
+        let (nested, rest) = rest.split_at(node.value.nested());
+        self.nodes = rest;
+        Some((node, FixedConf { nodes: nested }))
+    }
+}
+
+/// Parses a config into `nodes` without allocating, giving
+/// [SuperError::TooManyItems] if it needs more nodes than there are
+///
+/// ```
+/// use superconf::{parse_fixed, FixedNode, FixedValue};
+///
+/// let mut nodes = [FixedNode::default(); 8];
+/// let conf = parse_fixed("port 8080\nhosts [a, b]", &mut nodes).unwrap();
+///
+/// assert_eq!(conf.nodes().len(), 4);
+/// match conf.get("hosts[1]").unwrap().value {
+///     FixedValue::Scalar(host) => assert_eq!(host.as_str(), "b"),
+///     _ => panic!("expected a scalar"),
+/// }
+/// ```
+pub fn parse_fixed<'n, 'a>(
+    input: &'a str,
+    nodes: &'n mut [FixedNode<'a>],
+) -> Result<FixedConf<'n, 'a>, SuperError> {
+    // the reader never goes deeper than this, so every open list or group
+    // has room to remember where its node is
+    let mut open = [0; MAX_DEPTH];
+    let mut depth = 0;
+    let mut len = 0;
+    let mut key = None;
+
+    for event in Reader::new(input) {
+        let value = match event? {
+            Event::Key(found) => {
+                key = Some(found);
+                continue;
+            }
+            Event::Scalar(raw) => FixedValue::Scalar(raw),
+            Event::ListStart => FixedValue::List(0),
+            Event::GroupStart => FixedValue::Group(0),
+            Event::ListEnd | Event::GroupEnd => {
+                depth -= 1;
+                let start = open[depth];
+                if let FixedValue::List(nested) | FixedValue::Group(nested) =
+                    &mut nodes[start].value
+                {
+                    *nested = len - start - 1;
+                }
+                continue;
+            }
+        };
+
+        let node = nodes.get_mut(len).ok_or(SuperError::TooManyItems)?;
+        *node = FixedNode {
+            key: key.take(),
+            value,
+        };
+        if !matches!(value, FixedValue::Scalar(_)) {
+            open[depth] = len;
+            depth += 1;
+        }
+        len += 1;
+    }
+
+    Ok(FixedConf {
+        nodes: &nodes[..len],
+    })
+}
+
+/// Part of a path being looked up by [FixedConf::get]
+enum Step<'p> {
+    Key(Unescaped<'p>),
+    Index(usize),
+}
+
+/// Splits the next step off of a path, which only doesn't need a dot before
+/// a key if it's the `first`
+fn split_step(path: &str, first: bool) -> Option<(Step<'_>, &str)> {
+    if let Some(rest) = path.strip_prefix('[') {
+        let (index, rest) = rest.split_once(']')?;
+        return Some((Step::Index(index.parse().ok()?), rest));
+    }
+
+    let path = match first {
+        true => path,
+        false => path.strip_prefix('.')?,
+    };
+    let mut escaped = false;
+    let end = path
+        .char_indices()
+        .find(|(_, c)| {
+            let found = !escaped && matches!(c, '.' | '[' | ']');
+            escaped = !escaped && *c == '\\';
+            found
+        })
+        .map_or(path.len(), |(ind, _)| ind);
+
+    match end {
+        0 => None,
+        _ => Some((Step::Key(Raw::new(&path[..end]).chars()), &path[end..])),
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::{Parse, SuperConf, SuperValue};
+
+    fn scalar<'a>(node: Option<&FixedNode<'a>>) -> Option<SuperValue<'a>> {
+        match node?.value {
+            FixedValue::Scalar(raw) => Some(raw.to_value()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn same_as_tree() {
+        const TEXT: &str =
+            "port 8080\nserver {hosts [a, {b\\.c 1}], tls}\nname my\\ app\nlast [[1], 2]";
+        let tree = SuperConf::parse(TEXT).unwrap();
+        let mut nodes = [FixedNode::default(); 16];
+        let conf = parse_fixed(TEXT, &mut nodes).unwrap();
+
+        assert_eq!(conf.nodes().len(), 12);
+        assert_eq!(conf.items().count(), 4);
+        for path in [
+            "port",
+            "server.hosts[0]",
+            "server.hosts[1].b\\.c",
+            "server.tls",
+            "name",
+            "last[0][0]",
+            "last[1]",
+        ] {
+            assert_eq!(scalar(conf.get(path)), tree.get(path).cloned(), "{}", path);
+        }
+
+        assert_eq!(conf.get("server").unwrap().value, FixedValue::Group(5));
+        for path in [
+            "",
+            "port.x",
+            "server[0]",
+            "last[2]",
+            "last.0",
+            "server..tls",
+        ] {
+            assert_eq!(conf.get(path), None, "{}", path);
+        }
+    }
+
+    #[test]
+    fn capacity() {
+        let mut nodes = [FixedNode::default(); 3];
+
+        assert_eq!(
+            parse_fixed("a [1, 2]", &mut nodes).unwrap().nodes().len(),
+            3
+        );
+        assert_eq!(
+            parse_fixed("a [1, 2, 3]", &mut nodes),
+            Err(SuperError::TooManyItems)
+        );
+        assert_eq!(parse_fixed("a [1", &mut nodes), Err(SuperError::Unbalanced));
+    }
+}
@@ -0,0 +1,315 @@
+//! Config laid out flat with [FlatConf], where every node lives in one [Vec]
+//! and lists and groups point at their children by index, so walking over a
+//! large config doesn't chase a pointer for every list or group
+
+use super::cursor::scalar;
+use super::reader::{Event, Reader};
+use super::{Parse, Path, Segment, SuperConf, SuperError, SuperItem, SuperValue};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Config whose items and list elements are all kept in [FlatConf::nodes],
+/// where the children of every list or group are next to each other
+///
+/// Nodes are stored children first, so every list or group comes after all
+/// of the nodes inside of it and the items at the top level come last.
+/// Brackets can only be nested [MAX_DEPTH](crate::reader::MAX_DEPTH) deep
+/// when parsing as it's parsed with a [Reader].
+///
+/// ```
+/// use superconf::{FlatConf, FlatValue, Parse};
+///
+/// let conf = FlatConf::parse("port 8080\nhosts [a, b]").unwrap();
+/// assert_eq!(conf.get("hosts[1]"), Some(&FlatValue::Name("b".into())));
+///
+/// let hosts = conf.get("hosts").unwrap();
+/// assert_eq!(conf.children(hosts).len(), 2);
+/// assert_eq!(conf.nodes.len(), 4);
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct FlatConf<'a> {
+    pub nodes: Vec<FlatNode<'a>>,
+    /// Where the items at the top level are in [FlatConf::nodes]
+    pub items: Range<usize>,
+}
+
+/// Item of a config or group, or an element of a list which has an empty key
+#[derive(Debug, PartialEq, Clone)]
+pub struct FlatNode<'a> {
+    pub key: Cow<'a, str>,
+    pub value: FlatValue<'a>,
+}
+
+/// Flat counterpart to [SuperValue], where lists and groups are where their
+/// children are in [FlatConf::nodes]
+#[derive(Debug, PartialEq, Clone)]
+pub enum FlatValue<'a> {
+    Nothing,
+    Name(Cow<'a, str>),
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    Bytes(Cow<'a, str>),
+    List(Range<usize>),
+    Group(Range<usize>),
+}
+
+/// List or group which is still being parsed, alongside the key of the item
+/// which it's the value of
+struct Frame<'a> {
+    children: Vec<FlatNode<'a>>,
+    key: Option<Cow<'a, str>>,
+    list: bool,
+}
+
+impl<'a> FlatConf<'a> {
+    /// Items at the top level
+    pub fn items(&self) -> &[FlatNode<'a>] {
+        &self.nodes[self.items.clone()]
+    }
+
+    /// Children of a list or group in this config, which is nothing for any
+    /// other value
+    pub fn children(&self, value: &FlatValue<'a>) -> &[FlatNode<'a>] {
+        match value {
+            FlatValue::List(range) | FlatValue::Group(range) => &self.nodes[range.clone()],
+            _ => &[],
+        }
+    }
+
+    /// Gets the value at a path such as `server.hosts[2].port`, see
+    /// [SuperConf::get]
+    pub fn get(&self, path: &str) -> Option<&FlatValue<'a>> {
+        self.get_path(&Path::parse(path).ok()?)
+    }
+
+    /// Gets the value at an already-parsed [Path], see [SuperConf::get_path]
+    pub fn get_path(&self, path: &Path) -> Option<&FlatValue<'a>> {
+        let (first, rest) = path.segments.split_first()?;
+        let mut value = match first {
+            Segment::Key(key) => find(self.items(), key)?,
+            Segment::Index(_) => return None,
+        };
+
+        for segment in rest {
+            value = match (segment, value) {
+                (Segment::Key(key), FlatValue::Group(range)) => {
+                    find(&self.nodes[range.clone()], key)?
+                }
+                (Segment::Index(index), FlatValue::List(range)) => {
+                    &self.nodes[range.clone()].get(*index)?.value
+                }
+                _ => return None,
+            };
+        }
+
+        Some(value)
+    }
+
+    /// Copies into a normal config, still borrowing any text
+    pub fn to_conf(&self) -> SuperConf<'a> {
+        SuperConf {
+            items: self.to_items(self.items()),
+        }
+    }
+
+    fn to_items(&self, nodes: &[FlatNode<'a>]) -> Vec<SuperItem<'a>> {
+        nodes
+            .iter()
+            .map(|node| SuperItem {
+                key: node.key.clone(),
+                value: self.to_value(&node.value),
+                meta: None,
+            })
+            .collect()
+    }
+
+    fn to_value(&self, value: &FlatValue<'a>) -> SuperValue<'a> {
+        match value {
+            FlatValue::Nothing => SuperValue::Nothing,
+            FlatValue::Name(name) => SuperValue::Name(name.clone()),
+            FlatValue::Bool(boolean) => SuperValue::Bool(*boolean),
+            FlatValue::Integer(integer) => SuperValue::Integer(*integer),
+            FlatValue::Float(float) => SuperValue::Float(*float),
+            FlatValue::Bytes(data) => SuperValue::Bytes(Box::new(data.clone())),
+            FlatValue::List(range) => SuperValue::List(Box::new(
+                self.nodes[range.clone()]
+                    .iter()
+                    .map(|node| self.to_value(&node.value))
+                    .collect(),
+            )),
+            FlatValue::Group(range) => SuperValue::group(self.to_items(&self.nodes[range.clone()])),
+        }
+    }
+}
+
+impl<'a> Parse<'a> for FlatConf<'a> {
+    /// Parses a config the same as [SuperConf::parse] would, without ever
+    /// building it as a tree
+    fn parse(input: &'a str) -> Result<Self, SuperError> {
+        let mut nodes = Vec::new();
+        let mut items = Vec::new();
+        let mut stack: Vec<Frame<'a>> = Vec::new();
+        // children of lists and groups which have been closed, kept so that
+        // they can be reused for the next ones
+        let mut spare: Vec<Vec<FlatNode<'a>>> = Vec::new();
+        let mut key = None;
+
+        for event in Reader::new(input) {
+            let event = event?;
+            let value = match event {
+                Event::Key(raw) => {
+                    key = Some(raw.to_key());
+                    continue;
+                }
+                Event::Scalar(raw) => leaf(scalar(raw)),
+                Event::ListStart | Event::GroupStart => {
+                    stack.push(Frame {
+                        children: spare.pop().unwrap_or_default(),
+                        key: key.take(),
+                        list: event == Event::ListStart,
+                    });
+                    continue;
+                }
+                Event::ListEnd | Event::GroupEnd => {
+                    let Some(mut frame) = stack.pop() else {
+                        unreachable!()
+                    };
+                    key = frame.key;
+                    let range = append(&mut nodes, &mut frame.children);
+                    spare.push(frame.children);
+
+                    match frame.list {
+                        true => FlatValue::List(range),
+                        false => FlatValue::Group(range),
+                    }
+                }
+            };
+
+            let node = FlatNode {
+                key: key.take().unwrap_or_default(),
+                value,
+            };
+            match stack.last_mut() {
+                Some(frame) => frame.children.push(node),
+                None => items.push(node),
+            }
+        }
+
+        let items = append(&mut nodes, &mut items);
+        Ok(Self { nodes, items })
+    }
+}
+
+impl<'a> From<&SuperConf<'a>> for FlatConf<'a> {
+    /// Lays out a config flat, in the same order as [FlatConf::parse] would
+    fn from(conf: &SuperConf<'a>) -> Self {
+        let mut nodes = Vec::new();
+        let mut items = flatten_items(&mut nodes, &conf.items);
+        let items = append(&mut nodes, &mut items);
+
+        Self { nodes, items }
+    }
+}
+
+/// Flattens everything inside of `items`, giving the nodes for the items
+/// themselves which are left for the caller to add after
+fn flatten_items<'a>(nodes: &mut Vec<FlatNode<'a>>, items: &[SuperItem<'a>]) -> Vec<FlatNode<'a>> {
+    items
+        .iter()
+        .map(|item| FlatNode {
+            key: item.key.clone(),
+            value: flatten(nodes, &item.value),
+        })
+        .collect()
+}
+
+fn flatten<'a>(nodes: &mut Vec<FlatNode<'a>>, value: &SuperValue<'a>) -> FlatValue<'a> {
+    match value {
+        SuperValue::List(values) => {
+            let mut children: Vec<FlatNode<'a>> = values
+                .iter()
+                .map(|value| FlatNode {
+                    key: Cow::Borrowed(""),
+                    value: flatten(nodes, value),
+                })
+                .collect();
+            FlatValue::List(append(nodes, &mut children))
+        }
+        SuperValue::Group(items) => {
+            let mut children = flatten_items(nodes, items);
+            FlatValue::Group(append(nodes, &mut children))
+        }
+        scalar => leaf(scalar.clone()),
+    }
+}
+
+/// Converts a value which isn't a list or group
+fn leaf(value: SuperValue<'_>) -> FlatValue<'_> {
+    match value {
+        SuperValue::Nothing => FlatValue::Nothing,
+        SuperValue::Name(name) => FlatValue::Name(name),
+        SuperValue::Bool(boolean) => FlatValue::Bool(boolean),
+        SuperValue::Integer(integer) => FlatValue::Integer(integer),
+        SuperValue::Float(float) => FlatValue::Float(float),
+        SuperValue::Bytes(data) => FlatValue::Bytes(*data),
+        SuperValue::List(_) | SuperValue::Group(_) => unreachable!(),
+    }
+}
+
+/// Moves `children` onto the end of `nodes`, giving where they ended up
+fn append<'a>(nodes: &mut Vec<FlatNode<'a>>, children: &mut Vec<FlatNode<'a>>) -> Range<usize> {
+    let start = nodes.len();
+    nodes.append(children);
+    start..nodes.len()
+}
+
+fn find<'n, 'a>(nodes: &'n [FlatNode<'a>], key: &str) -> Option<&'n FlatValue<'a>> {
+    nodes
+        .iter()
+        .find(|node| node.key == key)
+        .map(|node| &node.value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_as_tree() {
+        const TEXT: &str =
+            "a [1] x\nb {c [1, {d e\\ f}], g 2.5}\nempty\nh\\ i true\nj [[], [1,\n 2]]";
+        let tree = SuperConf::parse(TEXT).unwrap();
+        let conf = FlatConf::parse(TEXT).unwrap();
+
+        assert_eq!(conf.to_conf(), tree);
+        assert_eq!(FlatConf::from(&tree), conf);
+        assert_eq!(conf.get("b.c[1].d"), Some(&FlatValue::Name("e f".into())));
+        assert_eq!(conf.get("h i"), Some(&FlatValue::Bool(true)));
+        assert_eq!(conf.get("j[1][1]"), Some(&FlatValue::Integer(2)));
+        assert_eq!(conf.get("b.x"), None);
+    }
+
+    #[test]
+    fn children_first() {
+        let conf = FlatConf::parse("a {b 1, c [2]}\nd").unwrap();
+        let keys: Vec<&str> = conf.nodes.iter().map(|node| &*node.key).collect();
+
+        assert_eq!(keys, ["", "b", "c", "a", "d"]);
+        assert_eq!(conf.items, 3..5);
+        assert_eq!(conf.nodes[2].value, FlatValue::List(0..1));
+        assert_eq!(conf.nodes[3].value, FlatValue::Group(1..3));
+        assert_eq!(conf.children(&FlatValue::Nothing), []);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(FlatConf::parse("a [1"), Err(SuperError::Unbalanced));
+        assert_eq!(
+            FlatConf::parse(&["a ", &"[".repeat(200)].concat()),
+            Err(SuperError::TooDeep)
+        );
+    }
+}
@@ -0,0 +1,404 @@
+//! Formatting of config text with [fmt], which evens out the layout of a
+//! config while keeping its comments and the order of its items
+
+use super::tokens::{Token, Tokens};
+use super::SuperError;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+/// Layout used by [fmt]
+#[derive(Debug, Clone)]
+pub struct FmtOptions {
+    /// Number of spaces used for each level of indentation
+    pub indent: usize,
+    /// Pads the keys of items on lines next to each other so that their
+    /// values start in the same column
+    pub align_values: bool,
+    /// Most blank lines which are kept in a row, where any more are removed
+    pub blank_lines: usize,
+}
+
+impl Default for FmtOptions {
+    fn default() -> Self {
+        Self {
+            indent: 4,
+            align_values: false,
+            blank_lines: 1,
+        }
+    }
+}
+
+/// Item, list element, comment or blank line inside of the config or a list
+/// or group
+enum Entry<'a> {
+    Child(Child<'a>),
+    Comment(&'a str),
+    Blank(usize),
+}
+
+/// Item or list element, where list elements don't have keys
+struct Child<'a> {
+    /// Key as written, still containing any escapes
+    key: Option<&'a str>,
+    value: Value<'a>,
+    /// Comment at the end of the line which the child ends on
+    comment: Option<&'a str>,
+}
+
+enum Value<'a> {
+    /// Value which isn't a list or group as written, or a list or group with
+    /// text after it which makes the whole of it a name
+    Text(&'a str),
+    List(Block<'a>),
+    Group(Block<'a>),
+}
+
+/// Children of the config or a list or group
+#[derive(Default)]
+struct Block<'a> {
+    entries: Vec<Entry<'a>>,
+    /// If it was written across more than one line, which it then stays
+    expanded: bool,
+}
+
+/// List or group which is still being read, alongside the key of the item
+/// which it's the value of
+struct Frame<'a> {
+    block: Block<'a>,
+    key: Option<&'a str>,
+    start: usize,
+    list: bool,
+}
+
+/// Formats config text, giving it even indentation and spacing while keeping
+/// its comments and the order of its items
+///
+/// Lists and groups which were written across multiple lines are given a
+/// line for each child, and the rest are kept on one line. Keys and values
+/// are kept exactly as they were written, so the text always parses into the
+/// same config as before.
+///
+/// ```
+/// use superconf::{fmt, FmtOptions};
+///
+/// let text = "port   8080 # the port\n\n\n\ntls {  enabled true ,\n  # where\ncert a.pem}";
+/// assert_eq!(
+///     fmt(text, &FmtOptions::default()).unwrap(),
+///     "port 8080 # the port\n\ntls {\n    enabled true\n    # where\n    cert a.pem\n}\n"
+/// );
+/// ```
+pub fn fmt(input: &str, options: &FmtOptions) -> Result<String, SuperError> {
+    let block = read(input)?;
+    let mut output = String::new();
+    Formatter {
+        out: &mut output,
+        options,
+    }
+    .entries(&block.entries, 0, false)
+    .expect("writing to a string never fails");
+
+    Ok(output)
+}
+
+/// Reads the layout of `input` without reading any of its values
+fn read(input: &str) -> Result<Block<'_>, SuperError> {
+    let mut stack = Vec::new();
+    let mut block = Block::default();
+    let mut key = None;
+    // newlines in a row, and where the last list or group closed started
+    let mut newlines = 0;
+    let mut closed = None;
+
+    for found in Tokens::new(input) {
+        let (token, span) = found?;
+        let same_line = newlines == 0;
+        if token != Token::Newline {
+            if newlines > 1 {
+                block.entries.push(Entry::Blank(newlines - 1));
+            }
+            newlines = 0;
+        }
+
+        let value = match token {
+            Token::Key(raw) => {
+                key = Some(raw.as_str());
+                continue;
+            }
+            Token::Scalar(raw) => Value::Text(raw.as_str()),
+            Token::ListStart | Token::GroupStart => {
+                stack.push(Frame {
+                    block: core::mem::take(&mut block),
+                    key: key.take(),
+                    start: span.start,
+                    list: token == Token::ListStart,
+                });
+                continue;
+            }
+            Token::ListEnd | Token::GroupEnd => {
+                let Some(frame) = stack.pop() else {
+                    unreachable!()
+                };
+                let inner = core::mem::replace(&mut block, frame.block);
+                block.expanded |= inner.expanded;
+                key = frame.key;
+                closed = Some(frame.start);
+
+                match frame.list {
+                    true => Value::List(inner),
+                    false => Value::Group(inner),
+                }
+            }
+            Token::Trailing(_) => {
+                if let (Some(Entry::Child(child)), Some(start)) = (block.entries.last_mut(), closed)
+                {
+                    child.value = Value::Text(&input[start..span.end]);
+                }
+                continue;
+            }
+            Token::Comment(comment) => {
+                match block.entries.last_mut() {
+                    Some(Entry::Child(child)) if same_line => child.comment = Some(comment),
+                    _ => block.entries.push(Entry::Comment(comment)),
+                }
+                continue;
+            }
+            Token::Newline => {
+                newlines += 1;
+                block.expanded = true;
+                continue;
+            }
+            Token::Comma => continue,
+        };
+
+        block.entries.push(Entry::Child(Child {
+            key: key.take(),
+            value,
+            comment: None,
+        }));
+    }
+
+    Ok(block)
+}
+
+struct Formatter<'o, W: Write> {
+    out: &'o mut W,
+    options: &'o FmtOptions,
+}
+
+impl<W: Write> Formatter<'_, W> {
+    /// Writes `entries` on their own lines at `depth`, with a comma after
+    /// each child if they're `list` elements
+    fn entries(&mut self, entries: &[Entry], depth: usize, list: bool) -> core::fmt::Result {
+        let start = entries
+            .iter()
+            .position(|entry| !matches!(entry, Entry::Blank(_)));
+        let end = entries
+            .iter()
+            .rposition(|entry| !matches!(entry, Entry::Blank(_)));
+        let entries = match (start, end) {
+            (Some(start), Some(end)) => &entries[start..=end],
+            _ => &[],
+        };
+
+        for (ind, entry) in entries.iter().enumerate() {
+            match entry {
+                Entry::Blank(lines) => {
+                    for _ in 0..(*lines).min(self.options.blank_lines) {
+                        self.out.write_char('\n')?;
+                    }
+                    continue;
+                }
+                Entry::Comment(comment) => {
+                    self.indent(depth)?;
+                    self.out.write_str(comment)?;
+                }
+                Entry::Child(child) => {
+                    self.indent(depth)?;
+                    let width = match self.options.align_values {
+                        true => key_width(entries, ind),
+                        false => 0,
+                    };
+                    self.child(child, depth, width)?;
+                    if list {
+                        self.out.write_char(',')?;
+                    }
+                    if let Some(comment) = child.comment {
+                        write!(self.out, " {}", comment)?;
+                    }
+                }
+            }
+            self.out.write_char('\n')?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a child with its key padded to `width`
+    fn child(&mut self, child: &Child, depth: usize, width: usize) -> core::fmt::Result {
+        if let Some(key) = child.key {
+            self.out.write_str(key)?;
+            if !matches!(child.value, Value::Text("")) {
+                let len = key.chars().count();
+                for _ in 0..width.saturating_sub(len).max(1) {
+                    self.out.write_char(' ')?;
+                }
+            }
+        }
+
+        match &child.value {
+            Value::Text(text) => self.out.write_str(text),
+            Value::List(block) => self.block(block, depth, ['[', ']'], true),
+            Value::Group(block) => self.block(block, depth, ['{', '}'], false),
+        }
+    }
+
+    fn block(
+        &mut self,
+        block: &Block,
+        depth: usize,
+        [open, close]: [char; 2],
+        list: bool,
+    ) -> core::fmt::Result {
+        self.out.write_char(open)?;
+
+        let children = block
+            .entries
+            .iter()
+            .filter_map(|entry| match entry {
+                Entry::Child(child) => Some(child),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let comments = block
+            .entries
+            .iter()
+            .any(|entry| matches!(entry, Entry::Comment(_)));
+
+        if block.expanded && (!children.is_empty() || comments) {
+            self.out.write_char('\n')?;
+            self.entries(&block.entries, depth + 1, list)?;
+            self.indent(depth)?;
+        } else {
+            for (ind, child) in children.iter().enumerate() {
+                if ind != 0 {
+                    self.out.write_str(", ")?;
+                }
+                self.child(child, depth, 0)?;
+            }
+            // a trailing blank element is skipped when parsing, so keep the
+            // final nothing by adding its comma explicitly
+            if list
+                && matches!(
+                    children.last(),
+                    Some(Child {
+                        value: Value::Text(""),
+                        ..
+                    })
+                )
+            {
+                self.out.write_char(',')?;
+            }
+        }
+
+        self.out.write_char(close)
+    }
+
+    fn indent(&mut self, depth: usize) -> core::fmt::Result {
+        for _ in 0..depth * self.options.indent {
+            self.out.write_char(' ')?;
+        }
+        Ok(())
+    }
+}
+
+/// Width which the key of the child at `ind` is padded to, which is the
+/// width of the longest key of the items with values on the lines around it
+fn key_width(entries: &[Entry], ind: usize) -> usize {
+    // an item is next to another if it has a value and they're only
+    // separated by items which have values, all on their own line
+    let aligned = |entry: &Entry| match entry {
+        Entry::Child(child) => {
+            child.key.is_some()
+                && !matches!(child.value, Value::Text(""))
+                && !matches!(&child.value, Value::List(block) | Value::Group(block) if block.expanded)
+        }
+        _ => false,
+    };
+    if !aligned(&entries[ind]) {
+        return 0;
+    }
+
+    let start = entries[..ind]
+        .iter()
+        .rposition(|entry| !aligned(entry))
+        .map_or(0, |found| found + 1);
+    let end = entries[ind..]
+        .iter()
+        .position(|entry| !aligned(entry))
+        .map_or(entries.len(), |found| ind + found);
+
+    entries[start..end]
+        .iter()
+        .filter_map(|entry| match entry {
+            Entry::Child(child) => child.key.map(|key| key.chars().count() + 1),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Parse, SuperConf};
+
+    fn check(input: &str, expected: &str) {
+        let output = fmt(input, &FmtOptions::default()).unwrap();
+
+        assert_eq!(output, expected);
+        assert_eq!(fmt(&output, &FmtOptions::default()).unwrap(), output);
+        assert_eq!(
+            SuperConf::parse(&output).unwrap(),
+            SuperConf::parse(input).unwrap()
+        );
+    }
+
+    #[test]
+    fn layout() {
+        check("", "");
+        check("\n\n  a   \n\n", "a\n");
+        check(
+            "port   8080\nname  a   b ,c\nempty  # nothing\n\n\n# about tls\ntls {enabled  true,cert   x}",
+            "port 8080\nname a   b ,c\nempty # nothing\n\n# about tls\ntls {enabled true, cert x}\n",
+        );
+        check("a [1,2 ,  ,]\nb [ ]\nc {\n}", "a [1, 2, ,]\nb []\nc {}\n");
+        check(
+            "a [\n1,\n  [2\n  ,3], # three\n\n {b 1\n  c [4, 5]}]",
+            "a [\n    1,\n    [\n        2,\n        3,\n    ], # three\n\n    {\n        b 1\n        c [4, 5]\n    },\n]\n",
+        );
+        check(
+            "a {  # start\n  b\\ c [1]   x y\n}",
+            "a {\n    # start\n    b\\ c [1]   x y\n}\n",
+        );
+    }
+
+    #[test]
+    fn options() {
+        let options = FmtOptions {
+            indent: 2,
+            align_values: true,
+            blank_lines: 0,
+        };
+        let input = "a 1\nlonger 2\nc\n\ngroup {\nx 1\nyy 2 # c\n}\nd 3";
+
+        assert_eq!(
+            fmt(input, &options).unwrap(),
+            "a      1\nlonger 2\nc\ngroup {\n  x  1\n  yy 2 # c\n}\nd 3\n"
+        );
+        assert_eq!(
+            fmt("a [", &FmtOptions::default()),
+            Err(SuperError::Unbalanced)
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Implementations of [Arbitrary] for fuzzing, behind the `arbitrary` feature
+//!
+//! Generated configs always survive being emitted and parsed again unchanged,
+//! so floats are never NaN, which isn't equal to itself, and names and keys
+//! are never empty, as they can't be written inside of a list or group.
+
+use super::{SuperConf, SuperItem, SuperValue};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+/// The deepest that generated lists and groups are nested
+const MAX_DEPTH: usize = 8;
+
+impl<'a> Arbitrary<'a> for SuperValue<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        value(u, 0)
+    }
+}
+
+impl<'a> Arbitrary<'a> for SuperItem<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        item(u, 0)
+    }
+}
+
+impl<'a> Arbitrary<'a> for SuperConf<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            items: many(u, |u| item(u, 0))?,
+        })
+    }
+}
+
+fn value<'a>(u: &mut Unstructured<'a>, depth: usize) -> Result<SuperValue<'a>> {
+    let kinds = match depth < MAX_DEPTH {
+        true => 7,
+        false => 5,
+    };
+
+    Ok(match u.choose_index(kinds)? {
+        0 => SuperValue::Nothing,
+        1 => SuperValue::Name(text(u)?),
+        2 => SuperValue::Bool(u.arbitrary()?),
+        3 => SuperValue::Integer(u.arbitrary()?),
+        4 => match u.arbitrary()? {
+            float if f64::is_nan(float) => SuperValue::Float(f64::INFINITY),
+            float => SuperValue::Float(float),
+        },
+        5 => SuperValue::list(many(u, |u| value(u, depth + 1))?),
+        _ => SuperValue::group(many(u, |u| item(u, depth + 1))?),
+    })
+}
+
+fn item<'a>(u: &mut Unstructured<'a>, depth: usize) -> Result<SuperItem<'a>> {
+    Ok(SuperItem {
+        key: text(u)?,
+        value: value(u, depth)?,
+        meta: None,
+    })
+}
+
+/// Generates elements for as long as the data says to continue, which stops
+/// once it runs out
+fn many<'a, T>(
+    u: &mut Unstructured<'a>,
+    mut element: impl FnMut(&mut Unstructured<'a>) -> Result<T>,
+) -> Result<Vec<T>> {
+    let mut elements = Vec::new();
+
+    while u.arbitrary()? {
+        elements.push(element(u)?);
+    }
+
+    Ok(elements)
+}
+
+fn text<'a>(u: &mut Unstructured<'a>) -> Result<Cow<'a, str>> {
+    match u.arbitrary()? {
+        "" => Ok(Cow::Borrowed("_")),
+        text => Ok(Cow::Borrowed(text)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Emit, Parse};
+
+    #[test]
+    fn round_trip() {
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+
+        for _ in 0..500 {
+            let bytes: Vec<u8> = (0..256)
+                .map(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 7;
+                    seed ^= seed << 17;
+                    seed as u8
+                })
+                .collect();
+            let conf = SuperConf::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+            let text = conf.emit();
+
+            assert_eq!(SuperConf::parse(&text), Ok(conf), "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,403 @@
+//! Reading configs written in other, flatter formats such as INI files, so
+//! moving over from them doesn't need any hand conversion
+
+use super::{parse_float, Path, SuperConf, SuperError, SuperValue};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+impl<'a> SuperConf<'a> {
+    /// Reads INI text, where each `[section]` becomes a group holding the
+    /// `key=value` lines under it
+    ///
+    /// Dots in section names and keys nest further groups, so `[server.tls]`
+    /// and `server.tls.port=1` go to the same place. Keys can also be split
+    /// from values with `:`, lines starting with `;` or `#` are comments and
+    /// values are read like scalars in superconf, with one pair of quotes
+    /// around them removed. Setting a key inside of something which isn't a
+    /// group gives [SuperError::PathNotFound].
+    pub fn from_ini(text: &'a str) -> Result<Self, SuperError> {
+        import_lines(text, false)
+    }
+
+    /// Reads Java-style properties text, which is like [SuperConf::from_ini]
+    /// without sections but with `!` comments, backslash escapes, lines
+    /// continued by ending them with a backslash and keys separated from
+    /// values by whitespace
+    pub fn from_properties(text: &'a str) -> Result<Self, SuperError> {
+        import_lines(text, true)
+    }
+
+    /// Reads dotenv text, such as from a `.env` file, where every
+    /// `KEY=value` line becomes an item with the key kept as it is
+    ///
+    /// Lines can start with `export`, and `#` starts a comment at the start
+    /// of a line or after whitespace. Values in single quotes are kept as
+    /// they are, values in double quotes can have `\n`, `\t`, `\r`, `\"`
+    /// and `\\` escapes, and both can go over multiple lines. Anything
+    /// unquoted is read like a scalar in superconf. A quote which isn't
+    /// closed gives [SuperError::UnclosedQuote].
+    pub fn from_dotenv(text: &'a str) -> Result<Self, SuperError> {
+        import_dotenv(text, false)
+    }
+
+    /// Reads dotenv text like [SuperConf::from_dotenv], but with keys
+    /// lowercased and split on `_` into groups, so `SERVER_PORT=80` sets
+    /// `server.port`
+    ///
+    /// Setting a key inside of something which isn't a group, like with
+    /// `SERVER=a` then `SERVER_PORT=80`, gives [SuperError::PathNotFound].
+    pub fn from_dotenv_nested(text: &'a str) -> Result<Self, SuperError> {
+        import_dotenv(text, true)
+    }
+}
+
+fn import_lines(text: &str, properties: bool) -> Result<SuperConf<'_>, SuperError> {
+    let mut conf = SuperConf { items: Vec::new() };
+    let mut section = Path::new();
+    let mut lines = text.lines();
+
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+
+        match line.chars().next() {
+            None | Some(';' | '#') => continue,
+            Some('!') if properties => continue,
+            Some('[') if !properties && line.ends_with(']') => {
+                section = dotted(Cow::Borrowed(line[1..line.len() - 1].trim()));
+
+                if conf.get_path(&section).is_none() {
+                    conf.set_path(&section, SuperValue::Group(Box::default()))?;
+                }
+                continue;
+            }
+            _ => (),
+        }
+
+        let (key, value) = match properties {
+            true => {
+                let line = continued(line, &mut lines);
+                let (key, value) = split_pair(&line, true);
+                (
+                    Cow::Owned(unescape_properties(key)),
+                    Cow::Owned(unescape_properties(value)),
+                )
+            }
+            false => {
+                let (key, value) = split_pair(line, false);
+                (Cow::Borrowed(key), Cow::Borrowed(value))
+            }
+        };
+
+        let mut path = section.clone();
+        path.segments.extend(dotted(key).segments);
+        conf.set_path(&path, scalar(value))?;
+    }
+
+    Ok(conf)
+}
+
+fn import_dotenv(text: &str, nested: bool) -> Result<SuperConf<'_>, SuperError> {
+    let mut conf = SuperConf { items: Vec::new() };
+    let mut rest = text.trim_start();
+
+    while !rest.is_empty() {
+        let first = rest.split('\n').next().unwrap_or(rest);
+        if first.starts_with('#') {
+            rest = rest[first.len()..].trim_start();
+            continue;
+        }
+
+        let line = match first.strip_prefix("export") {
+            Some(after) if after.starts_with([' ', '\t']) => after.trim_start(),
+            _ => first,
+        };
+        let (key, value) = match line.find('=') {
+            Some(ind) => {
+                // quoted values can go past the end of the line
+                let (value, after) = dotenv_value(&rest[first.len() - line.len() + ind + 1..])?;
+                rest = after;
+                (line[..ind].trim_end(), value)
+            }
+            None => {
+                rest = &rest[first.len()..];
+                (line.trim_end(), SuperValue::Nothing)
+            }
+        };
+
+        let mut path = Path::new();
+        match nested {
+            true => key
+                .split('_')
+                .for_each(|part| path.push_key(part.to_lowercase())),
+            false => path.push_key(key),
+        }
+        conf.set_path(&path, value)?;
+        rest = rest.trim_start();
+    }
+
+    Ok(conf)
+}
+
+/// Reads a dotenv value from the start of `text`, giving the text after the
+/// line it ends on
+fn dotenv_value(text: &str) -> Result<(SuperValue<'_>, &str), SuperError> {
+    let text = text.trim_start_matches([' ', '\t']);
+
+    match text.chars().next() {
+        Some(quote @ ('\'' | '"')) => {
+            let end = closing_quote(&text[1..], quote).ok_or(SuperError::UnclosedQuote)? + 1;
+            let inner = &text[1..end];
+            let inner = match quote == '"' && inner.contains('\\') {
+                true => Cow::Owned(unescape_dotenv(inner)),
+                false => Cow::Borrowed(inner),
+            };
+
+            // anything after the closing quote on the same line is ignored
+            let after = &text[end + 1..];
+            let after = after.find('\n').map_or("", |ind| &after[ind..]);
+            Ok((SuperValue::Name(inner), after))
+        }
+        _ => {
+            let line = text.split('\n').next().unwrap_or(text);
+            let value = line
+                .match_indices('#')
+                .find(|(ind, _)| *ind == 0 || line[..*ind].ends_with([' ', '\t']))
+                .map_or(line, |(ind, _)| &line[..ind]);
+
+            Ok((scalar(Cow::Borrowed(value.trim_end())), &text[line.len()..]))
+        }
+    }
+}
+
+/// Finds the closing `quote` in `text`, skipping escaped quotes for double
+/// quotes
+fn closing_quote(text: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+
+    text.char_indices().find_map(|(ind, c)| {
+        let found = !escaped && c == quote;
+        escaped = quote == '"' && !escaped && c == '\\';
+        found.then_some(ind)
+    })
+}
+
+fn unescape_dotenv(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some(escaped @ ('n' | 't' | 'r' | '"' | '\\'))) => {
+                chars.next();
+                output.push(match escaped {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    other => other,
+                });
+            }
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+/// Joins any following lines onto `line` while it ends in an unescaped
+/// backslash, leaving out the leading whitespace of each
+fn continued<'a>(line: &'a str, lines: &mut core::str::Lines<'a>) -> Cow<'a, str> {
+    if !super::escaped_end(line) {
+        return Cow::Borrowed(line);
+    }
+
+    let mut joined = String::from(&line[..line.len() - 1]);
+    for next in lines.by_ref() {
+        let next = next.trim_start();
+
+        match super::escaped_end(next) {
+            true => joined.push_str(&next[..next.len() - 1]),
+            false => {
+                joined.push_str(next);
+                break;
+            }
+        }
+    }
+
+    Cow::Owned(joined)
+}
+
+/// Splits a line at the first `=` or `:`, or the first unescaped whitespace
+/// for properties
+fn split_pair(line: &str, properties: bool) -> (&str, &str) {
+    let mut escaped = false;
+    let split = line.char_indices().find(|(_, c)| {
+        let found = !escaped && (*c == '=' || *c == ':' || (properties && c.is_whitespace()));
+        escaped = properties && !escaped && *c == '\\';
+        found
+    });
+
+    match split {
+        // properties can have whitespace around the separator as well
+        Some((ind, c)) if c.is_whitespace() => {
+            let value = line[ind..].trim_start();
+            let value = value.strip_prefix(['=', ':']).unwrap_or(value);
+            (&line[..ind], value.trim_start())
+        }
+        Some((ind, c)) => (
+            line[..ind].trim_end(),
+            line[ind + c.len_utf8()..].trim_start(),
+        ),
+        None => (line, ""),
+    }
+}
+
+fn unescape_properties(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => output.push('\n'),
+                Some('t') => output.push('\t'),
+                Some('r') => output.push('\r'),
+                Some('f') => output.push('\u{c}'),
+                Some('u') => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    output.extend(u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32));
+                }
+                Some(other) => output.push(other),
+                None => (),
+            },
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+/// Splits a key on its dots into a path
+fn dotted(key: Cow<'_, str>) -> Path<'_> {
+    let mut path = Path::new();
+
+    match key {
+        Cow::Borrowed(key) => key.split('.').for_each(|part| path.push_key(part.trim())),
+        Cow::Owned(key) => key
+            .split('.')
+            .for_each(|part| path.push_key(String::from(part.trim()))),
+    }
+
+    path
+}
+
+/// Reads text as a scalar by the same rules as superconf, apart from keeping
+/// backslashes and removing a pair of quotes
+fn scalar(text: Cow<'_, str>) -> SuperValue<'_> {
+    let unquoted = match &*text {
+        quoted
+            if quoted.len() >= 2
+                && (quoted.starts_with('"') && quoted.ends_with('"')
+                    || quoted.starts_with('\'') && quoted.ends_with('\'')) =>
+        {
+            return SuperValue::Name(match text {
+                Cow::Borrowed(text) => Cow::Borrowed(&text[1..text.len() - 1]),
+                Cow::Owned(text) => Cow::Owned(text[1..text.len() - 1].into()),
+            })
+        }
+        unquoted => unquoted,
+    };
+
+    match unquoted {
+        "" => SuperValue::Nothing,
+        "true" => SuperValue::Bool(true),
+        "false" => SuperValue::Bool(false),
+        _ => match (unquoted.parse(), parse_float(unquoted)) {
+            (Ok(integer), _) => SuperValue::Integer(integer),
+            (_, Some(float)) => SuperValue::Float(float),
+            _ => SuperValue::Name(text),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+
+    #[test]
+    fn ini() {
+        let conf = SuperConf::from_ini(
+            "; defaults\nname = example\n\n[server]\nport=8080\nhost: \"local host\"\n\n[server.tls]\nenabled = true\npath = C:\\certs\n[empty]\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            conf,
+            SuperConf::parse(
+                "name example\nserver {port 8080, host local\\ host, tls {enabled true, path C:\\\\certs}}\nempty {}"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            SuperConf::from_ini("a = 1\na.b = 2"),
+            Err(SuperError::PathNotFound)
+        );
+    }
+
+    #[test]
+    fn properties() {
+        let conf = SuperConf::from_properties(
+            "# comment\n! also comment\nserver.port 8080\nserver.host = a\\\n    b\nmessage=tab\\there \\u0041\nkey\\ with\\ spaces : 1.5\nempty\n",
+        )
+        .unwrap();
+
+        assert_eq!(conf.get("server.port"), Some(&SuperValue::Integer(8080)));
+        assert_eq!(
+            conf.get("server.host"),
+            Some(&SuperValue::Name("ab".into()))
+        );
+        assert_eq!(
+            conf.get("message"),
+            Some(&SuperValue::Name("tab\there A".into()))
+        );
+        assert_eq!(conf.get("key with spaces"), Some(&SuperValue::Float(1.5)));
+        assert_eq!(conf.get("empty"), Some(&SuperValue::Nothing));
+    }
+
+    #[test]
+    fn dotenv() {
+        let text = "# database\nexport DATABASE_URL=postgres://localhost/db # local\nDATABASE_POOL = 5\nGREETING=\"hello\\n\\\"world\\\"\"\nRAW='no \\n escapes'\nMULTI=\"a\nb\" ignored\nTAG=a#b\nEMPTY=\n";
+        let conf = SuperConf::from_dotenv(text).unwrap();
+
+        assert_eq!(
+            conf.get("DATABASE_URL"),
+            Some(&SuperValue::Name("postgres://localhost/db".into()))
+        );
+        assert_eq!(conf.get("DATABASE_POOL"), Some(&SuperValue::Integer(5)));
+        assert_eq!(
+            conf.get("GREETING"),
+            Some(&SuperValue::Name("hello\n\"world\"".into()))
+        );
+        assert_eq!(
+            conf.get("RAW"),
+            Some(&SuperValue::Name("no \\n escapes".into()))
+        );
+        assert_eq!(conf.get("MULTI"), Some(&SuperValue::Name("a\nb".into())));
+        assert_eq!(conf.get("TAG"), Some(&SuperValue::Name("a#b".into())));
+        assert_eq!(conf.get("EMPTY"), Some(&SuperValue::Nothing));
+        assert_eq!(conf.items.len(), 7);
+
+        let nested = SuperConf::from_dotenv_nested(text).unwrap();
+        assert_eq!(nested.get("database.pool"), Some(&SuperValue::Integer(5)));
+        assert_eq!(
+            SuperConf::from_dotenv("A=\"b"),
+            Err(SuperError::UnclosedQuote)
+        );
+        assert_eq!(
+            SuperConf::from_dotenv_nested("A=1\nA_B=2"),
+            Err(SuperError::PathNotFound)
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Interning of keys into small [Symbol] ids, so that configs which repeat
+//! the same keys many times over can be searched and compared without
+//! comparing any strings
+
+use super::{Path, Segment, SuperConf, SuperItem, SuperValue};
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Id standing in for a key, which only means something alongside the
+/// [Interner] which gave it out
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// Position of the key in the order it was first interned, starting at 0
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Table of every key interned so far, giving each distinct key one [Symbol]
+///
+/// ```
+/// use superconf::{Interner, Parse, Path, SuperConf, SuperValue};
+///
+/// let conf = SuperConf::parse("hosts {a {port 80}, b {port 8080}}").unwrap();
+/// let mut interner = Interner::new();
+/// let interned = interner.conf(&conf);
+///
+/// let path = interner.path(&Path::parse("hosts.b.port").unwrap()).unwrap();
+/// assert_eq!(interned.get(&path).unwrap().scalar(), Some(&SuperValue::Integer(8080)));
+/// assert_eq!(interner.len(), 4);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Interner<'a> {
+    keys: Vec<Cow<'a, str>>,
+    symbols: BTreeMap<Cow<'a, str>, Symbol>,
+}
+
+impl<'a> Interner<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the symbol for `key`, giving it a new one if it hasn't been seen
+    ///
+    /// # Panics
+    ///
+    /// If more than [u32::MAX] distinct keys are interned.
+    pub fn intern(&mut self, key: impl Into<Cow<'a, str>>) -> Symbol {
+        let key = key.into();
+        if let Some(symbol) = self.symbols.get(&*key) {
+            return *symbol;
+        }
+
+        let symbol = Symbol(u32::try_from(self.keys.len()).expect("too many keys to intern"));
+        self.keys.push(key.clone());
+        self.symbols.insert(key, symbol);
+        symbol
+    }
+
+    /// Gets the symbol for `key` without interning it, which is nothing if
+    /// it hasn't been seen so can't be in anything interned so far
+    pub fn get(&self, key: &str) -> Option<Symbol> {
+        self.symbols.get(key).copied()
+    }
+
+    /// Gets the key which `symbol` stands for
+    pub fn resolve(&self, symbol: Symbol) -> Option<&str> {
+        self.keys.get(symbol.index()).map(|key| &**key)
+    }
+
+    /// Number of distinct keys interned
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Converts a path into one made of symbols, which is nothing if any of
+    /// its keys haven't been seen
+    pub fn path(&self, path: &Path) -> Option<Vec<SymbolSegment>> {
+        path.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Key(key) => self.get(key).map(SymbolSegment::Key),
+                Segment::Index(index) => Some(SymbolSegment::Index(*index)),
+            })
+            .collect()
+    }
+
+    /// Interns every key in `conf`, giving a copy of its layout with the keys
+    /// swapped for symbols and the scalars borrowed from it
+    pub fn conf<'c>(&mut self, conf: &'c SuperConf<'a>) -> InternedConf<'c, 'a> {
+        InternedConf {
+            items: self.items(&conf.items),
+        }
+    }
+
+    fn items<'c>(&mut self, items: &'c [SuperItem<'a>]) -> Vec<InternedItem<'c, 'a>> {
+        items
+            .iter()
+            .map(|item| InternedItem {
+                key: self.intern(item.key.clone()),
+                value: self.value(&item.value),
+            })
+            .collect()
+    }
+
+    fn value<'c>(&mut self, value: &'c SuperValue<'a>) -> InternedValue<'c, 'a> {
+        match value {
+            SuperValue::List(values) => {
+                InternedValue::List(values.iter().map(|value| self.value(value)).collect())
+            }
+            SuperValue::Group(items) => InternedValue::Group(self.items(items)),
+            scalar => InternedValue::Scalar(scalar),
+        }
+    }
+}
+
+/// Part of a path made of symbols, see [Segment]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SymbolSegment {
+    Key(Symbol),
+    Index(usize),
+}
+
+/// Config with its keys swapped for symbols, made by [Interner::conf]
+///
+/// Configs interned by the same interner are equal if their keys and values
+/// are, without comparing the text of any keys.
+#[derive(Debug, PartialEq, Clone)]
+pub struct InternedConf<'c, 'a> {
+    pub items: Vec<InternedItem<'c, 'a>>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct InternedItem<'c, 'a> {
+    pub key: Symbol,
+    pub value: InternedValue<'c, 'a>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum InternedValue<'c, 'a> {
+    /// Any value which isn't a list or group
+    Scalar(&'c SuperValue<'a>),
+    List(Vec<InternedValue<'c, 'a>>),
+    Group(Vec<InternedItem<'c, 'a>>),
+}
+
+impl<'c, 'a> InternedValue<'c, 'a> {
+    /// Gets the value if it's a scalar
+    pub fn scalar(&self) -> Option<&'c SuperValue<'a>> {
+        match self {
+            Self::Scalar(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl<'c, 'a> InternedConf<'c, 'a> {
+    /// Gets the value at a path from [Interner::path], where the first item
+    /// is used if a key is repeated like with [SuperConf::get_path]
+    pub fn get(&self, path: &[SymbolSegment]) -> Option<&InternedValue<'c, 'a>> {
+        let (first, rest) = path.split_first()?;
+        let mut value = match first {
+            SymbolSegment::Key(key) => find(&self.items, *key)?,
+            SymbolSegment::Index(_) => return None,
+        };
+
+        for segment in rest {
+            value = match (segment, value) {
+                (SymbolSegment::Key(key), InternedValue::Group(items)) => find(items, *key)?,
+                (SymbolSegment::Index(index), InternedValue::List(values)) => values.get(*index)?,
+                _ => return None,
+            };
+        }
+
+        Some(value)
+    }
+}
+
+fn find<'i, 'c, 'a>(
+    items: &'i [InternedItem<'c, 'a>],
+    key: Symbol,
+) -> Option<&'i InternedValue<'c, 'a>> {
+    items
+        .iter()
+        .find(|item| item.key == key)
+        .map(|item| &item.value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+
+    #[test]
+    fn repeated_keys() {
+        let first = SuperConf::parse("hosts [{name a, port 80}, {name b, port 81}]").unwrap();
+        let second = SuperConf::parse("hosts [{name a, port 80}, {name b, port 81}]").unwrap();
+        let mut interner = Interner::new();
+        let interned = interner.conf(&first);
+
+        assert_eq!(interner.len(), 3);
+        assert_eq!(
+            interner.resolve(interner.get("port").unwrap()),
+            Some("port")
+        );
+        assert_eq!(interner.conf(&second), interned);
+        assert_eq!(interner.len(), 3);
+
+        let path = interner
+            .path(&Path::parse("hosts[1].port").unwrap())
+            .unwrap();
+        assert_eq!(
+            interned.get(&path).and_then(InternedValue::scalar),
+            Some(&SuperValue::Integer(81))
+        );
+        assert_eq!(interner.path(&Path::parse("hosts[1].tls").unwrap()), None);
+    }
+}
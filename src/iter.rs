@@ -0,0 +1,96 @@
+use super::{Path, Segment, SuperItem, SuperValue};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::Enumerate;
+use core::slice;
+
+/// Iterator over every scalar inside of a config alongside its full path,
+/// created by [SuperConf::leaves](crate::SuperConf::leaves)
+#[derive(Debug, Clone)]
+pub struct Leaves<'b, 'a> {
+    stack: Vec<Frame<'b, 'a>>,
+    path: Path<'a>,
+}
+
+#[derive(Debug, Clone)]
+enum Frame<'b, 'a> {
+    Items(slice::Iter<'b, SuperItem<'a>>),
+    Values(Enumerate<slice::Iter<'b, SuperValue<'a>>>),
+}
+
+impl<'b, 'a> Leaves<'b, 'a> {
+    pub(crate) fn new(items: &'b [SuperItem<'a>]) -> Self {
+        Self {
+            stack: vec![Frame::Items(items.iter())],
+            path: Path::new(),
+        }
+    }
+}
+
+impl<'b, 'a> Iterator for Leaves<'b, 'a> {
+    type Item = (Path<'a>, &'b SuperValue<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (segment, value) = match self.stack.last_mut()? {
+                Frame::Items(items) => match items.next() {
+                    Some(item) => (Segment::Key(item.key.clone()), &item.value),
+                    None => {
+                        self.stack.pop();
+                        self.path.pop();
+                        continue;
+                    }
+                },
+                Frame::Values(values) => match values.next() {
+                    Some((index, value)) => (Segment::Index(index), value),
+                    None => {
+                        self.stack.pop();
+                        self.path.pop();
+                        continue;
+                    }
+                },
+            };
+
+            self.path.segments.push(segment);
+
+            match value {
+                SuperValue::Group(items) => self.stack.push(Frame::Items(items.iter())),
+                SuperValue::List(values) => {
+                    self.stack.push(Frame::Values(values.iter().enumerate()))
+                }
+                scalar => {
+                    let path = self.path.clone();
+                    self.path.pop();
+                    return Some((path, scalar));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parse, SuperConf, SuperValue};
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn nested_leaves() {
+        let conf = SuperConf::parse("a 1\nb {c [2, {d 3}], e {}}\nf true").unwrap();
+        let leaves: Vec<(String, &SuperValue)> = conf
+            .leaves()
+            .map(|(path, value)| (path.to_string(), value))
+            .collect();
+
+        assert_eq!(
+            leaves,
+            vec![
+                ("a".into(), &SuperValue::Integer(1)),
+                ("b.c[0]".into(), &SuperValue::Integer(2)),
+                ("b.c[1].d".into(), &SuperValue::Integer(3)),
+                ("f".into(), &SuperValue::Bool(true))
+            ]
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Conversions to and from [serde_json::Value], behind the `json` feature
+//!
+//! Types map onto each other directly, so nothing is null, names are strings,
+//! lists are arrays and groups are objects. Where JSON can't hold something
+//! losslessly the closest value is used instead:
+//!
+//! - Floats which are NaN or infinite become null
+//! - Integers too large for an [i64] become floats
+//! - Empty strings become nothing, as an empty name can't be written in lists
+//! - Groups with duplicate keys only keep the last, and objects are sorted by
+//!   key unless serde_json's `preserve_order` feature is enabled
+
+use super::{SuperConf, SuperItem, SuperValue};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde_json::{Map, Number, Value};
+
+impl<'a> SuperValue<'a> {
+    /// Converts into a JSON value, see the [module](self) for how
+    pub fn to_json(&self) -> Value {
+        match self {
+            Self::Nothing => Value::Null,
+            Self::Name(name) => Value::String(name.to_string()),
+            Self::Bool(boolean) => Value::Bool(*boolean),
+            Self::Integer(integer) => Value::Number((*integer).into()),
+            Self::Float(float) => Number::from_f64(*float).map_or(Value::Null, Value::Number),
+            Self::Bytes(data) => Value::String(data.to_string()),
+            Self::List(values) => Value::Array(values.iter().map(Self::to_json).collect()),
+            Self::Group(items) => Value::Object(items_to_json(items)),
+        }
+    }
+
+    /// Converts from a JSON value, borrowing all strings from it
+    pub fn from_json(value: &'a Value) -> Self {
+        match value {
+            Value::Null => Self::Nothing,
+            Value::String(string) if string.is_empty() => Self::Nothing,
+            Value::String(string) => Self::Name(Cow::Borrowed(string)),
+            Value::Bool(boolean) => Self::Bool(*boolean),
+            Value::Number(number) => match (number.as_i64(), number.as_f64()) {
+                (Some(integer), _) => Self::Integer(integer),
+                (None, Some(float)) => Self::Float(float),
+                (None, None) => Self::Nothing,
+            },
+            Value::Array(values) => {
+                Self::List(Box::new(values.iter().map(Self::from_json).collect()))
+            }
+            Value::Object(object) => Self::group(items_from_json(object)),
+        }
+    }
+}
+
+impl<'a> SuperConf<'a> {
+    /// Converts into a JSON object of every item, see [SuperValue::to_json]
+    pub fn to_json(&self) -> Value {
+        Value::Object(items_to_json(&self.items))
+    }
+
+    /// Converts from a JSON object, or gives [None] if it's any other value
+    pub fn from_json(value: &'a Value) -> Option<Self> {
+        match value {
+            Value::Object(object) => Some(Self {
+                items: items_from_json(object),
+            }),
+            _ => None,
+        }
+    }
+}
+
+fn items_to_json(items: &[SuperItem]) -> Map<String, Value> {
+    items
+        .iter()
+        .map(|item| (item.key.to_string(), item.value.to_json()))
+        .collect()
+}
+
+fn items_from_json(object: &Map<String, Value>) -> Vec<SuperItem<'_>> {
+    object
+        .iter()
+        .map(|(key, value)| SuperItem {
+            key: Cow::Borrowed(key),
+            value: SuperValue::from_json(value),
+            meta: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+    use serde_json::json;
+
+    #[test]
+    fn round_trip() {
+        let conf = SuperConf::parse(
+            "name server\nports [80, 443]\ntls {enabled true, ratio 0.5}\nproxy\n",
+        )
+        .unwrap();
+        let json = conf.to_json();
+
+        assert_eq!(
+            json,
+            json!({
+                "name": "server",
+                "ports": [80, 443],
+                "tls": {"enabled": true, "ratio": 0.5},
+                "proxy": null,
+            })
+        );
+        assert_eq!(
+            SuperConf::from_json(&json).unwrap().get("tls.ratio"),
+            conf.get("tls.ratio")
+        );
+    }
+
+    #[test]
+    fn lossy() {
+        assert_eq!(SuperValue::Float(f64::NAN).to_json(), Value::Null);
+        assert_eq!(
+            SuperValue::from_json(&json!(u64::MAX)),
+            SuperValue::Float(u64::MAX as f64)
+        );
+        assert_eq!(SuperValue::from_json(&json!("")), SuperValue::Nothing);
+        assert_eq!(SuperConf::from_json(&json!([1])), None);
+    }
+}
@@ -0,0 +1,245 @@
+//! Configs which only parse the values which are used, with [LazyConf] or
+//! [SuperConf::extract]
+
+use super::cursor::Cursor;
+use super::{Meta, Parse, Path, Segment, SuperConf, SuperError, SuperItem, SuperValue};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::OnceCell;
+
+/// Alternative to [SuperConf] which keeps the text of each value and only
+/// parses it the first time it's used
+///
+/// Brackets are still checked for every value when parsing, so any config
+/// which [SuperConf] can't parse can't be parsed as this either.
+///
+/// ```
+/// use superconf::{LazyConf, Parse, SuperValue};
+///
+/// let conf = LazyConf::parse("port 8080\nhosts [a, b, c]").unwrap();
+///
+/// assert_eq!(conf.items[1].raw(), "[a, b, c]");
+/// assert_eq!(conf.get("port"), Some(&SuperValue::Integer(8080)));
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct LazyConf<'a> {
+    pub items: Vec<LazyItem<'a>>,
+}
+
+/// Item of a [LazyConf], holding the text of its value until it's needed
+#[derive(Debug, Clone)]
+pub struct LazyItem<'a> {
+    pub key: Cow<'a, str>,
+    raw: &'a str,
+    value: OnceCell<SuperValue<'a>>,
+    /// Documentation and attributes, if the item has any
+    pub meta: Option<Box<Meta>>,
+}
+
+impl PartialEq for LazyItem<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.raw == other.raw && self.meta == other.meta
+    }
+}
+
+impl<'a> LazyItem<'a> {
+    /// Text of the value as it was written, without any trivia around it
+    pub fn raw(&self) -> &'a str {
+        self.raw
+    }
+
+    /// Gets the value, parsing it if this is the first time
+    pub fn value(&self) -> &SuperValue<'a> {
+        self.value.get_or_init(|| parse_raw(self.raw))
+    }
+
+    /// Checks if the value has been parsed yet, which it always has been if
+    /// it's nothing
+    pub fn is_parsed(&self) -> bool {
+        self.value.get().is_some()
+    }
+
+    /// Parses the value if it hasn't been yet, giving a normal item
+    pub fn into_item(self) -> SuperItem<'a> {
+        let raw = self.raw;
+        SuperItem {
+            key: self.key,
+            value: self.value.into_inner().unwrap_or_else(|| parse_raw(raw)),
+            meta: self.meta,
+        }
+    }
+}
+
+impl<'a> LazyConf<'a> {
+    /// Gets the value at a path such as `server.hosts[2].port`, only parsing
+    /// the value of the item which the path starts at
+    pub fn get(&self, path: &str) -> Option<&SuperValue<'a>> {
+        self.get_path(&Path::parse(path).ok()?)
+    }
+
+    /// Gets the value at an already-parsed [Path]; the first item is used if
+    /// a key is repeated
+    pub fn get_path(&self, path: &Path) -> Option<&SuperValue<'a>> {
+        let (first, rest) = path.segments.split_first()?;
+        let value = match first {
+            Segment::Key(key) => self.items.iter().find(|item| item.key == *key)?.value(),
+            Segment::Index(_) => return None,
+        };
+
+        descend(value, rest)
+    }
+
+    /// Parses every value which hasn't been yet, giving a normal config
+    pub fn into_conf(self) -> SuperConf<'a> {
+        SuperConf {
+            items: self.items.into_iter().map(LazyItem::into_item).collect(),
+        }
+    }
+}
+
+impl<'a> SuperConf<'a> {
+    /// Parses only the values at `paths` out of `input`, giving each one in
+    /// the same order, for when a few values are needed out of a huge config
+    ///
+    /// Every other item is skipped over once its brackets are checked, and
+    /// only the first item is used if a key is repeated like [SuperConf::get].
+    ///
+    /// ```
+    /// use superconf::{SuperConf, SuperValue};
+    ///
+    /// let text = "server {port 8080, host a}\nlog {level debug}\nhuge [1, 2, 3]";
+    /// let values = SuperConf::extract(text, &["server.port", "log.level", "missing"]).unwrap();
+    ///
+    /// assert_eq!(
+    ///     values,
+    ///     [Some(SuperValue::Integer(8080)), Some(SuperValue::Name("debug".into())), None]
+    /// );
+    /// ```
+    pub fn extract(
+        input: &'a str,
+        paths: &[&str],
+    ) -> Result<Vec<Option<SuperValue<'a>>>, SuperError> {
+        let paths = paths
+            .iter()
+            .map(|path| Path::parse(path))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut found = vec![None; paths.len()];
+        let mut done = vec![false; paths.len()];
+
+        for item in Cursor::new(input).raw_conf()? {
+            let (raw, mut value) = (item.raw, item.value);
+            for (ind, path) in paths.iter().enumerate() {
+                let rest = match path.segments.split_first() {
+                    Some((Segment::Key(key), rest)) if !done[ind] && *key == item.key => rest,
+                    _ => continue,
+                };
+                let value = value.get_or_insert_with(|| parse_raw(raw));
+
+                found[ind] = descend(value, rest).cloned();
+                done[ind] = true;
+            }
+        }
+        Ok(found)
+    }
+}
+
+impl<'a> Parse<'a> for LazyConf<'a> {
+    fn parse(input: &'a str) -> Result<Self, SuperError> {
+        let items = Cursor::new(input)
+            .raw_conf()?
+            .into_iter()
+            .map(|item| LazyItem {
+                key: item.key,
+                raw: item.raw,
+                value: item.value.map_or_else(OnceCell::new, OnceCell::from),
+                meta: item.meta,
+            })
+            .collect();
+
+        Ok(Self { items })
+    }
+}
+
+/// Follows the `rest` of a path from the value which it starts at
+fn descend<'v, 'a>(mut value: &'v SuperValue<'a>, rest: &[Segment]) -> Option<&'v SuperValue<'a>> {
+    for segment in rest {
+        value = match (segment, value) {
+            (Segment::Key(key), SuperValue::Group(items)) => {
+                &items.iter().find(|item| item.key == *key)?.value
+            }
+            (Segment::Index(index), SuperValue::List(values)) => values.get(*index)?,
+            _ => return None,
+        };
+    }
+
+    Some(value)
+}
+
+fn parse_raw(raw: &str) -> SuperValue<'_> {
+    // brackets were checked when the item was split off, which is the only
+    // thing which parsing a value can fail on
+    SuperValue::parse(raw).expect("lazy value was checked when parsed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_as_tree() {
+        const TEXT: &str =
+            "a [1] x\n## b\nb {c [1, {d e}]} # f\n\nempty\n@secret\ng\t h # i\nj [1,\n 2]\nk \\ l\\\n m\nn{ o} p\n@dangling";
+        let conf = LazyConf::parse(TEXT).unwrap();
+
+        assert!(!conf.items[1].is_parsed() && conf.items[2].is_parsed());
+        assert_eq!(conf.get("b.c[1].d"), Some(&SuperValue::Name("e".into())));
+        assert!(conf.items[1].is_parsed() && !conf.items[0].is_parsed());
+        assert_eq!(conf.items[4].raw(), "[1,\n 2]");
+        assert_eq!(conf.items[6].raw(), "o} p");
+        assert_eq!(
+            conf.items[3].meta.as_ref().map(|meta| meta.attrs.len()),
+            Some(1)
+        );
+        assert_eq!(conf.into_conf(), SuperConf::parse(TEXT).unwrap());
+    }
+
+    #[test]
+    fn extracted() {
+        const TEXT: &str = "a {b [1, {c d}]}\ne 1\na {f 2}\n@x\ng\nh [x] y";
+        let extract = |paths: &[&str]| SuperConf::extract(TEXT, paths);
+
+        assert_eq!(
+            extract(&["a.b[1].c", "e", "a.f", "g", "h", "a.b[2]", "e", "@x"]),
+            Ok(vec![
+                Some(SuperValue::Name("d".into())),
+                Some(SuperValue::Integer(1)),
+                None,
+                Some(SuperValue::Nothing),
+                Some(SuperValue::Name("[x] y".into())),
+                None,
+                Some(SuperValue::Integer(1)),
+                None,
+            ])
+        );
+        assert_eq!(extract(&[]), Ok(vec![]));
+        assert_eq!(extract(&["a..b"]), Err(SuperError::InvalidPath));
+        assert_eq!(
+            SuperConf::extract("a 1\nb [", &["a"]),
+            Err(SuperError::Unbalanced)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        for input in ["a [1", "a ]", "a {b [}]", "a\nb [1] ]"] {
+            assert_eq!(
+                LazyConf::parse(input),
+                Err(SuperError::Unbalanced),
+                "{}",
+                input
+            );
+        }
+    }
+}
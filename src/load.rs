@@ -0,0 +1,264 @@
+//! Loading configs from files and readers, behind the `std` feature
+//!
+//! Parsed configs borrow from their text, so these read it in and then copy
+//! everything into a [SuperConf] which owns all of its text instead.
+
+use super::migrate::{MigrateError, Migrated, Migrator};
+use super::{Parse, Path, SuperConf, SuperError};
+use core::fmt;
+use std::boxed::Box;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::string::String;
+use std::vec::Vec;
+
+/// Error from loading a config with [SuperConf::from_file],
+/// [SuperConf::from_reader], [SuperConf::load_and_migrate] or
+/// [SuperConf::load_dir]
+#[derive(Debug)]
+pub enum LoadError {
+    /// When reading failed, or the text wasn't valid UTF-8
+    Io(io::Error),
+    /// When the text was read but couldn't be parsed
+    Parse(SuperError),
+    /// When the config was parsed but couldn't be migrated
+    Migrate(MigrateError),
+    /// When one of the files of a directory couldn't be loaded
+    InFile { path: PathBuf, err: Box<LoadError> },
+}
+
+/// Which file each value of a config loaded by [SuperConf::load_dir] came
+/// from
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Sources {
+    /// Path of every scalar in the config in the order they're found,
+    /// alongside the last file which set it
+    pub values: Vec<(Path<'static>, PathBuf)>,
+}
+
+impl Sources {
+    /// Finds the file which set the scalar at `path`
+    pub fn get(&self, path: &str) -> Option<&std::path::Path> {
+        let path = Path::parse(path).ok()?;
+        self.values
+            .iter()
+            .find(|(found, _)| *found == path)
+            .map(|(_, file)| file.as_path())
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "couldn't read config: {}", err),
+            Self::Parse(err) => write!(f, "couldn't parse config: {}", err),
+            Self::Migrate(err) => write!(f, "couldn't migrate config: {}", err),
+            Self::InFile { path, err } => write!(f, "{}: {}", path.display(), err),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Parse(err) => Some(err),
+            Self::Migrate(err) => Some(err),
+            Self::InFile { err, .. } => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<SuperError> for LoadError {
+    fn from(err: SuperError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl From<MigrateError> for LoadError {
+    fn from(err: MigrateError) -> Self {
+        Self::Migrate(err)
+    }
+}
+
+impl SuperConf<'static> {
+    /// Reads and parses the file at `path`
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, LoadError> {
+        Self::from_reader(std::fs::File::open(path)?)
+    }
+
+    /// Reads everything from `reader` and parses it
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, LoadError> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+
+        let conf = SuperConf::parse(&text)?;
+        Ok(conf.into_owned())
+    }
+
+    /// Reads and parses the file at `path` and then upgrades it with
+    /// `migrator`, giving what was changed
+    ///
+    /// The file itself is left alone, so it can be written back with
+    /// [SuperConf::write_to_file](crate::SuperConf::write_to_file) if
+    /// anything was migrated.
+    pub fn load_and_migrate<P: AsRef<std::path::Path>>(
+        path: P,
+        migrator: &Migrator,
+    ) -> Result<(Self, Migrated), LoadError> {
+        let mut conf = Self::from_file(path)?;
+        let migrated = migrator.migrate(&mut conf)?;
+
+        Ok((conf, migrated))
+    }
+
+    /// Reads and parses every `*.sconf` file directly inside of `dir` in
+    /// lexical order, [merging](SuperConf::merge) each one on top of the ones
+    /// before it like a `conf.d` directory, giving which file set each value
+    ///
+    /// ```no_run
+    /// use superconf::SuperConf;
+    ///
+    /// let (conf, sources) = SuperConf::load_dir("/etc/app/conf.d").unwrap();
+    /// if let Some(file) = sources.get("server.port") {
+    ///     println!("port set by {}", file.display());
+    /// }
+    /// ```
+    pub fn load_dir<P: AsRef<std::path::Path>>(dir: P) -> Result<(Self, Sources), LoadError> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension() == Some("sconf".as_ref()) && path.is_file() {
+                files.push(path);
+            }
+        }
+        files.sort();
+
+        let mut conf = SuperConf { items: Vec::new() };
+        let mut leaves = Vec::with_capacity(files.len());
+        for path in &files {
+            let layer = Self::from_file(path).map_err(|err| LoadError::InFile {
+                path: path.clone(),
+                err: Box::new(err),
+            })?;
+            leaves.push(
+                layer
+                    .leaves()
+                    .map(|(path, _)| path.into_owned())
+                    .collect::<Vec<_>>(),
+            );
+            conf.merge(layer);
+        }
+
+        // values are only ever replaced by later files, so whichever is the
+        // last to have a value at a path is the one which set it
+        let values = conf
+            .leaves()
+            .filter_map(|(path, _)| {
+                let path = path.into_owned();
+                let ind = leaves.iter().rposition(|found| found.contains(&path))?;
+                Some((path, files[ind].clone()))
+            })
+            .collect();
+
+        Ok((conf, Sources { values }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Emit, SuperErrorKind, SuperValue};
+    use std::string::ToString;
+
+    #[test]
+    fn reader_and_file() {
+        let conf = SuperConf::from_reader("port 8080\nhosts [a, b]".as_bytes()).unwrap();
+        assert_eq!(conf.get("hosts[1]"), Some(&SuperValue::Name("b".into())));
+
+        let path = std::env::temp_dir().join("superconf-from-file.conf");
+        conf.write_to_file(&path).unwrap();
+        assert_eq!(SuperConf::from_file(&path).unwrap(), conf);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            SuperConf::from_file(&path),
+            Err(LoadError::Io(err)) if err.kind() == io::ErrorKind::NotFound
+        ));
+        assert!(matches!(
+            SuperConf::from_reader("a [".as_bytes()),
+            Err(LoadError::Parse(SuperError {
+                kind: SuperErrorKind::Unbalanced,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn migrated_file() {
+        let migrator = Migrator::new(1).step(0, |conf| {
+            let port = conf.take("port").unwrap_or(SuperValue::Integer(80));
+            conf.set("listen", port).map_err(|err| err.to_string())
+        });
+        let path = std::env::temp_dir().join("superconf-load-and-migrate.conf");
+
+        std::fs::write(&path, "port 8080").unwrap();
+        let (conf, migrated) = SuperConf::load_and_migrate(&path, &migrator).unwrap();
+        assert_eq!(conf, SuperConf::parse("listen 8080\nversion 1").unwrap());
+        assert_eq!(migrated.from, 0);
+
+        std::fs::write(&path, "version 2").unwrap();
+        assert!(matches!(
+            SuperConf::load_and_migrate(&path, &migrator),
+            Err(LoadError::Migrate(MigrateError::TooNew(2)))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn dir() {
+        let dir = std::env::temp_dir().join("superconf-load-dir");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir(&dir).unwrap();
+        for (name, text) in [
+            ("10-base.sconf", "server {port 80, host a}\nhosts [a, b]"),
+            ("20-prod.sconf", "server {port 8080}\nhosts [c]"),
+            ("30-local.conf", "server {port 1}"),
+        ] {
+            std::fs::write(dir.join(name), text).unwrap();
+        }
+
+        let (conf, sources) = SuperConf::load_dir(&dir).unwrap();
+        assert_eq!(
+            conf,
+            SuperConf::parse("server {port 8080, host a}\nhosts [c]").unwrap()
+        );
+        assert_eq!(
+            sources.get("server.port"),
+            Some(&*dir.join("20-prod.sconf"))
+        );
+        assert_eq!(
+            sources.get("server.host"),
+            Some(&*dir.join("10-base.sconf"))
+        );
+        assert_eq!(sources.get("hosts[0]"), Some(&*dir.join("20-prod.sconf")));
+        assert_eq!(sources.get("hosts[1]"), None);
+        assert_eq!(sources.values.len(), 3);
+
+        std::fs::write(dir.join("15-broken.sconf"), "a [").unwrap();
+        let err = SuperConf::load_dir(&dir).unwrap_err();
+        assert!(matches!(
+            &err,
+            LoadError::InFile { path, err }
+                if path.ends_with("15-broken.sconf")
+                    && matches!(**err, LoadError::Parse(SuperError { kind: SuperErrorKind::Unbalanced, .. }))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,63 @@
+//! Implementations of [defmt::Format] for the parsed tree, behind the `defmt`
+//! feature, which are written by hand as deriving it for these recursive
+//! types makes the compiler run out of memory
+//!
+//! The other public types derive it instead.
+
+use super::{SuperConf, SuperItem, SuperValue};
+use defmt::{write, Format, Formatter};
+
+impl Format for SuperValue<'_> {
+    fn format(&self, f: Formatter) {
+        match self {
+            SuperValue::Nothing => write!(f, "Nothing"),
+            SuperValue::Name(name) => write!(f, "Name({=str})", name),
+            SuperValue::Bool(boolean) => write!(f, "Bool({=bool})", boolean),
+            SuperValue::Integer(integer) => write!(f, "Integer({=i64})", integer),
+            SuperValue::Float(float) => write!(f, "Float({=f64})", float),
+            SuperValue::Bytes(data) => write!(f, "Bytes({=str})", data),
+            SuperValue::List(values) => {
+                write!(f, "List([");
+                for (ind, value) in values.iter().enumerate() {
+                    if ind != 0 {
+                        write!(f, ", ");
+                    }
+                    value.format(f);
+                }
+                write!(f, "])")
+            }
+            SuperValue::Group(items) => {
+                write!(f, "Group(");
+                items_format(items, f);
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl Format for SuperItem<'_> {
+    fn format(&self, f: Formatter) {
+        write!(f, "SuperItem {{ key: {=str}, value: ", self.key);
+        self.value.format(f);
+        write!(f, " }}")
+    }
+}
+
+impl Format for SuperConf<'_> {
+    fn format(&self, f: Formatter) {
+        write!(f, "SuperConf {{ items: ");
+        items_format(&self.items, f);
+        write!(f, " }}")
+    }
+}
+
+fn items_format(items: &[SuperItem], f: Formatter) {
+    write!(f, "[");
+    for (ind, item) in items.iter().enumerate() {
+        if ind != 0 {
+            write!(f, ", ");
+        }
+        item.format(f);
+    }
+    write!(f, "]")
+}
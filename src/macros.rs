@@ -0,0 +1,136 @@
+/// Writes a [SuperConf](crate::SuperConf) inline, which is handy for default
+/// configs
+///
+/// Items are separated by commas as newlines can't be seen by macros, keys
+/// can be idents or string literals and values can be literals, idents as
+/// names, lists, groups or a Rust expression in brackets which converts into
+/// a [SuperValue](crate::SuperValue). A key without a value has nothing.
+///
+/// ```
+/// use superconf::{superconf, SuperValue};
+///
+/// let workers = 4;
+/// let conf = superconf! {
+///     port 8080,
+///     "log level" debug,
+///     tls { enabled true, hosts [a, b] },
+///     workers (workers as i64),
+///     backup,
+/// };
+///
+/// assert_eq!(conf.get("tls.enabled"), Some(&SuperValue::Bool(true)));
+/// assert_eq!(conf.get("workers"), Some(&SuperValue::Integer(4)));
+/// ```
+#[macro_export]
+macro_rules! superconf {
+    (@items [$($done:expr,)*]) => {
+        $crate::convert::vec![$($done,)*]
+    };
+    (@items [$($done:expr,)*] $key:tt $(, $($rest:tt)*)?) => {
+        $crate::superconf!(
+            @items [$($done,)* $crate::superconf!(@item $key $crate::SuperValue::Nothing),]
+            $($($rest)*)?
+        )
+    };
+    (@items [$($done:expr,)*] $key:tt - $value:literal $(, $($rest:tt)*)?) => {
+        $crate::superconf!(
+            @items [$($done,)* $crate::superconf!(@item $key $crate::SuperValue::from(-$value)),]
+            $($($rest)*)?
+        )
+    };
+    (@items [$($done:expr,)*] $key:tt $value:tt $(, $($rest:tt)*)?) => {
+        $crate::superconf!(
+            @items [$($done,)* $crate::superconf!(@item $key $crate::superconf!(@value $value)),]
+            $($($rest)*)?
+        )
+    };
+
+    (@item $key:ident $value:expr) => {
+        $crate::SuperItem {
+            key: $crate::convert::Cow::Borrowed(stringify!($key)),
+            value: $value,
+            meta: ::core::option::Option::None,
+        }
+    };
+    (@item $key:literal $value:expr) => {
+        $crate::SuperItem {
+            key: $crate::convert::Cow::Borrowed($key),
+            value: $value,
+            meta: ::core::option::Option::None,
+        }
+    };
+
+    (@list [$($done:expr,)*]) => {
+        $crate::convert::vec![$($done,)*]
+    };
+    (@list [$($done:expr,)*] - $value:literal $(, $($rest:tt)*)?) => {
+        $crate::superconf!(
+            @list [$($done,)* $crate::SuperValue::from(-$value),]
+            $($($rest)*)?
+        )
+    };
+    (@list [$($done:expr,)*] $value:tt $(, $($rest:tt)*)?) => {
+        $crate::superconf!(
+            @list [$($done,)* $crate::superconf!(@value $value),]
+            $($($rest)*)?
+        )
+    };
+
+    (@value [$($values:tt)*]) => {
+        $crate::SuperValue::list($crate::superconf!(@list [] $($values)*))
+    };
+    (@value {$($items:tt)*}) => {
+        $crate::SuperValue::group($crate::superconf!(@items [] $($items)*))
+    };
+    (@value ($value:expr)) => {
+        $crate::SuperValue::from($value)
+    };
+    (@value $value:literal) => {
+        $crate::SuperValue::from($value)
+    };
+    (@value $name:ident) => {
+        $crate::SuperValue::Name($crate::convert::Cow::Borrowed(stringify!($name)))
+    };
+
+    ($($items:tt)*) => {
+        $crate::SuperConf {
+            items: $crate::superconf!(@items [] $($items)*),
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parse, SuperConf, SuperValue};
+
+    #[test]
+    fn matches_parsed() {
+        let conf = superconf! {
+            port 8080,
+            ratio -1.5,
+            name example,
+            "log level" "very verbose",
+            tls { enabled true, hosts [a, -2, [], {x false}] },
+            empty
+        };
+
+        assert_eq!(
+            conf,
+            SuperConf::parse(
+                "port 8080\nratio -1.5\nname example\nlog\\ level very verbose\ntls {enabled true, hosts [a, -2, [], {x false}]}\nempty"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn expressions() {
+        let name = "dynamic";
+        let conf = superconf! { name (name), doubled (2.0 * 3.0), trailing [1, 2,], };
+
+        assert_eq!(conf.get("name"), Some(&SuperValue::Name("dynamic".into())));
+        assert_eq!(conf.get("doubled"), Some(&SuperValue::Float(6.0)));
+        assert_eq!(conf.get("trailing[1]"), Some(&SuperValue::Integer(2)));
+        assert_eq!(superconf! {}.items, []);
+    }
+}
@@ -0,0 +1,335 @@
+//! Layering of whole configs on top of each other with [SuperConf::merge],
+//! such as the files of a `conf.d` directory, and of profiles inside of one
+//! config with [SuperConf::select_profile]
+
+use super::{Growable, Parse, Path, SuperConf, SuperItem, SuperValue};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Key of the group which holds every profile, which can also be written as
+/// a prefix like `profile.dev`
+pub const PROFILE_KEY: &str = "profile";
+
+/// How a value is layered on top of the one below it by
+/// [SuperConf::merge_with]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Strategy {
+    /// Value on top replaces the one below, even if they're both groups
+    Replace,
+    /// Lists are joined with the values on top after the ones below, with
+    /// any other value replaced
+    Append,
+    /// Groups are merged item by item with any other value replaced, which
+    /// is what [SuperConf::merge] does everywhere
+    Deep,
+    /// Values are an error if they're in both layers and aren't equal
+    Error,
+}
+
+/// Strategy to use for the values at some paths, where every other value
+/// is layered with [Strategy::Deep]
+#[derive(Debug, Clone, Default)]
+pub struct Strategies {
+    pub paths: Vec<(Path<'static>, Strategy)>,
+}
+
+impl Strategies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses `strategy` for the value at `path`, which is only for that value
+    /// and not the ones inside of it
+    pub fn at(mut self, path: &'static str, strategy: Strategy) -> Self {
+        self.paths.push((
+            Path::parse(path).expect("paths of strategies are valid"),
+            strategy,
+        ));
+        self
+    }
+
+    /// Finds the strategy for the value at `path`
+    pub fn get(&self, path: &Path<'_>) -> Strategy {
+        self.paths
+            .iter()
+            .find(|(found, _)| found == path)
+            .map_or(Strategy::Deep, |(_, strategy)| *strategy)
+    }
+}
+
+/// Error from [SuperConf::merge_with] for a value with [Strategy::Error]
+/// which was different in both layers
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MergeError {
+    pub path: Path<'static>,
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: conflicting values in both layers", self.path)
+    }
+}
+
+impl core::error::Error for MergeError {}
+
+impl<'a> SuperConf<'a> {
+    /// Layers `other` on top of this config, where groups which are in both
+    /// are merged item by item and any other value from `other` replaces the
+    /// one here, including lists
+    ///
+    /// ```
+    /// use superconf::{Parse, SuperConf};
+    ///
+    /// let mut conf = SuperConf::parse("server {port 80, host a}\nhosts [a, b]").unwrap();
+    /// conf.merge(SuperConf::parse("server {port 8080}\nhosts [c]\ndebug true").unwrap());
+    ///
+    /// assert_eq!(
+    ///     conf,
+    ///     SuperConf::parse("server {port 8080, host a}\nhosts [c]\ndebug true").unwrap()
+    /// );
+    /// ```
+    pub fn merge(&mut self, other: SuperConf<'a>) {
+        merge_items(&mut self.items, other.items)
+    }
+
+    /// Layers `other` on top of this config like [SuperConf::merge], using
+    /// the strategy from `strategies` for the values at each of its paths,
+    /// where the config is left partly merged if there's an error
+    ///
+    /// ```
+    /// use superconf::{Parse, Strategies, Strategy, SuperConf};
+    ///
+    /// let strategies = Strategies::new()
+    ///     .at("plugins", Strategy::Append)
+    ///     .at("tls", Strategy::Replace)
+    ///     .at("name", Strategy::Error);
+    /// let mut conf = SuperConf::parse("plugins [a, b]\ntls {cert x, key y}\nname app").unwrap();
+    /// conf.merge_with(SuperConf::parse("plugins [c]\ntls {cert z}").unwrap(), &strategies)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     conf,
+    ///     SuperConf::parse("plugins [a, b, c]\ntls {cert z}\nname app").unwrap()
+    /// );
+    /// ```
+    pub fn merge_with(
+        &mut self,
+        other: SuperConf<'a>,
+        strategies: &Strategies,
+    ) -> Result<(), MergeError> {
+        layer_items(&mut self.items, other.items, &mut Path::new(), strategies)
+    }
+
+    /// Merges the profile called `name` on top of the rest of the config,
+    /// removing every profile, which gives if it was found
+    ///
+    /// Profiles are the groups inside of the `profile` group, or the groups
+    /// at the top level with keys like `profile.dev`, which are all merged if
+    /// there's more than one for the same profile.
+    ///
+    /// ```
+    /// use superconf::{Parse, SuperConf};
+    ///
+    /// let text = "port 80\nhost a\nprofile {\n    dev {port 8080}\n    prod {host b}\n}";
+    /// let mut conf = SuperConf::parse(text).unwrap();
+    ///
+    /// assert!(conf.select_profile("dev"));
+    /// assert_eq!(conf, SuperConf::parse("port 8080\nhost a").unwrap());
+    /// ```
+    pub fn select_profile(&mut self, name: &str) -> bool {
+        let mut selected = Vec::new();
+
+        for item in core::mem::take(&mut self.items) {
+            let prefixed = item
+                .key
+                .strip_prefix(PROFILE_KEY)
+                .and_then(|rest| rest.strip_prefix('.'));
+            match (prefixed, item.value) {
+                (None, SuperValue::Group(profiles)) if item.key == PROFILE_KEY => selected.extend(
+                    profiles
+                        .into_iter()
+                        .filter(|profile| profile.key == name)
+                        .filter_map(|profile| match profile.value {
+                            SuperValue::Group(items) => Some(*items),
+                            _ => None,
+                        }),
+                ),
+                (Some(found), SuperValue::Group(items)) => {
+                    if found == name {
+                        selected.push(*items)
+                    }
+                }
+                (_, value) => self.items.push(SuperItem {
+                    key: item.key,
+                    value,
+                    meta: item.meta,
+                }),
+            }
+        }
+
+        let found = !selected.is_empty();
+        for items in selected {
+            merge_items(&mut self.items, items);
+        }
+        found
+    }
+}
+
+/// Merges `other` into `items`, adding any items which aren't in it yet
+pub(crate) fn merge_items<'a>(
+    items: &mut impl Growable<'a>,
+    other: impl IntoIterator<Item = SuperItem<'a>>,
+) {
+    for item in other {
+        match items.iter_mut().find(|found| found.key == item.key) {
+            Some(found) => {
+                #[cfg(feature = "tracing")]
+                tracing::trace!(key = &*item.key, from = ?found.value, to = ?item.value, "merged item");
+                merge_value(&mut found.value, item.value)
+            }
+            None => {
+                #[cfg(feature = "tracing")]
+                tracing::trace!(key = &*item.key, value = ?item.value, "added item");
+                items.push(item)
+            }
+        }
+    }
+}
+
+fn merge_value<'a>(value: &mut SuperValue<'a>, other: SuperValue<'a>) {
+    match (value, other) {
+        (SuperValue::Group(items), SuperValue::Group(other)) => merge_items(&mut **items, *other),
+        (value, other) => *value = other,
+    }
+}
+
+fn layer_items<'a>(
+    items: &mut impl Growable<'a>,
+    other: impl IntoIterator<Item = SuperItem<'a>>,
+    path: &mut Path<'static>,
+    strategies: &Strategies,
+) -> Result<(), MergeError> {
+    for item in other {
+        match items.iter_mut().find(|found| found.key == item.key) {
+            Some(found) => {
+                path.push_key(String::from(&*item.key));
+                #[cfg(feature = "tracing")]
+                tracing::trace!(
+                    path = %path,
+                    strategy = ?strategies.get(path),
+                    from = ?found.value,
+                    to = ?item.value,
+                    "layered item"
+                );
+                layer_value(&mut found.value, item.value, path, strategies)?;
+                path.pop();
+            }
+            None => items.push(item),
+        }
+    }
+    Ok(())
+}
+
+fn layer_value<'a>(
+    value: &mut SuperValue<'a>,
+    other: SuperValue<'a>,
+    path: &mut Path<'static>,
+    strategies: &Strategies,
+) -> Result<(), MergeError> {
+    match (strategies.get(path), value, other) {
+        (Strategy::Error, value, other) if *value != other => {
+            return Err(MergeError { path: path.clone() })
+        }
+        (Strategy::Append, SuperValue::List(values), SuperValue::List(other)) => {
+            values.extend(*other)
+        }
+        (Strategy::Deep, SuperValue::Group(items), SuperValue::Group(other)) => {
+            layer_items(&mut **items, *other, path, strategies)?
+        }
+        (_, value, other) => *value = other,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+    use alloc::string::ToString;
+
+    #[test]
+    fn merged() {
+        let mut conf = SuperConf::parse("a {b {c 1, d 2}}\ne 1\nf {g 1}").unwrap();
+        conf.merge(SuperConf::parse("a {b {d 3, h 4}}\ne {i 5}\nf 6\nj").unwrap());
+
+        assert_eq!(
+            conf,
+            SuperConf::parse("a {b {c 1, d 3, h 4}}\ne {i 5}\nf 6\nj").unwrap()
+        );
+    }
+
+    #[test]
+    fn strategies() {
+        let strategies = Strategies::new()
+            .at("a", Strategy::Append)
+            .at("b.c", Strategy::Replace)
+            .at("b.d", Strategy::Append)
+            .at("e", Strategy::Error)
+            .at("f", Strategy::Error);
+        let mut conf =
+            SuperConf::parse("a [1]\nb {c {g 1, h 2}, d {g 1}, i [1]}\ne {j 1}\nf 1").unwrap();
+        conf.merge_with(
+            SuperConf::parse("a [2, 3]\nb {c {g 3}, d {k 2}, i [2]}\ne {j 1}\nl 1").unwrap(),
+            &strategies,
+        )
+        .unwrap();
+
+        assert_eq!(
+            conf,
+            SuperConf::parse("a [1, 2, 3]\nb {c {g 3}, d {k 2}, i [2]}\ne {j 1}\nf 1\nl 1")
+                .unwrap()
+        );
+        assert_eq!(
+            conf.merge_with(SuperConf::parse("e {j 2}").unwrap(), &strategies)
+                .map_err(|err| err.to_string()),
+            Err("e: conflicting values in both layers".into())
+        );
+        assert_eq!(
+            conf.clone()
+                .merge_with(SuperConf::parse("b {c {g 4}}").unwrap(), &Strategies::new()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn profiles() {
+        const TEXT: &str = "a {b 1, c 2}\nprofile.dev {a {b 3}}\nprofile {dev {d 4}, prod {d 5}}\nprofile.prod {e 6}\nprofile\\ x 7";
+        let select = |name| {
+            let mut conf = SuperConf::parse(TEXT).unwrap();
+            (conf.select_profile(name), conf)
+        };
+
+        assert_eq!(
+            select("dev"),
+            (
+                true,
+                SuperConf::parse("a {b 3, c 2}\nprofile\\ x 7\nd 4").unwrap()
+            )
+        );
+        assert_eq!(
+            select("prod"),
+            (
+                true,
+                SuperConf::parse("a {b 1, c 2}\nprofile\\ x 7\nd 5\ne 6").unwrap()
+            )
+        );
+        assert_eq!(
+            select("test"),
+            (
+                false,
+                SuperConf::parse("a {b 1, c 2}\nprofile\\ x 7").unwrap()
+            )
+        );
+    }
+}
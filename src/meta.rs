@@ -0,0 +1,214 @@
+//! Documentation and attributes of items, written as `##` comments and
+//! lines like `@secret @unit(ms)` straight above them, which tools such as
+//! schema validation, redaction or `--help-config` commands can read
+//!
+//! ```
+//! use superconf::{Parse, SuperConf};
+//!
+//! let conf = SuperConf::parse("## How long to wait\n@unit(ms) @deprecated\ntimeout 500\n@secret\ntoken abc").unwrap();
+//!
+//! let timeout = &conf.items[0];
+//! assert_eq!(timeout.doc(), Some("How long to wait"));
+//! assert_eq!(timeout.attr("unit").unwrap().arg.as_deref(), Some("ms"));
+//! assert!(timeout.attr("deprecated").is_some());
+//! assert_eq!(conf.attributed("secret"), [superconf::Path::parse("token").unwrap()]);
+//! ```
+
+use super::{Path, SuperConf, SuperItem, SuperValue};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Items which are written like attributes but are read by the rest of this
+/// crate as items of their own
+const DIRECTIVES: [&str; 3] = ["@include", "@if", "@extends"];
+
+/// Documentation and attributes of an item, which are only kept for items
+/// that have any so that the rest stay small
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Meta {
+    /// Text of the `##` comments written straight above the item, without
+    /// the `##` and with each comment on its own line
+    pub doc: Option<String>,
+    /// Attributes from the lines straight above the item, in the order they
+    /// were written
+    pub attrs: Vec<Attr>,
+}
+
+/// Attribute like `@secret` or `@unit(ms)`, where the argument can't have
+/// any whitespace or commas in it
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attr {
+    pub name: String,
+    pub arg: Option<String>,
+}
+
+impl SuperItem<'_> {
+    /// Gives the documentation of the item, which is written as `##`
+    /// comments straight above it
+    ///
+    /// ```
+    /// use superconf::{Parse, SuperConf};
+    ///
+    /// let conf = SuperConf::parse("## Port to listen on\n## for http\nport 80\n# not docs\nhost 0.0.0.0").unwrap();
+    ///
+    /// assert_eq!(conf.items[0].doc(), Some("Port to listen on\nfor http"));
+    /// assert_eq!(conf.items[1].doc(), None);
+    /// ```
+    pub fn doc(&self) -> Option<&str> {
+        self.meta.as_ref()?.doc.as_deref()
+    }
+
+    /// Gives every attribute of the item
+    pub fn attrs(&self) -> &[Attr] {
+        self.meta.as_ref().map_or(&[], |meta| &meta.attrs)
+    }
+
+    /// Finds the first attribute of the item with `name`, which is written
+    /// without its `@`
+    pub fn attr(&self, name: &str) -> Option<&Attr> {
+        self.attrs().iter().find(|attr| attr.name == name)
+    }
+}
+
+impl SuperConf<'_> {
+    /// Finds the path of every item which has an attribute with `name`,
+    /// including items inside of groups and lists
+    pub fn attributed(&self, name: &str) -> Vec<Path<'_>> {
+        let mut found = Vec::new();
+        attributed_items(&mut found, &mut Path::new(), &self.items, name);
+        found
+    }
+}
+
+fn attributed_items<'b>(
+    found: &mut Vec<Path<'b>>,
+    path: &mut Path<'b>,
+    items: &'b [SuperItem<'_>],
+    name: &str,
+) {
+    for item in items {
+        path.push_key(&*item.key);
+        if item.attr(name).is_some() {
+            found.push(path.clone());
+        }
+        attributed_value(found, path, &item.value, name);
+        path.pop();
+    }
+}
+
+fn attributed_value<'b>(
+    found: &mut Vec<Path<'b>>,
+    path: &mut Path<'b>,
+    value: &'b SuperValue<'_>,
+    name: &str,
+) {
+    match value {
+        SuperValue::Group(items) => attributed_items(found, path, items, name),
+        SuperValue::List(values) => {
+            for (index, value) in values.iter().enumerate() {
+                path.push_index(index);
+                attributed_value(found, path, value, name);
+                path.pop();
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Reads the attributes of an item which is a line of only attributes, such
+/// as `@secret @unit(ms)`, which is nothing for any other item
+pub(crate) fn attrs_of(key: &str, value: &SuperValue<'_>) -> Option<Vec<Attr>> {
+    if !key.starts_with('@') || DIRECTIVES.contains(&key) {
+        return None;
+    }
+
+    let rest = match value {
+        SuperValue::Nothing => "",
+        SuperValue::Name(name) => name,
+        _ => return None,
+    };
+    core::iter::once(key)
+        .chain(rest.split_whitespace())
+        .map(attr)
+        .collect()
+}
+
+fn attr(word: &str) -> Option<Attr> {
+    let word = word.strip_prefix('@')?;
+    let (name, arg) = match word.split_once('(') {
+        Some((name, arg)) => (name, Some(arg.strip_suffix(')')?)),
+        None => (word, None),
+    };
+
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    valid.then(|| Attr {
+        name: name.into(),
+        arg: arg.map(Into::into),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+
+    #[test]
+    fn attributes() {
+        let conf = SuperConf::parse(
+            "@a\n## b\n@c(d) @e\nf 1\n@include x\n@if y {g 2}\nh {@i\nj [{@k, l}]}\n@m 1\nn\n@o",
+        )
+        .unwrap();
+        fn names<'b>(item: &'b SuperItem) -> Vec<(&'b str, Option<&'b str>)> {
+            item.attrs()
+                .iter()
+                .map(|attr| (attr.name.as_str(), attr.arg.as_deref()))
+                .collect()
+        }
+
+        let keys: Vec<_> = conf.items.iter().map(|item| &*item.key).collect();
+        assert_eq!(keys, ["f", "@include", "@if", "h", "@m", "n", "@o"]);
+        assert_eq!(
+            names(&conf.items[0]),
+            [("a", None), ("c", Some("d")), ("e", None)]
+        );
+        assert_eq!(conf.items[0].doc(), Some("b"));
+        assert_eq!(names(&conf.items[1]), []);
+        assert_eq!(names(&conf.items[5]), []);
+        assert_eq!(conf.attributed("k"), [Path::parse("h.j[0].l").unwrap()]);
+        assert_eq!(conf.attributed("i"), [Path::parse("h.j").unwrap()]);
+    }
+
+    #[test]
+    fn escaped() {
+        let text = "\\@x\na 1\nb {\\@y, c 2}";
+        let conf = SuperConf::parse(text).unwrap();
+
+        let keys: Vec<_> = conf.items.iter().map(|item| &*item.key).collect();
+        assert_eq!(keys, ["@x", "a", "b"]);
+        assert_eq!(conf.items[1].attrs(), []);
+        assert_eq!(conf.attributed("y"), []);
+        assert_eq!(crate::LazyConf::parse(text).unwrap().into_conf(), conf);
+        assert_eq!(SuperConf::parse_lines(text.lines()).unwrap(), conf);
+        assert_eq!(crate::Document::parse(text).unwrap().to_conf(), conf);
+    }
+
+    #[test]
+    fn invalid() {
+        for (key, value) in [
+            ("a", SuperValue::Nothing),
+            ("@", SuperValue::Nothing),
+            ("@a(b", SuperValue::Nothing),
+            ("@a", SuperValue::Name("b".into())),
+            ("@a", SuperValue::Integer(1)),
+            ("@a.b", SuperValue::Nothing),
+            ("@extends", SuperValue::Nothing),
+        ] {
+            assert_eq!(attrs_of(key, &value), None, "{} {:?}", key, value);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! Versioning of configs with a [Migrator], which upgrades configs written for
+//! older versions of an application to the shape it currently expects
+//!
+//! The version of a config is the integer of its `version` item, with configs
+//! which don't have one taken to be version 0.
+
+use super::{Path, Segment, SuperConf, SuperValue};
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+/// Key of the item which holds the version of a config
+pub const VERSION_KEY: &str = "version";
+
+/// Upgrade of a config from one version to the next, giving the message to
+/// report if it can't be done
+pub type Migration = fn(&mut SuperConf<'_>) -> Result<(), String>;
+
+/// Migrations from every old version of a config up to [Migrator::current]
+///
+/// ```
+/// use superconf::{Migrator, Parse, SuperConf, SuperValue};
+///
+/// let migrator = Migrator::new(2)
+///     // version 1 renamed `port` to `listen`
+///     .step(0, |conf| {
+///         let port = conf.take("port").ok_or("missing port")?;
+///         conf.set("listen", port).map_err(|err| err.to_string())
+///     })
+///     // version 2 added `workers`
+///     .step(1, |conf| conf.set("workers", 4.into()).map_err(|err| err.to_string()));
+///
+/// let mut conf = SuperConf::parse("port 8080").unwrap();
+/// let migrated = migrator.migrate(&mut conf).unwrap();
+///
+/// assert_eq!((migrated.from, migrated.to), (0, 2));
+/// assert_eq!(conf, SuperConf::parse("listen 8080\nversion 2\nworkers 4").unwrap());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Migrator {
+    /// Version which configs are upgraded to
+    pub current: u32,
+    /// Migration from each version to the one after it
+    pub steps: Vec<(u32, Migration)>,
+}
+
+/// What was done by [Migrator::migrate]
+#[derive(Debug, PartialEq, Clone)]
+pub struct Migrated {
+    /// Version which the config was written for
+    pub from: u32,
+    /// Version which the config is now, which is always [Migrator::current]
+    pub to: u32,
+    /// Paths of every scalar which was added, removed or changed by the
+    /// migrations apart from the version, in the order they're found
+    pub changed: Vec<Path<'static>>,
+}
+
+/// Error from [Migrator::migrate], where the config is left at the last
+/// version it was migrated to
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum MigrateError {
+    /// When the version isn't an integer which fits in a [u32]
+    InvalidVersion,
+    /// When the config is for a newer version than [Migrator::current]
+    TooNew(u32),
+    /// When there's no migration from a version to the one after it
+    NoMigration(u32),
+    /// When the migration from a version failed, with its message
+    Failed { from: u32, message: String },
+}
+
+impl Migrator {
+    pub fn new(current: u32) -> Self {
+        Self {
+            current,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Adds the migration from version `from` to the one after it
+    pub fn step(mut self, from: u32, migration: Migration) -> Self {
+        self.steps.push((from, migration));
+        self
+    }
+
+    /// Finds the version of `conf`, which is 0 if it doesn't have one
+    pub fn version(conf: &SuperConf<'_>) -> Result<u32, MigrateError> {
+        match conf.get(VERSION_KEY) {
+            None => Ok(0),
+            Some(SuperValue::Integer(version)) => {
+                u32::try_from(*version).map_err(|_| MigrateError::InvalidVersion)
+            }
+            Some(_) => Err(MigrateError::InvalidVersion),
+        }
+    }
+
+    /// Runs every migration from the version of `conf` up to
+    /// [Migrator::current], updating its version after each one
+    ///
+    /// Configs which are already current are left alone, apart from being
+    /// given a version if they didn't have one.
+    pub fn migrate(&self, conf: &mut SuperConf<'_>) -> Result<Migrated, MigrateError> {
+        let from = Self::version(conf)?;
+        if from > self.current {
+            return Err(MigrateError::TooNew(from));
+        }
+        let before = leaves(conf);
+
+        for version in from..self.current {
+            let Some((_, migration)) = self.steps.iter().find(|(found, _)| *found == version)
+            else {
+                return Err(MigrateError::NoMigration(version));
+            };
+            migration(conf).map_err(|message| MigrateError::Failed {
+                from: version,
+                message,
+            })?;
+            set_version(conf, version + 1);
+        }
+        if conf.get(VERSION_KEY).is_none() {
+            set_version(conf, self.current);
+        }
+
+        let after = leaves(conf);
+        let changed = before
+            .iter()
+            .filter(|leaf| !after.contains(leaf))
+            .chain(after.iter().filter(|leaf| !before.contains(leaf)))
+            .map(|(path, _)| path);
+        let mut paths: Vec<Path<'static>> = Vec::new();
+        for path in changed {
+            if !paths.contains(path) {
+                paths.push(path.clone());
+            }
+        }
+
+        Ok(Migrated {
+            from,
+            to: self.current,
+            changed: paths,
+        })
+    }
+}
+
+fn set_version(conf: &mut SuperConf<'_>, version: u32) {
+    conf.set(VERSION_KEY, i64::from(version).into())
+        .expect("version is a valid path at the top level")
+}
+
+/// Every scalar apart from the version alongside its path
+fn leaves(conf: &SuperConf<'_>) -> Vec<(Path<'static>, SuperValue<'static>)> {
+    conf.leaves()
+        .filter(|(path, _)| path.segments != [Segment::Key(Cow::Borrowed(VERSION_KEY))])
+        .map(|(path, value)| (path.into_owned(), value.clone().into_owned()))
+        .collect()
+}
+
+impl fmt::Display for MigrateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidVersion => write!(f, "{} isn't a valid version", VERSION_KEY),
+            Self::TooNew(version) => write!(f, "version {} is newer than supported", version),
+            Self::NoMigration(version) => {
+                write!(f, "there's no migration from version {}", version)
+            }
+            Self::Failed { from, message } => {
+                write!(f, "couldn't migrate from version {}: {}", from, message)
+            }
+        }
+    }
+}
+
+impl core::error::Error for MigrateError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+    use alloc::string::ToString;
+
+    fn migrator() -> Migrator {
+        Migrator::new(3)
+            .step(0, |conf| {
+                let port = conf.take("port").ok_or("missing port")?;
+                conf.set("server.port", port).map_err(|err| err.to_string())
+            })
+            .step(1, |conf| {
+                conf.set("server.workers", 4.into())
+                    .map_err(|err| err.to_string())
+            })
+            .step(2, |conf| match conf.get("server.port") {
+                Some(SuperValue::Integer(port)) if *port > 0 => Ok(()),
+                _ => Err("port has to be positive".into()),
+            })
+    }
+
+    #[test]
+    fn migrate() {
+        let mut conf = SuperConf::parse("port 80\nname a").unwrap();
+        let migrated = migrator().migrate(&mut conf).unwrap();
+
+        assert_eq!(
+            conf,
+            SuperConf::parse("name a\nserver {port 80, workers 4}\nversion 3").unwrap()
+        );
+        assert_eq!((migrated.from, migrated.to), (0, 3));
+        assert_eq!(
+            migrated
+                .changed
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["port", "server.port", "server.workers"]
+        );
+
+        let mut current = conf.clone();
+        assert_eq!(migrator().migrate(&mut current).unwrap().changed, []);
+        assert_eq!(current, conf);
+
+        let mut conf = SuperConf::parse("server {port 80}\nversion 1").unwrap();
+        assert_eq!(migrator().migrate(&mut conf).unwrap().from, 1);
+        assert_eq!(conf.get("server.workers"), Some(&SuperValue::Integer(4)));
+    }
+
+    #[test]
+    fn errors() {
+        let migrate = |text| migrator().migrate(&mut SuperConf::parse(text).unwrap());
+
+        assert_eq!(migrate("version x"), Err(MigrateError::InvalidVersion));
+        assert_eq!(migrate("version -1"), Err(MigrateError::InvalidVersion));
+        assert_eq!(migrate("version 4"), Err(MigrateError::TooNew(4)));
+        assert_eq!(
+            Migrator::new(1).migrate(&mut SuperConf::parse("").unwrap()),
+            Err(MigrateError::NoMigration(0))
+        );
+
+        let mut conf = SuperConf::parse("port 0").unwrap();
+        let err = migrator().migrate(&mut conf).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "couldn't migrate from version 2: port has to be positive"
+        );
+        assert_eq!(Migrator::version(&conf), Ok(2));
+    }
+}
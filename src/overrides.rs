@@ -0,0 +1,181 @@
+//! Layering of values from outside of a config on top of it, such as from
+//! command line flags or environment variables
+
+use super::{escaped_end, Parse, SuperConf, SuperError, SuperItem, SuperValue};
+use alloc::string::String;
+
+impl<'a> SuperConf<'a> {
+    /// Applies an override written like `server.port=9090`, as would be given
+    /// to a `--set` flag, where the value is parsed like any other so it can
+    /// also be a list or group
+    ///
+    /// The value is set with [SuperConf::set], so missing items are added. A
+    /// `=` inside of the path can be escaped with a backslash.
+    pub fn apply_override(&mut self, arg: &'a str) -> Result<(), SuperError> {
+        let ind = arg
+            .match_indices('=')
+            .map(|(ind, _)| ind)
+            .find(|ind| !escaped_end(&arg[..*ind]))
+            .ok_or(SuperError::InvalidOverride)?;
+
+        self.set(arg[..ind].trim(), SuperValue::parse(&arg[ind + 1..])?)
+    }
+
+    /// Applies every override from `args` in order, see
+    /// [SuperConf::apply_override]
+    ///
+    /// ```
+    /// use superconf::{Parse, SuperConf, SuperValue};
+    ///
+    /// // these would come from something like `--set` flags parsed by clap
+    /// let args = vec!["server.port=9090".to_string(), "hosts=[a, b]".to_string()];
+    /// let mut conf = SuperConf::parse("server {port 80}").unwrap();
+    ///
+    /// conf.apply_overrides(args.iter().map(String::as_str)).unwrap();
+    /// assert_eq!(conf.get("server.port"), Some(&SuperValue::Integer(9090)));
+    /// assert_eq!(conf.get("hosts[1]"), Some(&SuperValue::Name("b".into())));
+    /// ```
+    pub fn apply_overrides<I>(&mut self, args: I) -> Result<(), SuperError>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        args.into_iter()
+            .try_for_each(|arg| self.apply_override(arg))
+    }
+
+    /// Overrides items with environment variables named after their paths,
+    /// so `server.port` is overridden by `APP_SERVER__PORT` with a prefix of
+    /// `APP`
+    ///
+    /// Keys are uppercased with anything other than letters and digits
+    /// becoming `_`, and nested keys are separated by `__`. Only items which
+    /// already exist are looked up, and a variable for a group replaces the
+    /// whole group instead of the items inside. Values are parsed like any
+    /// other, and `lookup` is given each name to check so this doesn't need
+    /// std:
+    ///
+    /// ```
+    /// use superconf::{Parse, SuperConf};
+    ///
+    /// let mut conf = SuperConf::parse("server {port 80}").unwrap();
+    ///
+    /// conf.apply_env("APP", |name| std::env::var(name).ok()).unwrap();
+    /// ```
+    pub fn apply_env<F>(&mut self, prefix: &str, mut lookup: F) -> Result<(), SuperError>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let mut name = String::from(prefix);
+        let sep = match prefix.is_empty() {
+            true => "",
+            false => "_",
+        };
+
+        env_items(&mut self.items, &mut name, sep, &mut lookup)
+    }
+}
+
+fn env_items<F>(
+    items: &mut [SuperItem],
+    name: &mut String,
+    sep: &str,
+    lookup: &mut F,
+) -> Result<(), SuperError>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    for item in items {
+        let len = name.len();
+        name.push_str(sep);
+        name.extend(item.key.chars().map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_uppercase(),
+            false => '_',
+        }));
+
+        match (lookup(name), &mut item.value) {
+            (Some(text), value) => {
+                *value = SuperValue::parse(&text)?.into_owned();
+            }
+            (None, SuperValue::Group(items)) => env_items(items, name, "__", lookup)?,
+            (None, _) => (),
+        }
+
+        name.truncate(len);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn overrides() {
+        let mut conf = SuperConf::parse("port 80\ntls {enabled false}").unwrap();
+
+        conf.apply_overrides([
+            "port=8080",
+            "tls.enabled = true",
+            "a\\=b=c d",
+            "b\\\\=c",
+            "empty=",
+        ])
+        .unwrap();
+
+        assert_eq!(conf.get("port"), Some(&SuperValue::Integer(8080)));
+        assert_eq!(conf.get("tls.enabled"), Some(&SuperValue::Bool(true)));
+        assert_eq!(conf.get("a=b"), Some(&SuperValue::Name("c d".into())));
+        assert_eq!(conf.get("b\\\\"), Some(&SuperValue::Name("c".into())));
+        assert_eq!(conf.get("empty"), Some(&SuperValue::Nothing));
+        assert_eq!(
+            conf.apply_override("port"),
+            Err(SuperError::InvalidOverride)
+        );
+        assert_eq!(conf.apply_override("port=[1"), Err(SuperError::Unbalanced));
+    }
+
+    #[test]
+    fn env() {
+        let mut conf = SuperConf::parse(
+            "server {port 80, log-level info, tls {enabled false}}\nhosts [a]\nname x",
+        )
+        .unwrap();
+        let mut looked_up = Vec::new();
+        conf.apply_env("APP", |name| {
+            looked_up.push(String::from(name));
+            match name {
+                "APP_SERVER__PORT" => Some("9090".into()),
+                "APP_SERVER__LOG_LEVEL" => Some("debug".into()),
+                "APP_HOSTS" => Some("[b, c]".into()),
+                _ => None,
+            }
+        })
+        .unwrap();
+
+        assert_eq!(conf.get("server.port"), Some(&SuperValue::Integer(9090)));
+        assert_eq!(
+            conf.get("server.log-level"),
+            Some(&SuperValue::Name("debug".into()))
+        );
+        assert_eq!(conf.get("hosts[1]"), Some(&SuperValue::Name("c".into())));
+        assert_eq!(
+            looked_up,
+            [
+                "APP_SERVER",
+                "APP_SERVER__PORT",
+                "APP_SERVER__LOG_LEVEL",
+                "APP_SERVER__TLS",
+                "APP_SERVER__TLS__ENABLED",
+                "APP_HOSTS",
+                "APP_NAME"
+            ]
+        );
+
+        let mut conf = SuperConf::parse("port 80").unwrap();
+        conf.apply_env("", |name| (name == "PORT").then(|| "1".into()))
+            .unwrap();
+        assert_eq!(conf.get("port"), Some(&SuperValue::Integer(1)));
+    }
+}
@@ -0,0 +1,97 @@
+//! Parsing of very large configs on every thread with
+//! [SuperConf::parse_parallel], behind the `rayon` feature
+
+use super::chunked::LineEnds;
+use super::cursor::Cursor;
+use super::{SuperConf, SuperError};
+use alloc::vec::Vec;
+use rayon::prelude::*;
+
+/// Smallest amount of text worth handing to a thread of its own
+const MIN_CHUNK: usize = 64 * 1024;
+
+impl<'a> SuperConf<'a> {
+    /// Parses a config the same as [Parse](crate::Parse) would, but splits it
+    /// into pieces which are parsed in parallel using rayon
+    ///
+    /// Pieces are only split at newlines which are outside of any brackets,
+    /// so a group spanning many lines always stays in one piece, and never
+    /// straight after documentation or attributes so that they stay with the
+    /// item below them. Configs which are too small to be worth splitting are
+    /// parsed as usual.
+    ///
+    /// ```
+    /// use superconf::{SuperConf, SuperValue};
+    ///
+    /// let text = "port 8080\nhosts {\n  a 1\n}\n".repeat(10_000);
+    /// let conf = SuperConf::parse_parallel(&text).unwrap();
+    ///
+    /// assert_eq!(conf.items.len(), 20_000);
+    /// assert_eq!(conf.get("hosts.a"), Some(&SuperValue::Integer(1)));
+    /// ```
+    pub fn parse_parallel(input: &'a str) -> Result<Self, SuperError> {
+        let target = (input.len() / rayon::current_num_threads()).max(MIN_CHUNK);
+        parse_split(input, target)
+    }
+}
+
+/// Parses pieces of at least `target` bytes in parallel and joins them up in
+/// the order they were written
+fn parse_split(input: &str, target: usize) -> Result<SuperConf<'_>, SuperError> {
+    let mut pieces = Vec::new();
+    let mut lines = LineEnds::default();
+    let mut start = 0;
+    let mut line = 0;
+
+    for (ind, byte) in input.bytes().enumerate() {
+        if !lines.is_end(byte) {
+            continue;
+        }
+
+        // lines of comments or attributes stay in the same piece as the
+        // line after them, as `##` comments and attributes belong to it
+        let text = input[line..ind].trim_start();
+        line = ind + 1;
+        if !text.starts_with(['#', '@']) && ind - start >= target {
+            pieces.push(&input[start..ind]);
+            start = ind + 1;
+        }
+    }
+    pieces.push(&input[start..]);
+
+    let parsed = pieces
+        .into_par_iter()
+        .map(|piece| Cursor::new(piece).conf())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut items = Vec::with_capacity(parsed.iter().map(Vec::len).sum());
+    parsed.into_iter().for_each(|piece| items.extend(piece));
+    Ok(SuperConf { items })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+
+    #[test]
+    fn same_as_serial() {
+        let text =
+            "## a\n@x(1)\na 1 # [\n@y\n## b\nb {c [1,\n 2]\n ## d\n d e}\n## g\n\nf \\\ng\n\n"
+                .repeat(50);
+        let expected = SuperConf::parse(&text).unwrap();
+
+        for target in [0, 1, 7, 100, text.len()] {
+            assert_eq!(parse_split(&text, target).unwrap(), expected, "{}", target);
+        }
+        assert_eq!(SuperConf::parse_parallel(&text).unwrap(), expected);
+    }
+
+    #[test]
+    fn errors() {
+        let text = ["a 1\n".repeat(20), "b [\n".into(), "c 1\n".repeat(20)].concat();
+
+        assert_eq!(parse_split(&text, 8), Err(SuperError::Unbalanced));
+        assert_eq!(parse_split("a 1\nb ]\nc 1", 0), Err(SuperError::Unbalanced));
+    }
+}
@@ -0,0 +1,174 @@
+use super::{unescape, Parse, SuperError};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Location of a value inside of a config, written like `server.hosts[2].port`
+/// with dots between keys and square brackets around list indices
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Path<'a> {
+    pub segments: Vec<Segment<'a>>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Segment<'a> {
+    /// Key of an item inside of a group, or at the top level
+    Key(Cow<'a, str>),
+    /// Position of an element inside of a list
+    Index(usize),
+}
+
+impl<'a> Path<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_key(&mut self, key: impl Into<Cow<'a, str>>) {
+        self.segments.push(Segment::Key(key.into()))
+    }
+
+    pub fn push_index(&mut self, index: usize) {
+        self.segments.push(Segment::Index(index))
+    }
+
+    pub fn pop(&mut self) -> Option<Segment<'a>> {
+        self.segments.pop()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Copies any borrowed keys so the path no longer borrows from anything
+    pub fn into_owned(self) -> Path<'static> {
+        Path {
+            segments: self
+                .segments
+                .into_iter()
+                .map(|segment| match segment {
+                    Segment::Key(key) => Segment::Key(Cow::Owned(key.into_owned())),
+                    Segment::Index(index) => Segment::Index(index),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<'a> Parse<'a> for Path<'a> {
+    fn parse(input: &'a str) -> Result<Self, SuperError> {
+        let mut path = Self::new();
+        let mut chars = input.char_indices().peekable();
+        let mut after_dot = false;
+
+        while let Some((start, c)) = chars.next() {
+            match c {
+                '[' if !after_dot => {
+                    let close = input[start..].find(']').ok_or(SuperError::InvalidPath)? + start;
+                    let index = input[start + 1..close]
+                        .parse()
+                        .map_err(|_| SuperError::InvalidPath)?;
+
+                    path.push_index(index);
+                    while chars.next_if(|(ind, _)| *ind <= close).is_some() {}
+                }
+                '.' if !after_dot && !path.is_empty() => after_dot = true,
+                '.' | '[' | ']' => return Err(SuperError::InvalidPath),
+                _ if !after_dot && !path.is_empty() => return Err(SuperError::InvalidPath),
+                _ => {
+                    let mut end = input.len();
+                    let mut flipflop = c == '\\';
+
+                    while let Some((ind, c)) = chars.peek().copied() {
+                        if flipflop {
+                            flipflop = false;
+                        } else if c == '\\' {
+                            flipflop = true;
+                        } else if c == '.' || c == '[' || c == ']' {
+                            end = ind;
+                            break;
+                        }
+                        chars.next();
+                    }
+
+                    path.push_key(unescape(&input[start..end]));
+                    after_dot = false;
+                }
+            }
+        }
+
+        if path.is_empty() || after_dot {
+            Err(SuperError::InvalidPath)
+        } else {
+            Ok(path)
+        }
+    }
+}
+
+impl fmt::Display for Path<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (ind, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Key(key) => {
+                    if ind != 0 {
+                        f.write_str(".")?;
+                    }
+                    for c in key.chars() {
+                        if matches!(c, '.' | '[' | ']' | '\\') {
+                            f.write_str("\\")?;
+                        }
+                        write!(f, "{}", c)?;
+                    }
+                }
+                Segment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn parse_display() {
+        let path = Path::parse("server.hosts[2].port").unwrap();
+
+        assert_eq!(
+            path.segments,
+            vec![
+                Segment::Key("server".into()),
+                Segment::Key("hosts".into()),
+                Segment::Index(2),
+                Segment::Key("port".into())
+            ]
+        );
+        assert_eq!(path.to_string(), "server.hosts[2].port");
+    }
+
+    #[test]
+    fn escaped_keys() {
+        let path = Path::parse("a\\.b[0][1].c").unwrap();
+
+        assert_eq!(path.segments[0], Segment::Key("a.b".into()));
+        assert_eq!(path.segments.len(), 4);
+        assert_eq!(path.to_string(), "a\\.b[0][1].c");
+    }
+
+    #[test]
+    fn invalid() {
+        for input in ["", ".a", "a.", "a..b", "a[", "a[x]", "a[0]b", "a.[0]", "a]"] {
+            assert_eq!(
+                Path::parse(input),
+                Err(SuperError::InvalidPath),
+                "{}",
+                input
+            );
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Support for loading superconf files through [figment](https://docs.rs/figment)
+//! and [config](https://docs.rs/config), behind the features of the same names,
+//! so they can be merged with other sources such as environment variables
+
+#[cfg(feature = "config")]
+use super::{Parse, SuperConf, SuperValue};
+#[cfg(feature = "config")]
+use alloc::boxed::Box;
+#[cfg(feature = "config")]
+use alloc::string::{String, ToString};
+
+/// Superconf format for figment and config, see the implementations of their
+/// traits for how each are used
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SuperConfFormat;
+
+/// Using [Format::file](figment::providers::Format::file) or
+/// [Format::string](figment::providers::Format::string) gives a provider:
+///
+/// ```
+/// use figment::providers::Format;
+/// use figment::Figment;
+/// use superconf::SuperConfFormat;
+///
+/// let figment = Figment::new().merge(SuperConfFormat::string("port 8080\n"));
+///
+/// assert_eq!(figment.extract_inner::<u16>("port").unwrap(), 8080);
+/// ```
+#[cfg(feature = "figment")]
+impl figment::providers::Format for SuperConfFormat {
+    type Error = super::SerdeError;
+
+    const NAME: &'static str = "superconf";
+
+    fn from_str<T: serde::de::DeserializeOwned>(string: &str) -> Result<T, super::SerdeError> {
+        super::from_str(string)
+    }
+}
+
+/// Used as the format of a [File](config::File) source, which finds files
+/// with the `conf` extension:
+///
+/// ```
+/// use config::{Config, File};
+/// use superconf::SuperConfFormat;
+///
+/// let config = Config::builder()
+///     .add_source(File::from_str("port 8080\n", SuperConfFormat))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(config.get_int("port").unwrap(), 8080);
+/// ```
+#[cfg(feature = "config")]
+impl config::Format for SuperConfFormat {
+    fn parse(
+        &self,
+        uri: Option<&String>,
+        text: &str,
+    ) -> Result<config::Map<String, config::Value>, Box<dyn core::error::Error + Send + Sync>> {
+        let conf = SuperConf::parse(text).map_err(Box::new)?;
+
+        Ok(conf
+            .items
+            .iter()
+            .map(|item| (item.key.to_string(), config_value(uri, &item.value)))
+            .collect())
+    }
+}
+
+#[cfg(feature = "config")]
+impl config::FileStoredFormat for SuperConfFormat {
+    fn file_extensions(&self) -> &'static [&'static str] {
+        &["conf"]
+    }
+}
+
+#[cfg(feature = "config")]
+fn config_value(uri: Option<&String>, value: &SuperValue) -> config::Value {
+    use config::ValueKind;
+
+    let kind = match value {
+        SuperValue::Nothing => ValueKind::Nil,
+        SuperValue::Name(name) => ValueKind::String(name.to_string()),
+        SuperValue::Bool(boolean) => ValueKind::Boolean(*boolean),
+        SuperValue::Integer(integer) => ValueKind::I64(*integer),
+        SuperValue::Float(float) => ValueKind::Float(*float),
+        SuperValue::Bytes(data) => ValueKind::String(data.to_string()),
+        SuperValue::List(values) => ValueKind::Array(
+            values
+                .iter()
+                .map(|value| config_value(uri, value))
+                .collect(),
+        ),
+        SuperValue::Group(items) => ValueKind::Table(
+            items
+                .iter()
+                .map(|item| (item.key.to_string(), config_value(uri, &item.value)))
+                .collect(),
+        ),
+    };
+
+    config::Value::new(uri, kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Config {
+        name: String,
+        port: u16,
+        hosts: Vec<String>,
+        debug: bool,
+    }
+
+    fn expected() -> Config {
+        Config {
+            name: "default".into(),
+            port: 8080,
+            hosts: vec!["a".into(), "b".into()],
+            debug: true,
+        }
+    }
+
+    #[cfg(feature = "figment")]
+    #[test]
+    fn figment() {
+        use figment::providers::{Format, Serialized};
+        use figment::Figment;
+
+        let defaults = Config {
+            name: "default".into(),
+            port: 80,
+            hosts: Vec::new(),
+            debug: false,
+        };
+        let config: Config = Figment::from(Serialized::defaults(defaults))
+            .merge(SuperConfFormat::string(
+                "port 8080\nhosts [a, b]\ndebug true",
+            ))
+            .extract()
+            .unwrap();
+        assert_eq!(config, expected());
+
+        let err = Figment::new()
+            .merge(SuperConfFormat::string("port [8080"))
+            .extract::<Config>()
+            .unwrap_err();
+        assert!(err.to_string().contains("unbalanced"), "{}", err);
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn config() {
+        use config::{Config as Builder, File};
+
+        let config: Config = Builder::builder()
+            .set_default("name", "default")
+            .unwrap()
+            .add_source(File::from_str(
+                "port 8080\nhosts [a, b]\ndebug true",
+                SuperConfFormat,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        assert_eq!(config, expected());
+
+        let err = Builder::builder()
+            .add_source(File::from_str("port [8080", SuperConfFormat))
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("unbalanced"), "{}", err);
+    }
+}
@@ -0,0 +1,222 @@
+//! Pull parser which hands out [Event]s one at a time instead of building a
+//! tree, so huge files can be streamed through without holding every node
+
+use super::tokens::{Token, Tokens};
+use super::SuperError;
+
+pub use super::tokens::{Raw, Snapshot, Unescaped, MAX_DEPTH};
+
+/// Piece of a config given by [Reader], where every [Event::Key] is followed
+/// by the events of exactly one value
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Event<'a> {
+    /// Key of an item at the top level or inside of a group
+    Key(Raw<'a>),
+    /// Value which isn't a list or group, which is empty for nothing
+    Scalar(Raw<'a>),
+    ListStart,
+    ListEnd,
+    GroupStart,
+    GroupEnd,
+}
+
+/// Parses text into [Event]s as they're asked for, giving the same config
+/// as [SuperConf::parse](crate::SuperConf::parse) would without allocating
+///
+/// Brackets can only be nested [MAX_DEPTH] deep, past which
+/// [SuperError::TooDeep] is given. Any error ends the events.
+///
+/// ```
+/// use superconf::{Event, Reader};
+///
+/// let mut keys = Vec::new();
+/// for event in Reader::new("port 8080\ntls {enabled true}") {
+///     if let Event::Key(key) = event.unwrap() {
+///         keys.push(key.as_str());
+///     }
+/// }
+///
+/// assert_eq!(keys, ["port", "tls", "enabled"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Reader<'a> {
+    tokens: Tokens<'a>,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            tokens: Tokens::new(input),
+        }
+    }
+
+    /// Picks up from a [Snapshot] taken of a reader for the same `input`,
+    /// which is nothing if it can't have been
+    pub fn resume(input: &'a str, snapshot: Snapshot) -> Option<Self> {
+        Tokens::resume(input, snapshot).map(|tokens| Self { tokens })
+    }
+
+    /// Takes a [Snapshot] of where the reader is up to, which is small enough
+    /// to be kept around or saved while parsing is suspended
+    pub fn snapshot(&self) -> Snapshot {
+        self.tokens.snapshot()
+    }
+
+    /// Looks past the list or group which was just started at `start`, giving
+    /// the whole of it as a name if something comes after its closing
+    /// bracket, like `[1] x`
+    fn trailing(&mut self, start: usize) -> Result<Option<Raw<'a>>, SuperError> {
+        let mut ahead = self.tokens.clone();
+
+        match ahead.skip_nested() {
+            Ok(Some(span)) => {
+                self.tokens = ahead;
+                Ok(Some(Raw::new(&self.tokens.input()[start..span.end])))
+            }
+            Ok(None) => Ok(None),
+            Err(err) => {
+                self.tokens = ahead;
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Reader<'a> {
+    type Item = Result<Event<'a>, SuperError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (token, span) = match self.tokens.next()? {
+                Ok(found) => found,
+                Err(err) => return Some(Err(err)),
+            };
+
+            return Some(Ok(match token {
+                Token::Key(raw) => Event::Key(raw),
+                Token::Scalar(raw) => Event::Scalar(raw),
+                Token::ListStart | Token::GroupStart => match self.trailing(span.start) {
+                    Ok(Some(raw)) => Event::Scalar(raw),
+                    Ok(None) if token == Token::ListStart => Event::ListStart,
+                    Ok(None) => Event::GroupStart,
+                    Err(err) => return Some(Err(err)),
+                },
+                Token::ListEnd => Event::ListEnd,
+                Token::GroupEnd => Event::GroupEnd,
+                Token::Trailing(_) | Token::Comma | Token::Newline | Token::Comment(_) => continue,
+            }));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::{Parse, SuperConf, SuperItem, SuperValue};
+    use alloc::vec::Vec;
+
+    /// Builds a tree back up out of events, to compare with the usual parser
+    fn items(reader: &mut Reader) -> Result<Vec<SuperItem<'static>>, SuperError> {
+        let mut items = Vec::new();
+
+        while let Some(event) = reader.next() {
+            match event? {
+                Event::Key(key) => items.push(SuperItem {
+                    key: key.to_key().into_owned().into(),
+                    value: value(reader)?,
+                    meta: None,
+                }),
+                Event::GroupEnd => break,
+                other => panic!("{:?} where an item should be", other),
+            }
+        }
+
+        Ok(items)
+    }
+
+    fn value(reader: &mut Reader) -> Result<SuperValue<'static>, SuperError> {
+        match reader.next().unwrap()? {
+            Event::Scalar(text) => Ok(text.to_value().into_owned()),
+            Event::GroupStart => Ok(SuperValue::group(items(reader)?)),
+            Event::ListStart => {
+                let mut values = Vec::new();
+                loop {
+                    let mut peek = reader.clone();
+                    if let Some(Ok(Event::ListEnd)) = peek.next() {
+                        *reader = peek;
+                        return Ok(SuperValue::list(values));
+                    }
+                    values.push(value(reader)?);
+                }
+            }
+            other => panic!("{:?} where a value should be", other),
+        }
+    }
+
+    #[test]
+    fn same_as_tree() {
+        for input in [
+            "port 8080\nhosts [a, b c, [1, {x y}], ]\ntls {enabled true,\n path a\\,b}",
+            "# heading\nempty\nlist [1, # one\n 2]\nblank [,]\nname a\\#b#c",
+            "a [1] x\nb {c} d\nkey\\ with\\ spaces  1.5\ttab",
+            "a[b c] 1\n  k\t \n{x} y",
+            "\r\nlast {a [], b {}}\r\n",
+            "k [[1 ]\n, {}\n]",
+        ] {
+            assert_eq!(
+                items(&mut Reader::new(input)),
+                Ok(SuperConf::parse(input).unwrap().into_owned().items),
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn errors() {
+        for input in ["a [1", "a {b]", "a ]", "a x[1", "{", "a b}"] {
+            assert_eq!(
+                Reader::new(input).find_map(Result::err),
+                Some(SuperError::Unbalanced),
+                "{}",
+                input
+            );
+        }
+
+        let nested = |depth| ["a ", &"[".repeat(depth), &"]".repeat(depth)].concat();
+        assert_eq!(Reader::new(&nested(MAX_DEPTH)).find_map(Result::err), None);
+        assert_eq!(
+            Reader::new(&nested(MAX_DEPTH + 1)).find_map(Result::err),
+            Some(SuperError::TooDeep)
+        );
+    }
+
+    #[test]
+    fn resume() {
+        let text = "a [1, {b c}]\nd é";
+        let events: Vec<_> = Reader::new(text).collect();
+
+        for skip in 0..=events.len() {
+            let mut reader = Reader::new(text);
+            reader.by_ref().take(skip).for_each(drop);
+            let resumed = Reader::resume(text, reader.snapshot()).unwrap();
+            assert_eq!(resumed.collect::<Vec<_>>(), events[skip..], "{}", skip);
+        }
+
+        let mut reader = Reader::new(text);
+        reader.by_ref().take(9).for_each(drop);
+        assert!(Reader::resume("d é", reader.snapshot()).is_none());
+        assert!(Reader::resume(&text[..1], reader.snapshot()).is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_snapshot() {
+        let mut reader = Reader::new("a [1, 2]");
+        reader.by_ref().take(3).for_each(drop);
+        let saved = serde_json::to_string(&reader.snapshot()).unwrap();
+
+        let resumed = Reader::resume("a [1, 2]", serde_json::from_str(&saved).unwrap()).unwrap();
+        assert_eq!(resumed.collect::<Vec<_>>(), reader.collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,873 @@
+//! Resolving of placeholders in a config with a [Pipeline] of [Provider]s,
+//! such as `${path}` interpolations, `@include` items and secrets, without
+//! this crate knowing where any of their values are kept
+
+use super::tokens::{Token, Tokens};
+use super::{Emit, Growable, Parse, Path, SuperConf, SuperItem, SuperValue};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+#[cfg(feature = "std")]
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Start of a name which is a placeholder for a secret
+pub const SECRET_SCHEME: &str = "secret://";
+
+/// Deepest that includes can be nested inside of other includes
+pub const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Key of an item which is replaced by the items it includes
+const INCLUDE_KEY: &str = "@include";
+
+/// Backend which secrets are looked up in, which is implemented for closures
+/// taking the reference and giving the secret
+pub trait Resolver {
+    /// Gives the secret which `reference` stands for, which is everything
+    /// after `secret://`, or a message about why it couldn't be found
+    fn resolve(&self, reference: &str) -> Result<String, String>;
+}
+
+impl<F: Fn(&str) -> Result<String, String>> Resolver for F {
+    fn resolve(&self, reference: &str) -> Result<String, String> {
+        self(reference)
+    }
+}
+
+/// Source of the values which placeholders stand for, consulted in order by
+/// a [Pipeline]
+pub trait Provider {
+    /// Gives the value which `placeholder` stands for or a message about why
+    /// it couldn't be found, which is nothing if this doesn't provide it
+    fn provide(&self, placeholder: &Placeholder<'_>)
+        -> Option<Result<SuperValue<'static>, String>>;
+
+    /// Gives the text which an include this provides was read from, so that
+    /// [Provenance] can tell which line each of its items is on
+    fn source(&self, include: &str) -> Option<String> {
+        let _ = include;
+        None
+    }
+}
+
+/// Placeholder which a [Provider] is asked for
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Placeholder<'p> {
+    /// What's inside of `${...}`
+    Interpolation(&'p str),
+    /// Name of an `@include` item, which has to be provided as a group
+    Include(&'p str),
+    /// Name like `secret://vault/db`, split into `secret` and `vault/db`
+    Scheme { scheme: &'p str, reference: &'p str },
+}
+
+/// Chain of [Provider]s used by [SuperConf::resolve_with], where the first
+/// to provide a placeholder is used
+///
+/// ```
+/// use superconf::{Parse, Pipeline, Secrets, SuperConf, SuperValue};
+///
+/// let vault = |reference: &str| Ok(format!("{}-secret", reference));
+/// let pipeline = Pipeline::new().provider(Secrets(&vault));
+///
+/// let mut conf = SuperConf::parse("host db\nurl postgres://${host}:5432\ntoken secret://db").unwrap();
+/// conf.resolve_with(&pipeline).unwrap();
+///
+/// assert_eq!(conf.get("url"), Some(&SuperValue::Name("postgres://db:5432".into())));
+/// assert_eq!(conf.get("token"), Some(&SuperValue::Name("db-secret".into())));
+/// ```
+#[derive(Default)]
+pub struct Pipeline<'p> {
+    providers: Vec<Box<dyn Provider + 'p>>,
+}
+
+/// Provides secrets from a [Resolver] for names like `secret://vault/db`
+pub struct Secrets<'r>(pub &'r dyn Resolver);
+
+/// Provides the items of an `@include` from the file it names, relative to
+/// [Files::dir]
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct Files {
+    pub dir: std::path::PathBuf,
+}
+
+/// Provides environment variables for placeholders like `${env:HOME}`, which
+/// are parsed like any other value
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Env;
+
+/// Config whose placeholders are only resolved as values are looked up, from
+/// [SuperConf::deferred]
+///
+/// Placeholders which are never looked up never reach the pipeline, so a
+/// secret for a backend which isn't enabled doesn't have to be available.
+/// Includes aren't replaced, as they change what's in the config and so have
+/// to be replaced with [SuperConf::resolve_with] beforehand.
+///
+/// ```
+/// use superconf::{Parse, Pipeline, Secrets, SuperConf, SuperValue};
+///
+/// let vault = |reference: &str| match reference {
+///     "db" => Ok("hunter2".to_string()),
+///     _ => Err(format!("{} isn't in the vault", reference)),
+/// };
+/// let pipeline = Pipeline::new().provider(Secrets(&vault));
+///
+/// let conf = SuperConf::parse("db {password secret://db}\ncache {password secret://cache}").unwrap();
+/// let deferred = conf.deferred(&pipeline);
+///
+/// assert_eq!(deferred.get("db.password"), Ok(Some(SuperValue::Name("hunter2".into()))));
+/// assert!(deferred.get("cache").is_err());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Deferred<'r, 'a> {
+    pub conf: &'r SuperConf<'a>,
+    pub pipeline: &'r Pipeline<'r>,
+}
+
+/// Where the items of a config came from after its includes were replaced,
+/// from [SuperConf::resolve_traced], for debugging configs which are merged
+/// together from many files
+///
+/// ```
+/// use superconf::{Parse, Pipeline, Placeholder, Provider, SuperConf, SuperValue};
+///
+/// struct Base;
+///
+/// impl Provider for Base {
+///     fn provide(&self, placeholder: &Placeholder<'_>) -> Option<Result<SuperValue<'static>, String>> {
+///         match placeholder {
+///             Placeholder::Include("base") => Some(Ok(SuperValue::parse("{port 80, tls {cert x.pem}}").unwrap().into_owned())),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// let mut conf = SuperConf::parse("name app\n@include base").unwrap();
+/// let provenance = conf.resolve_traced(&Pipeline::new().provider(Base)).unwrap();
+///
+/// assert_eq!(provenance.get("tls.cert").unwrap().include, "base");
+/// assert_eq!(provenance.get("name"), None);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Provenance {
+    /// Every include in the order it was replaced, as the include which it
+    /// was inside of, which is nothing for the config itself, and its name
+    pub graph: Vec<(Option<String>, String)>,
+    origins: Vec<(Path<'static>, Origin)>,
+}
+
+/// Include which an item came from, and the line it was on if the provider
+/// of the include gave its [source](Provider::source)
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Origin {
+    pub include: String,
+    /// Line counting from one
+    pub line: Option<usize>,
+}
+
+/// Error from [SuperConf::resolve] or [SuperConf::resolve_with], at the
+/// [Path] of the placeholder which couldn't be resolved
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ResolveError {
+    pub path: Path<'static>,
+    pub message: String,
+}
+
+impl<'p> Pipeline<'p> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a provider, which is consulted after every one before it
+    pub fn provider(mut self, provider: impl Provider + 'p) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    fn provide(
+        &self,
+        placeholder: &Placeholder<'_>,
+    ) -> Option<Result<SuperValue<'static>, String>> {
+        self.providers
+            .iter()
+            .find_map(|provider| provider.provide(placeholder))
+    }
+
+    fn source(&self, include: &str) -> Option<String> {
+        self.providers
+            .iter()
+            .find_map(|provider| provider.source(include))
+    }
+}
+
+impl Provider for Secrets<'_> {
+    fn provide(
+        &self,
+        placeholder: &Placeholder<'_>,
+    ) -> Option<Result<SuperValue<'static>, String>> {
+        match placeholder {
+            Placeholder::Scheme {
+                scheme: "secret",
+                reference,
+            } => Some(
+                self.0
+                    .resolve(reference)
+                    .map(|secret| SuperValue::Name(Cow::Owned(secret))),
+            ),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Provider for Files {
+    fn provide(
+        &self,
+        placeholder: &Placeholder<'_>,
+    ) -> Option<Result<SuperValue<'static>, String>> {
+        let Placeholder::Include(file) = placeholder else {
+            return None;
+        };
+
+        Some(
+            SuperConf::from_file(self.dir.join(file))
+                .map(|conf| SuperValue::group(conf.items))
+                .map_err(|err| err.to_string()),
+        )
+    }
+
+    fn source(&self, include: &str) -> Option<String> {
+        std::fs::read_to_string(self.dir.join(include)).ok()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Provider for Env {
+    fn provide(
+        &self,
+        placeholder: &Placeholder<'_>,
+    ) -> Option<Result<SuperValue<'static>, String>> {
+        let name = match placeholder {
+            Placeholder::Interpolation(reference) => reference.strip_prefix("env:")?,
+            _ => return None,
+        };
+
+        Some(match std::env::var(name) {
+            Ok(var) => SuperValue::parse(&var)
+                .map(SuperValue::into_owned)
+                .map_err(|err| err.to_string()),
+            Err(_) => Err(alloc::format!("environment variable {} isn't set", name)),
+        })
+    }
+}
+
+impl<'a> Deferred<'_, 'a> {
+    /// Gets the value at a path like [SuperConf::get], with every placeholder
+    /// inside of it resolved
+    pub fn get(&self, path: &str) -> Result<Option<SuperValue<'a>>, ResolveError> {
+        match Path::parse(path) {
+            Ok(path) => self.get_path(&path),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Gets the value at an already-parsed [Path] like [SuperConf::get_path],
+    /// with every placeholder inside of it resolved
+    pub fn get_path(&self, path: &Path<'_>) -> Result<Option<SuperValue<'a>>, ResolveError> {
+        let Some(value) = self.conf.get_path(path) else {
+            return Ok(None);
+        };
+
+        let mut value = value.clone();
+        let mut resolving = Resolving {
+            pipeline: self.pipeline,
+            conf: Some(self.conf),
+            stack: Vec::new(),
+            provenance: Provenance::default(),
+        };
+        resolving.value(&mut value, &mut path.clone().into_owned())?;
+        Ok(Some(value))
+    }
+}
+
+impl Provenance {
+    /// Finds where the value at a path came from, which is nothing if it's
+    /// from the config itself or the path is invalid
+    pub fn get(&self, path: &str) -> Option<&Origin> {
+        self.get_path(&Path::parse(path).ok()?)
+    }
+
+    /// Finds where the value at an already-parsed [Path] came from, which is
+    /// where the outermost item it's inside of was included from
+    pub fn get_path(&self, path: &Path<'_>) -> Option<&Origin> {
+        self.origins
+            .iter()
+            .rev()
+            .filter(|(found, _)| path.segments.starts_with(&found.segments))
+            .max_by_key(|(found, _)| found.segments.len())
+            .map(|(_, origin)| origin)
+    }
+}
+
+impl fmt::Debug for Pipeline<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("providers", &self.providers.len())
+            .finish()
+    }
+}
+
+impl<'a> SuperConf<'a> {
+    /// Replaces every name starting with `secret://` with the secret which
+    /// `resolver` gives for the rest of it, along with any `${path}`
+    /// placeholders, see [SuperConf::resolve_with]
+    ///
+    /// ```
+    /// use superconf::{Parse, SuperConf, SuperValue};
+    ///
+    /// let mut conf = SuperConf::parse("db {user app, password secret://vault/db}").unwrap();
+    /// conf.resolve(&|reference: &str| match reference {
+    ///     "vault/db" => Ok("hunter2".to_string()),
+    ///     _ => Err(format!("{} isn't in the vault", reference)),
+    /// })
+    /// .unwrap();
+    ///
+    /// assert_eq!(conf.get("db.password"), Some(&SuperValue::Name("hunter2".into())));
+    /// ```
+    pub fn resolve(&mut self, resolver: &impl Resolver) -> Result<(), ResolveError> {
+        self.resolve_with(&Pipeline::new().provider(Secrets(resolver)))
+    }
+
+    /// Replaces every placeholder with what `pipeline` provides for it, which
+    /// are:
+    ///
+    /// - An item with a key of `@include`, replaced by the items of the group
+    ///   its name is provided as
+    /// - `${reference}` inside of a name, replaced by the value provided for
+    ///   the reference or the value at it as a [Path] of the config, written
+    ///   as text if there's more to the name than just the placeholder
+    /// - A name like `scheme://reference`, replaced by the value provided
+    ///   for it and left alone if nothing provides it so that urls are kept
+    ///
+    /// Includes are replaced first, so interpolations can refer to values
+    /// from included files. Includes which include themselves and values
+    /// which refer back to themselves are errors.
+    pub fn resolve_with(&mut self, pipeline: &Pipeline<'_>) -> Result<(), ResolveError> {
+        self.resolve_traced(pipeline).map(|_| ())
+    }
+
+    /// Resolves like [SuperConf::resolve_with], giving back where each item
+    /// which was included came from
+    ///
+    /// Includes can be nested at most [MAX_INCLUDE_DEPTH] deep, and an
+    /// include which ends up including itself is an error which gives every
+    /// include along the way.
+    pub fn resolve_traced(&mut self, pipeline: &Pipeline<'_>) -> Result<Provenance, ResolveError> {
+        let mut resolving = Resolving {
+            pipeline,
+            conf: None,
+            stack: Vec::new(),
+            provenance: Provenance::default(),
+        };
+        resolving.includes(&mut self.items, &mut Path::new())?;
+
+        let conf = self.clone();
+        resolving.conf = Some(&conf);
+        resolving.items(&mut self.items, &mut Path::new())?;
+        Ok(resolving.provenance)
+    }
+
+    /// Looks up values with their placeholders resolved by `pipeline` only
+    /// as they're needed, see [Deferred]
+    pub fn deferred<'r>(&'r self, pipeline: &'r Pipeline<'r>) -> Deferred<'r, 'a> {
+        Deferred {
+            conf: self,
+            pipeline,
+        }
+    }
+}
+
+/// Where a single pass of [SuperConf::resolve_with] is up to
+struct Resolving<'r, 'a> {
+    pipeline: &'r Pipeline<'r>,
+    /// Config as it was after includes were replaced, which interpolations
+    /// are looked up in
+    conf: Option<&'r SuperConf<'a>>,
+    /// Includes or interpolations which are being resolved, so that they
+    /// can't refer back to themselves
+    stack: Vec<String>,
+    provenance: Provenance,
+}
+
+impl<'a> Resolving<'_, 'a> {
+    /// Replaces every `@include` item inside of `items` and their values
+    fn includes(
+        &mut self,
+        items: &mut impl Growable<'a>,
+        path: &mut Path<'static>,
+    ) -> Result<(), ResolveError> {
+        let mut ind = 0;
+        while let Some(item) = items.get_mut(ind) {
+            if item.key != INCLUDE_KEY {
+                path.push_key(item.key.clone().into_owned());
+                self.includes_value(&mut item.value, path)?;
+                path.pop();
+                ind += 1;
+                continue;
+            }
+
+            let name = match &item.value {
+                SuperValue::Name(name) => name.clone().into_owned(),
+                _ => return Err(error(path, "include isn't a name")),
+            };
+            if self.stack.contains(&name) {
+                let mut chain = self.stack.join(" includes ");
+                chain.push_str(" includes ");
+                chain.push_str(&name);
+                return Err(error(path, chain));
+            }
+            if self.stack.len() == MAX_INCLUDE_DEPTH {
+                return Err(error(
+                    path,
+                    alloc::format!("includes are nested more than {} deep", MAX_INCLUDE_DEPTH),
+                ));
+            }
+            let mut included = match self.pipeline.provide(&Placeholder::Include(&name)) {
+                Some(Ok(SuperValue::Group(included))) => *included,
+                Some(Ok(_)) => return Err(error(path, alloc::format!("{} isn't a group", name))),
+                Some(Err(message)) => return Err(error(path, message)),
+                None => {
+                    return Err(error(
+                        path,
+                        alloc::format!("nothing provides the include {}", name),
+                    ))
+                }
+            };
+
+            let lines = self.pipeline.source(&name).map(|text| item_lines(&text));
+            for (ind, item) in included.iter().enumerate() {
+                if item.key == INCLUDE_KEY {
+                    continue;
+                }
+                let mut path = path.clone();
+                path.push_key(item.key.clone().into_owned());
+                let line = lines.as_ref().and_then(|lines| lines.get(ind).copied());
+                self.provenance.origins.push((
+                    path,
+                    Origin {
+                        include: name.clone(),
+                        line,
+                    },
+                ));
+            }
+            self.provenance
+                .graph
+                .push((self.stack.last().cloned(), name.clone()));
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                include = %name,
+                from = ?self.stack.last(),
+                at = %path,
+                items = included.len(),
+                "including"
+            );
+
+            self.stack.push(name);
+            self.includes(&mut included, path)?;
+            self.stack.pop();
+
+            let len = included.len();
+            items.replace(ind, included);
+            ind += len;
+        }
+        Ok(())
+    }
+
+    fn includes_value(
+        &mut self,
+        value: &mut SuperValue<'a>,
+        path: &mut Path<'static>,
+    ) -> Result<(), ResolveError> {
+        match value {
+            SuperValue::Group(items) => self.includes(&mut **items, path),
+            SuperValue::List(values) => {
+                for (index, value) in values.iter_mut().enumerate() {
+                    path.push_index(index);
+                    self.includes_value(value, path)?;
+                    path.pop();
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn items(
+        &mut self,
+        items: &mut [SuperItem<'a>],
+        path: &mut Path<'static>,
+    ) -> Result<(), ResolveError> {
+        for item in items {
+            path.push_key(item.key.clone().into_owned());
+            self.value(&mut item.value, path)?;
+            path.pop();
+        }
+        Ok(())
+    }
+
+    fn value(
+        &mut self,
+        value: &mut SuperValue<'a>,
+        path: &mut Path<'static>,
+    ) -> Result<(), ResolveError> {
+        match value {
+            SuperValue::Name(name) => {
+                if let Some(resolved) = self.name(name).map_err(|message| error(path, message))? {
+                    *value = resolved;
+                }
+                Ok(())
+            }
+            SuperValue::Group(items) => self.items(items, path),
+            SuperValue::List(values) => {
+                for (index, value) in values.iter_mut().enumerate() {
+                    path.push_index(index);
+                    self.value(value, path)?;
+                    path.pop();
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Gives what a name resolves to, which is nothing if it doesn't have
+    /// any placeholders
+    fn name(&mut self, name: &str) -> Result<Option<SuperValue<'a>>, String> {
+        if let Some(reference) = name
+            .strip_prefix("${")
+            .and_then(|rest| rest.strip_suffix('}'))
+            .filter(|reference| !reference.contains("${") && !reference.contains('}'))
+        {
+            return self.interpolation(reference).map(Some);
+        }
+
+        if name.contains("${") {
+            let mut text = String::new();
+            let mut rest = name;
+            while let Some(start) = rest.find("${") {
+                let end = rest[start..]
+                    .find('}')
+                    .ok_or("placeholder isn't closed with `}`")?
+                    + start;
+                text.push_str(&rest[..start]);
+                match self.interpolation(&rest[start + 2..end])? {
+                    SuperValue::Name(name) => text.push_str(&name),
+                    value => value
+                        .emit_to(&mut text)
+                        .expect("writing to a string never fails"),
+                }
+                rest = &rest[end + 1..];
+            }
+            text.push_str(rest);
+            return Ok(Some(SuperValue::Name(Cow::Owned(text))));
+        }
+
+        match name.split_once("://") {
+            Some((scheme, reference)) if is_scheme(scheme) => self
+                .pipeline
+                .provide(&Placeholder::Scheme { scheme, reference })
+                .transpose(),
+            _ => Ok(None),
+        }
+    }
+
+    /// Gives the value which `${reference}` stands for, with any
+    /// placeholders inside of it resolved too
+    fn interpolation(&mut self, reference: &str) -> Result<SuperValue<'a>, String> {
+        if let Some(provided) = self
+            .pipeline
+            .provide(&Placeholder::Interpolation(reference))
+        {
+            return provided;
+        }
+
+        if self.stack.iter().any(|found| found == reference) {
+            return Err(alloc::format!("{} refers back to itself", reference));
+        }
+        let conf = self
+            .conf
+            .expect("interpolations are only resolved after includes");
+        let mut value = Path::parse(reference)
+            .ok()
+            .and_then(|path| conf.get_path(&path))
+            .ok_or_else(|| alloc::format!("nothing provides ${{{}}}", reference))?
+            .clone();
+
+        self.stack.push(reference.into());
+        // errors from inside of the value are reported where it's used
+        let resolved = self.value(&mut value, &mut Path::new());
+        self.stack.pop();
+
+        resolved.map(|_| value).map_err(|err| err.message)
+    }
+}
+
+/// Finds the line which each item at the top level of `text` starts on
+fn item_lines(text: &str) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let (mut line, mut counted, mut depth) = (1, 0, 0usize);
+
+    for found in Tokens::new(text) {
+        let Ok((token, span)) = found else {
+            break;
+        };
+        match token {
+            Token::Key(_) if depth == 0 => {
+                line += text[counted..span.start].matches('\n').count();
+                counted = span.start;
+                lines.push(line);
+            }
+            Token::ListStart | Token::GroupStart => depth += 1,
+            Token::ListEnd | Token::GroupEnd => depth = depth.saturating_sub(1),
+            _ => (),
+        }
+    }
+    lines
+}
+
+/// Checks if text before a `://` is a scheme, like `secret` or `https`
+fn is_scheme(scheme: &str) -> bool {
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+fn error(path: &Path<'static>, message: impl Into<String>) -> ResolveError {
+    ResolveError {
+        path: path.clone(),
+        message: message.into(),
+    }
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.include)?;
+        match self.line {
+            Some(line) => write!(f, ":{}", line),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl core::error::Error for ResolveError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault(reference: &str) -> Result<String, String> {
+        match reference {
+            "db" => Ok("hunter2".into()),
+            "keys/0" => Ok("abc".into()),
+            _ => Err("not found".into()),
+        }
+    }
+
+    /// Provides a group of items for each include
+    struct Includes;
+
+    impl Provider for Includes {
+        fn provide(
+            &self,
+            placeholder: &Placeholder<'_>,
+        ) -> Option<Result<SuperValue<'static>, String>> {
+            let text = match placeholder {
+                Placeholder::Include("base") => "port 80\nhost a",
+                Placeholder::Include("nested") => "@include base\nworkers ${answer}",
+                Placeholder::Include("loop") => "@include loop",
+                Placeholder::Include("ping") => "@include pong",
+                Placeholder::Include("pong") => "@include ping",
+                Placeholder::Include(name) if name.starts_with("deep") => {
+                    return Some(Ok(SuperValue::group(alloc::vec![SuperItem {
+                        key: INCLUDE_KEY.into(),
+                        value: SuperValue::Name(alloc::format!("{}x", name).into()),
+                        meta: None,
+                    }])))
+                }
+                Placeholder::Include("scalar") => return Some(Ok(SuperValue::Integer(1))),
+                Placeholder::Interpolation("answer") => return Some(Ok(SuperValue::Integer(42))),
+                _ => return None,
+            };
+            Some(Ok(SuperValue::group(
+                SuperConf::parse(text).unwrap().into_owned().items,
+            )))
+        }
+    }
+
+    fn resolve(text: &str) -> Result<SuperConf<'static>, String> {
+        let mut conf = SuperConf::parse(text).unwrap().into_owned();
+        let pipeline = Pipeline::new().provider(Includes).provider(Secrets(&vault));
+        conf.resolve_with(&pipeline)
+            .map(|_| conf)
+            .map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn secrets() {
+        let mut conf =
+            SuperConf::parse("db {password secret://db}\nkeys [secret://keys/0, plain]\nn 1")
+                .unwrap();
+        conf.resolve(&vault).unwrap();
+
+        assert_eq!(
+            conf,
+            SuperConf::parse("db {password hunter2}\nkeys [abc, plain]\nn 1").unwrap()
+        );
+
+        let mut conf = SuperConf::parse("a [1, {b secret://missing}]").unwrap();
+        assert_eq!(
+            conf.resolve(&vault).unwrap_err().to_string(),
+            "a[1].b: not found"
+        );
+    }
+
+    #[test]
+    fn pipeline() {
+        let conf = |text| SuperConf::parse(text).unwrap();
+
+        assert_eq!(
+            resolve("a {@include nested, x 1}\nlink https://${a.host}:${a.port}/${answer}\nb ${a}"),
+            Ok(conf("a {port 80, host a, workers 42, x 1}\nlink https://a:80/42\nb {port 80, host a, workers 42, x 1}"))
+        );
+        assert_eq!(
+            resolve("a ${b}\nb [${c}, 2]\nc {d ${e}}\ne x"),
+            Ok(conf("a [{d x}, 2]\nb [{d x}, 2]\nc {d x}\ne x"))
+        );
+        assert_eq!(
+            resolve("url https://example.com\ntoken secret://db"),
+            Ok(conf("url https://example.com\ntoken hunter2"))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            resolve("a ${b}\nb ${a}"),
+            Err("a: b refers back to itself".into())
+        );
+        assert_eq!(
+            resolve("a [x ${a}y]"),
+            Err("a[0]: a refers back to itself".into())
+        );
+        assert_eq!(
+            resolve("a $\\{x"),
+            Err("a: placeholder isn't closed with `}`".into())
+        );
+        assert_eq!(
+            resolve("a {b ${c}}"),
+            Err("a.b: nothing provides ${c}".into())
+        );
+        assert_eq!(
+            resolve("a {@include loop}"),
+            Err("a: loop includes loop".into())
+        );
+        assert_eq!(
+            resolve("@include ping"),
+            Err(": ping includes pong includes ping".into())
+        );
+        assert_eq!(
+            resolve("@include deep"),
+            Err(": includes are nested more than 16 deep".into())
+        );
+        assert_eq!(
+            resolve("@include scalar"),
+            Err(": scalar isn't a group".into())
+        );
+        assert_eq!(
+            resolve("@include other"),
+            Err(": nothing provides the include other".into())
+        );
+    }
+
+    #[test]
+    fn deferred() {
+        let asked = core::cell::Cell::new(0);
+        let vault = |reference: &str| {
+            asked.set(asked.get() + 1);
+            vault(reference)
+        };
+        let pipeline = Pipeline::new().provider(Secrets(&vault));
+        let conf =
+            SuperConf::parse("a {b secret://db, c ${d}}\nd [secret://keys/0]\ne secret://missing")
+                .unwrap();
+        let deferred = conf.deferred(&pipeline);
+
+        assert_eq!(
+            deferred.get("a.b"),
+            Ok(Some(SuperValue::Name("hunter2".into())))
+        );
+        assert_eq!(asked.get(), 1);
+        assert_eq!(
+            deferred.get("a.c"),
+            Ok(Some(SuperValue::list(alloc::vec![SuperValue::Name(
+                "abc".into()
+            )])))
+        );
+        assert_eq!(asked.get(), 2);
+        assert_eq!(deferred.get("x"), Ok(None));
+        assert_eq!(deferred.get("e").unwrap_err().to_string(), "e: not found");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn files() {
+        let dir = std::env::temp_dir().join("superconf-resolve-files");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(dir.join("base.sconf"), "port 80\n\n@include more.sconf").unwrap();
+        std::fs::write(
+            dir.join("more.sconf"),
+            "# hosts\nhost ${env:SUPERCONF_RESOLVE_HOST}",
+        )
+        .unwrap();
+        std::env::set_var("SUPERCONF_RESOLVE_HOST", "[a, b]");
+
+        let pipeline = Pipeline::new()
+            .provider(Files { dir: dir.clone() })
+            .provider(Env);
+        let mut conf = SuperConf::parse("a {@include base.sconf}\nname x").unwrap();
+        let provenance = conf.resolve_traced(&pipeline).unwrap();
+        assert_eq!(
+            conf,
+            SuperConf::parse("a {port 80, host [a, b]}\nname x").unwrap()
+        );
+
+        let origin = |path| provenance.get(path).map(ToString::to_string);
+        assert_eq!(origin("a.port"), Some("base.sconf:1".into()));
+        assert_eq!(origin("a.host[1]"), Some("more.sconf:2".into()));
+        assert_eq!(origin("a"), None);
+        assert_eq!(origin("name"), None);
+        assert_eq!(
+            provenance.graph,
+            [
+                (None, "base.sconf".into()),
+                (Some("base.sconf".into()), "more.sconf".into())
+            ]
+        );
+
+        let mut conf = SuperConf::parse("@include missing.sconf").unwrap();
+        assert!(conf.resolve_with(&pipeline).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
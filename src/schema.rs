@@ -0,0 +1,1841 @@
+//! Schemas describing the items which a config is expected to have, so that
+//! whole configs can be checked with [Schema::validate] before they're used
+
+use super::convert::{from_item, ConvertError, FromSuperValue};
+use super::tokens::{Span, Token, Tokens};
+use super::{
+    value, Group, Growable, Parse, Path, Segment, SuperConf, SuperError, SuperItem, SuperValue,
+};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::{Bound, RangeBounds};
+
+#[cfg(feature = "regex")]
+pub use regex_lite::Regex;
+
+/// Items which a config or group is expected to have, where any items which
+/// aren't in the schema are left alone unless [Schema::deny_unknown] is set
+///
+/// ```
+/// use superconf::schema::{Kind, Rule, Schema, ViolationKind};
+/// use superconf::{Parse, SuperConf};
+///
+/// let schema = Schema::new()
+///     .required("port", Kind::Integer)
+///     .optional("hosts", Rule::list(Kind::Name))
+///     .optional("tls", Schema::new().required("enabled", Kind::Bool));
+///
+/// let conf = SuperConf::parse("port 80\nhosts [a, b]").unwrap();
+/// assert!(schema.validate(&conf).is_valid());
+///
+/// let report = schema.validate_text("port x\ntls {}").unwrap();
+/// assert_eq!(report.to_string(), "port: expected an integer\ntls.enabled: missing item");
+/// assert_eq!(report.violations[1].kind, ViolationKind::Missing);
+/// assert_eq!(report.violations[1].span.map(|span| span.start), Some(7));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    pub entries: Vec<Entry>,
+    /// Gives [ViolationKind::Unknown] for any item whose key isn't one of
+    /// the entries, here or in any schema inside of this one, see
+    /// [Schema::deny_unknown]
+    pub deny_unknown: bool,
+    /// Rules over more than one item, which are checked after every item
+    pub relations: Vec<Relation>,
+}
+
+/// Rule over more than one item of a config or group, where paths are from
+/// the group which the schema is for
+///
+/// ```
+/// use superconf::schema::{Kind, Schema};
+/// use superconf::{Parse, SuperConf};
+///
+/// let tls = Schema::new()
+///     .required("enabled", Kind::Bool)
+///     .optional("cert", Kind::Name)
+///     .required_if("cert", "enabled", true);
+/// let schema = Schema::new()
+///     .optional("min", Kind::Integer)
+///     .optional("max", Kind::Integer)
+///     .at_most("min", "max")
+///     .required("tls", tls);
+///
+/// let report = schema.validate(&SuperConf::parse("min 5\nmax 2\ntls {enabled true}").unwrap());
+/// assert_eq!(
+///     report.to_string(),
+///     "tls.cert: missing item, which is required when tls.enabled is true\n\
+///      min: expected at most max which is 2, found 5"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub enum Relation {
+    /// Requires the item at `then` when the value at `when` is `is`
+    RequiredIf {
+        then: Path<'static>,
+        when: Path<'static>,
+        is: SuperValue<'static>,
+    },
+    /// Requires the number at `lesser` to be at most the number at `greater`,
+    /// if they're both numbers
+    AtMost {
+        lesser: Path<'static>,
+        greater: Path<'static>,
+    },
+    /// Check of the application's own over the items, which gives
+    /// [ViolationKind::Custom] at the group with the message it fails with
+    Custom(ItemsValidator),
+}
+
+/// Check of the application's own for [Relation::Custom], which gives the
+/// message to report if the items don't pass
+pub type ItemsValidator = fn(&[SuperItem<'_>]) -> Result<(), String>;
+
+/// Item which a [Schema] expects
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub key: Cow<'static, str>,
+    /// Gives [ViolationKind::Missing] if there's no item with this key
+    pub required: bool,
+    /// Value which [Schema::apply_defaults] adds the item with if it's
+    /// missing
+    pub default: Option<SuperValue<'static>>,
+    pub rule: Rule,
+}
+
+/// What a value is expected to be, for the value of an [Entry] or for every
+/// element of a list
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub kind: Kind,
+    /// Allows nothing as well as values of [Rule::kind], like for an [Option]
+    pub nothing: bool,
+    /// Schema for the items of a group
+    pub items: Option<Schema>,
+    /// Rule for every element of a list
+    pub elements: Option<Box<Rule>>,
+    /// Names which are allowed, if only some are
+    pub one_of: Option<Vec<Cow<'static, str>>>,
+    /// Numbers which are allowed, for integers and floats
+    pub range: (Bound<f64>, Bound<f64>),
+    /// Numbers of elements which are allowed, for lists
+    pub len: (Bound<usize>, Bound<usize>),
+    /// Pattern which names have to match somewhere, behind the `regex`
+    /// feature
+    #[cfg(feature = "regex")]
+    pub pattern: Option<Regex>,
+    /// Checks of the application's own, which are only run once the value
+    /// passes every other check and give [ViolationKind::Custom] with the
+    /// message they fail with
+    pub validators: Vec<Validator>,
+}
+
+/// Check of the application's own for [Rule::validators], which gives the
+/// message to report if the value doesn't pass
+pub type Validator = fn(&SuperValue<'_>) -> Result<(), String>;
+
+/// Type of value which a [Rule] expects
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Kind {
+    /// Any value at all, including nothing
+    Any,
+    Bool,
+    Integer,
+    /// Float, or an integer which can always be used as one
+    Float,
+    Name,
+    /// Binary data written as `b64"..."`
+    Bytes,
+    List,
+    Group,
+}
+
+/// Every way in which a config doesn't match a [Schema], given by
+/// [Schema::validate]
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Report {
+    /// Violations in the order they were found, which is the order of the
+    /// schema's entries and then of any list elements, with unknown items
+    /// after the entries of their group and any relations after everything
+    pub violations: Vec<Violation>,
+}
+
+/// Way in which a config doesn't match a [Schema], at the [Path] of the
+/// value or missing item
+#[derive(Debug, PartialEq, Clone)]
+pub struct Violation {
+    pub path: Path<'static>,
+    /// Where the offending item or element was written, or for a missing
+    /// item the group it's missing from, once found by [Report::locate]
+    pub span: Option<Span>,
+    pub kind: ViolationKind,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ViolationKind {
+    /// When a required item isn't in its group or the config
+    Missing,
+    /// When an item required by [Relation::RequiredIf] isn't there,
+    /// containing the full path of the value which required it
+    RequiredBy {
+        when: Path<'static>,
+        is: SuperValue<'static>,
+    },
+    /// When a number is more than the one it has to be at most by
+    /// [Relation::AtMost], containing the full path of the other number
+    NotAtMost {
+        greater: Path<'static>,
+        found: f64,
+        limit: f64,
+    },
+    /// When an item isn't in a schema which denies unknown items
+    Unknown {
+        /// Key of an entry which is close enough to the one written that it
+        /// was likely meant, if there is one
+        closest: Option<Cow<'static, str>>,
+    },
+    /// When a value is the wrong type, containing the type which was expected
+    Expected(Kind),
+    /// When a name isn't one of the names which are allowed
+    NotOneOf {
+        allowed: Vec<Cow<'static, str>>,
+        /// Allowed name which is close enough to the one written that it was
+        /// likely meant, if there is one
+        closest: Option<Cow<'static, str>>,
+    },
+    /// When a number is outside of the range which is allowed
+    OutOfRange {
+        found: f64,
+        range: (Bound<f64>, Bound<f64>),
+    },
+    /// When a list has too few or too many elements
+    WrongLength {
+        found: usize,
+        range: (Bound<usize>, Bound<usize>),
+    },
+    /// When a name doesn't match the pattern it has to, containing the
+    /// pattern as it was written
+    NoMatch(String),
+    /// When a validator of [Rule::validators] fails, containing its message
+    Custom(String),
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an entry for an item which has to be there
+    pub fn required(self, key: impl Into<Cow<'static, str>>, rule: impl Into<Rule>) -> Self {
+        self.entry(Entry {
+            key: key.into(),
+            required: true,
+            default: None,
+            rule: rule.into(),
+        })
+    }
+
+    /// Adds an entry for an item which only has to match if it's there
+    pub fn optional(self, key: impl Into<Cow<'static, str>>, rule: impl Into<Rule>) -> Self {
+        self.entry(Entry {
+            key: key.into(),
+            required: false,
+            default: None,
+            rule: rule.into(),
+        })
+    }
+
+    /// Adds an entry for an item which is added with `default` by
+    /// [Schema::apply_defaults] if it's missing
+    pub fn defaulted(
+        self,
+        key: impl Into<Cow<'static, str>>,
+        rule: impl Into<Rule>,
+        default: impl Into<SuperValue<'static>>,
+    ) -> Self {
+        self.entry(Entry {
+            key: key.into(),
+            required: false,
+            default: Some(default.into()),
+            rule: rule.into(),
+        })
+    }
+
+    pub fn entry(mut self, entry: Entry) -> Self {
+        self.entries.push(entry);
+        self
+    }
+
+    /// Denies items which aren't in this schema or any schema inside of it,
+    /// which would otherwise be left alone, so that typos are caught
+    ///
+    /// This is only checked when validating, so it also applies to entries
+    /// which are added after it.
+    ///
+    /// ```
+    /// use superconf::schema::{Kind, Schema};
+    /// use superconf::{Parse, SuperConf};
+    ///
+    /// let schema = Schema::new()
+    ///     .optional("port", Kind::Integer)
+    ///     .optional("tls", Schema::new().optional("cert", Kind::Name))
+    ///     .deny_unknown();
+    /// let report = schema.validate(&SuperConf::parse("prot 8080\ntls {cret a}").unwrap());
+    ///
+    /// assert_eq!(
+    ///     report.to_string(),
+    ///     "tls.cret: unknown item, did you mean cert?\nprot: unknown item, did you mean port?"
+    /// );
+    /// ```
+    pub fn deny_unknown(mut self) -> Self {
+        self.deny_unknown = true;
+        self
+    }
+
+    /// Requires the item at `then` when the value at `when` is `is`, see
+    /// [Relation::RequiredIf]
+    ///
+    /// Panics if either path is invalid.
+    pub fn required_if(
+        self,
+        then: &'static str,
+        when: &'static str,
+        is: impl Into<SuperValue<'static>>,
+    ) -> Self {
+        self.relation(Relation::RequiredIf {
+            then: relative(then),
+            when: relative(when),
+            is: is.into(),
+        })
+    }
+
+    /// Requires the number at `lesser` to be at most the number at `greater`,
+    /// see [Relation::AtMost]
+    ///
+    /// Panics if either path is invalid.
+    pub fn at_most(self, lesser: &'static str, greater: &'static str) -> Self {
+        self.relation(Relation::AtMost {
+            lesser: relative(lesser),
+            greater: relative(greater),
+        })
+    }
+
+    pub fn relation(mut self, relation: Relation) -> Self {
+        self.relations.push(relation);
+        self
+    }
+
+    /// Gets the entry for `key`
+    pub fn get(&self, key: &str) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.key == key)
+    }
+
+    /// Checks `conf` against the schema, giving every way it doesn't match
+    /// without any spans as the text it came from isn't known
+    ///
+    /// Only the first item with a key is checked if it's repeated, as that's
+    /// the one which [SuperConf::get] gives.
+    pub fn validate(&self, conf: &SuperConf<'_>) -> Report {
+        let mut violations = Vec::new();
+        let mut relations = Vec::new();
+        self.check_items(
+            &conf.items,
+            &mut Path::new(),
+            &mut violations,
+            &mut relations,
+            false,
+        );
+
+        violations.append(&mut relations);
+        Report { violations }
+    }
+
+    /// Parses and checks `text` against the schema, giving every way it
+    /// doesn't match along with where it was written
+    pub fn validate_text(&self, text: &str) -> Result<Report, SuperError> {
+        let mut report = self.validate(&SuperConf::parse(text)?);
+        report.locate(text);
+        Ok(report)
+    }
+
+    /// Adds every missing item which has a default, including inside of groups
+    /// and the groups of lists, so that they don't have to be defaulted when
+    /// they're read
+    ///
+    /// A missing group which isn't required is added too if the schema for
+    /// its items gives it any defaults.
+    ///
+    /// ```
+    /// use superconf::schema::{Kind, Schema};
+    /// use superconf::{Parse, SuperConf, SuperValue};
+    ///
+    /// let schema = Schema::new()
+    ///     .defaulted("port", Kind::Integer, 8080)
+    ///     .optional("tls", Schema::new().defaulted("enabled", Kind::Bool, false));
+    ///
+    /// let mut conf = SuperConf::parse("port 80").unwrap();
+    /// schema.apply_defaults(&mut conf);
+    ///
+    /// assert_eq!(conf.get("port"), Some(&SuperValue::Integer(80)));
+    /// assert_eq!(conf.get("tls.enabled"), Some(&SuperValue::Bool(false)));
+    /// ```
+    pub fn apply_defaults(&self, conf: &mut SuperConf<'_>) {
+        self.fill(&mut conf.items)
+    }
+
+    fn fill<'a>(&self, items: &mut impl Growable<'a>) {
+        for entry in &self.entries {
+            match items.iter_mut().find(|item| item.key == entry.key) {
+                Some(item) => entry.rule.fill(&mut item.value),
+                None => {
+                    if let Some(value) = entry.missing() {
+                        items.push(SuperItem {
+                            key: entry.key.clone(),
+                            value,
+                            meta: None,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks `items` against the schema, where `denied` is if a schema
+    /// around this one denies unknown items
+    fn check_items(
+        &self,
+        items: &[SuperItem<'_>],
+        path: &mut Path<'static>,
+        violations: &mut Vec<Violation>,
+        relations: &mut Vec<Violation>,
+        denied: bool,
+    ) {
+        let denied = denied || self.deny_unknown;
+
+        for entry in &self.entries {
+            path.push_key(entry.key.clone());
+            match items.iter().find(|item| item.key == entry.key) {
+                Some(item) => entry
+                    .rule
+                    .check(&item.value, path, violations, relations, denied),
+                None if entry.required => violations.push(Violation {
+                    path: path.clone(),
+                    span: None,
+                    kind: ViolationKind::Missing,
+                }),
+                None => (),
+            }
+            path.pop();
+        }
+        for relation in &self.relations {
+            relation.check(items, path, relations);
+        }
+
+        if !denied {
+            return;
+        }
+        for item in items {
+            if self.get(&item.key).is_none() {
+                let keys: Vec<&Cow<'static, str>> =
+                    self.entries.iter().map(|entry| &entry.key).collect();
+                path.push_key(item.key.clone().into_owned());
+                violations.push(Violation {
+                    path: path.clone(),
+                    span: None,
+                    kind: ViolationKind::Unknown {
+                        closest: closest(&item.key, &keys).map(|&key| key.clone()),
+                    },
+                });
+                path.pop();
+            }
+        }
+    }
+}
+
+impl Relation {
+    fn check(&self, items: &[SuperItem<'_>], path: &Path<'static>, found: &mut Vec<Violation>) {
+        // full path of a path from the group
+        let full = |relative: &Path<'static>| {
+            let mut full = path.clone();
+            full.segments.extend(relative.segments.iter().cloned());
+            full
+        };
+        let mut violation = |path, kind| {
+            found.push(Violation {
+                path,
+                span: None,
+                kind,
+            })
+        };
+
+        match self {
+            Self::RequiredIf { then, when, is } => {
+                if value(items, &when.segments) == Some(is)
+                    && value(items, &then.segments).is_none()
+                {
+                    let kind = ViolationKind::RequiredBy {
+                        when: full(when),
+                        is: is.clone(),
+                    };
+                    violation(full(then), kind);
+                }
+            }
+            Self::AtMost { lesser, greater } => {
+                let number = |path: &Path<'static>| match value(items, &path.segments)? {
+                    SuperValue::Integer(integer) => Some(*integer as f64),
+                    SuperValue::Float(float) => Some(*float),
+                    _ => None,
+                };
+                if let (Some(found), Some(limit)) = (number(lesser), number(greater)) {
+                    if found > limit {
+                        let kind = ViolationKind::NotAtMost {
+                            greater: full(greater),
+                            found,
+                            limit,
+                        };
+                        violation(full(lesser), kind);
+                    }
+                }
+            }
+            Self::Custom(validator) => {
+                if let Err(message) = validator(items) {
+                    violation(path.clone(), ViolationKind::Custom(message));
+                }
+            }
+        }
+    }
+}
+
+impl Entry {
+    /// Value which the item is added with when it's missing, if any
+    fn missing(&self) -> Option<SuperValue<'static>> {
+        if let Some(default) = &self.default {
+            return Some(default.clone());
+        }
+
+        match (&self.rule.items, self.required) {
+            (Some(schema), false) => {
+                let mut items = Group::new();
+                schema.fill(&mut items);
+                (!items.is_empty()).then(|| SuperValue::Group(Box::new(items)))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Rule {
+    pub fn new(kind: Kind) -> Self {
+        Self {
+            kind,
+            nothing: false,
+            items: None,
+            elements: None,
+            one_of: None,
+            range: (Bound::Unbounded, Bound::Unbounded),
+            len: (Bound::Unbounded, Bound::Unbounded),
+            #[cfg(feature = "regex")]
+            pattern: None,
+            validators: Vec::new(),
+        }
+    }
+
+    /// Rule for a group whose items match `schema`
+    pub fn group(schema: Schema) -> Self {
+        Self {
+            items: Some(schema),
+            ..Self::new(Kind::Group)
+        }
+    }
+
+    /// Rule for a list whose elements all match `rule`
+    pub fn list(rule: impl Into<Rule>) -> Self {
+        Self {
+            elements: Some(Box::new(rule.into())),
+            ..Self::new(Kind::List)
+        }
+    }
+
+    /// Rule for a name which has to be one of `names`
+    ///
+    /// ```
+    /// use superconf::schema::{Rule, Schema};
+    /// use superconf::{Parse, SuperConf};
+    ///
+    /// let levels = ["trace", "debug", "info", "warn", "error"];
+    /// let schema = Schema::new().required("log_level", Rule::one_of(levels));
+    /// let report = schema.validate(&SuperConf::parse("log_level warm").unwrap());
+    ///
+    /// assert_eq!(
+    ///     report.to_string(),
+    ///     "log_level: expected one of trace, debug, info, warn or error, did you mean warn?"
+    /// );
+    /// ```
+    pub fn one_of<N: Into<Cow<'static, str>>>(names: impl IntoIterator<Item = N>) -> Self {
+        Self {
+            one_of: Some(names.into_iter().map(Into::into).collect()),
+            ..Self::new(Kind::Name)
+        }
+    }
+
+    /// Only allows numbers inside of `range`
+    ///
+    /// ```
+    /// use superconf::schema::{Kind, Rule, Schema};
+    /// use superconf::{Parse, SuperConf};
+    ///
+    /// let schema = Schema::new()
+    ///     .required("port", Rule::new(Kind::Integer).range(1..=65535))
+    ///     .optional("retries", Rule::new(Kind::Integer).range(0..))
+    ///     .optional("hosts", Rule::list(Kind::Name).len(1..=8));
+    /// let report = schema.validate(&SuperConf::parse("port 0\nretries -1\nhosts []").unwrap());
+    ///
+    /// assert_eq!(
+    ///     report.to_string(),
+    ///     "port: expected a number from 1 to 65535, found 0\n\
+    ///      retries: expected a number of at least 0, found -1\n\
+    ///      hosts: expected from 1 to 8 elements, found 0"
+    /// );
+    /// ```
+    pub fn range<T: Copy + Into<f64>>(mut self, range: impl RangeBounds<T>) -> Self {
+        self.range = (
+            bound(range.start_bound(), |found| found.into()),
+            bound(range.end_bound(), |found| found.into()),
+        );
+        self
+    }
+
+    /// Only allows names which match `pattern`, which has to be anchored
+    /// with `^` and `$` to match the whole name
+    ///
+    /// ```
+    /// use superconf::schema::{Kind, Regex, Rule, Schema};
+    /// use superconf::{Parse, SuperConf};
+    ///
+    /// let host = Regex::new(r"^[a-z0-9-]+(\.[a-z0-9-]+)*$").unwrap();
+    /// let schema = Schema::new().required("host", Rule::new(Kind::Name).pattern(host));
+    ///
+    /// assert!(schema.validate(&SuperConf::parse("host example.com").unwrap()).is_valid());
+    /// assert_eq!(
+    ///     schema.validate(&SuperConf::parse("host bad_host").unwrap()).to_string(),
+    ///     r"host: expected a name matching ^[a-z0-9-]+(\.[a-z0-9-]+)*$"
+    /// );
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn pattern(mut self, pattern: Regex) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
+
+    /// Allows nothing as well, see [Rule::nothing]
+    pub fn or_nothing(mut self) -> Self {
+        self.nothing = true;
+        self
+    }
+
+    /// Adds a check of the application's own, see [Rule::validators]
+    ///
+    /// ```
+    /// use superconf::schema::{Kind, Rule, Schema};
+    /// use superconf::{Parse, SuperConf, SuperValue};
+    ///
+    /// fn absolute(value: &SuperValue) -> Result<(), String> {
+    ///     match value {
+    ///         SuperValue::Name(path) if path.starts_with('/') => Ok(()),
+    ///         _ => Err("path must be absolute".into()),
+    ///     }
+    /// }
+    ///
+    /// let schema = Schema::new().required("root", Rule::new(Kind::Name).validator(absolute));
+    /// let report = schema.validate(&SuperConf::parse("root srv/www").unwrap());
+    ///
+    /// assert_eq!(report.to_string(), "root: path must be absolute");
+    /// ```
+    pub fn validator(mut self, validator: Validator) -> Self {
+        self.validators.push(validator);
+        self
+    }
+
+    /// Only allows lists with a number of elements inside of `range`
+    pub fn len(mut self, range: impl RangeBounds<usize>) -> Self {
+        self.len = (
+            bound(range.start_bound(), |found| found),
+            bound(range.end_bound(), |found| found),
+        );
+        self
+    }
+
+    fn fill(&self, value: &mut SuperValue<'_>) {
+        match (value, &self.items, &self.elements) {
+            (SuperValue::Group(items), Some(schema), _) => schema.fill(&mut **items),
+            (SuperValue::List(values), _, Some(rule)) => {
+                values.iter_mut().for_each(|value| rule.fill(value))
+            }
+            _ => (),
+        }
+    }
+
+    fn check(
+        &self,
+        value: &SuperValue<'_>,
+        path: &mut Path<'static>,
+        violations: &mut Vec<Violation>,
+        relations: &mut Vec<Violation>,
+        denied: bool,
+    ) {
+        if let Some(kind) = self.violation(value) {
+            violations.push(Violation {
+                path: path.clone(),
+                span: None,
+                kind,
+            });
+            return;
+        }
+        for validator in &self.validators {
+            if let Err(message) = validator(value) {
+                violations.push(Violation {
+                    path: path.clone(),
+                    span: None,
+                    kind: ViolationKind::Custom(message),
+                });
+            }
+        }
+
+        match (value, &self.items, &self.elements) {
+            (SuperValue::Group(items), Some(schema), _) => {
+                schema.check_items(items, path, violations, relations, denied)
+            }
+            (SuperValue::List(values), _, Some(rule)) => {
+                for (ind, value) in values.iter().enumerate() {
+                    path.push_index(ind);
+                    rule.check(value, path, violations, relations, denied);
+                    path.pop();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn out_of_range(&self, found: f64) -> Option<ViolationKind> {
+        (!self.range.contains(&found)).then_some(ViolationKind::OutOfRange {
+            found,
+            range: self.range,
+        })
+    }
+
+    /// Checks `value` itself without going into any of its children
+    fn violation(&self, value: &SuperValue<'_>) -> Option<ViolationKind> {
+        if self.nothing && *value == SuperValue::Nothing {
+            return None;
+        }
+        if !self.kind.matches(value) {
+            return Some(ViolationKind::Expected(self.kind));
+        }
+
+        match value {
+            SuperValue::Name(name) => {
+                if let Some(allowed) = self.one_of.as_ref().filter(|found| !found.contains(name)) {
+                    return Some(ViolationKind::NotOneOf {
+                        allowed: allowed.clone(),
+                        closest: closest(name, allowed).cloned(),
+                    });
+                }
+                #[cfg(feature = "regex")]
+                if let Some(pattern) = self.pattern.as_ref().filter(|found| !found.is_match(name)) {
+                    return Some(ViolationKind::NoMatch(pattern.as_str().into()));
+                }
+                None
+            }
+            SuperValue::Integer(integer) => self.out_of_range(*integer as f64),
+            SuperValue::Float(float) => self.out_of_range(*float),
+            SuperValue::List(values) => {
+                (!self.len.contains(&values.len())).then_some(ViolationKind::WrongLength {
+                    found: values.len(),
+                    range: self.len,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Types which a [Rule] can be made for, so that a schema can be made for a
+/// struct with `#[derive(SuperSchema)]` from the `derive` feature
+///
+/// Structs which derive this have a group as their rule, with an entry for
+/// each field like `#[derive(SuperConfig)]` would read it. Fields can be
+/// given a default with `#[schema(default = ...)]`, and fields with
+/// `#[superconf(default)]` are given the [Default] of their type.
+///
+/// ```
+/// # #[cfg(feature = "derive")] {
+/// use superconf::schema::Schema;
+/// use superconf::{Parse, SuperConf, SuperConfig, SuperSchema};
+///
+/// #[derive(SuperConfig, SuperSchema)]
+/// struct Config {
+///     port: u16,
+///     #[schema(default = "info")]
+///     level: String,
+/// }
+///
+/// let schema = Schema::of::<Config>();
+/// let report = schema.validate(&SuperConf::parse("port 70000").unwrap());
+///
+/// assert_eq!(report.to_string(), "port: expected a number from 0 to 65535, found 70000");
+/// assert_eq!(schema.get("level").and_then(|entry| entry.default.clone()), Some("info".into()));
+/// # }
+/// ```
+pub trait SuperSchema {
+    fn rule() -> Rule;
+
+    /// If an item of this type has to be there, which isn't the case for
+    /// [Option]
+    fn required() -> bool {
+        true
+    }
+}
+
+impl Schema {
+    /// Gets the schema for the items of a type, which is empty if its rule
+    /// isn't for a group
+    pub fn of<T: SuperSchema + ?Sized>() -> Self {
+        T::rule().items.unwrap_or_default()
+    }
+
+    /// Reads a schema which is written as a config itself, so it can be kept
+    /// in a file next to the configs it's for, such as to check an embedded
+    /// config at compile time with `include_superconf!`
+    ///
+    /// The config is written like the rule of a group, where every rule is a
+    /// group of fields named after the builder methods:
+    ///
+    /// - `kind` is one of `any`, `bool`, `integer`, `float`, `name`, `bytes`,
+    ///   `list` or `group`, which is found from the other fields if it's left
+    ///   out
+    /// - `items` has an entry for each item of a group, which can also have
+    ///   `optional` or a `default`
+    /// - `elements` is the rule for every element of a list
+    /// - `one_of`, `range`, `len`, `or_nothing` and `deny_unknown` are the
+    ///   same as the builder methods, where `range` and `len` have an
+    ///   inclusive `min` and `max`, and `pattern` needs the `regex` feature
+    ///
+    /// Relations and validators can only be added with the builder.
+    ///
+    /// ```
+    /// use superconf::schema::Schema;
+    /// use superconf::{Parse, SuperConf};
+    ///
+    /// let schema = SuperConf::parse(
+    ///     "deny_unknown\nitems {\n    port {kind integer, range {min 1, max 65535}}\n    hosts {optional, elements {kind name}}\n}",
+    /// )
+    /// .unwrap();
+    /// let schema = Schema::from_conf(&schema).unwrap();
+    ///
+    /// let report = schema.validate_text("port 0\nhsots [a]").unwrap();
+    /// assert_eq!(
+    ///     report.to_string(),
+    ///     "port: expected a number from 1 to 65535, found 0\nhsots: unknown item, did you mean hosts?"
+    /// );
+    /// ```
+    pub fn from_conf(conf: &SuperConf<'_>) -> Result<Self, ConvertError> {
+        let fields = fields_of(&conf.items, &["items", "deny_unknown"])?;
+        let mut schema = match fields.iter().find(|field| field.key == "items") {
+            Some(field) => schema_of(&field.value).map_err(|err| within(err, "items"))?,
+            None => Schema::new(),
+        };
+
+        schema.deny_unknown = flag(fields, "deny_unknown")?;
+        Ok(schema)
+    }
+}
+
+/// Fields which a [Rule] is written with for [Schema::from_conf]
+const RULE_FIELDS: [&str; 9] = [
+    "kind",
+    "items",
+    "elements",
+    "one_of",
+    "range",
+    "len",
+    "pattern",
+    "or_nothing",
+    "deny_unknown",
+];
+
+/// Fields which only an [Entry] can have on top of its rule
+const ENTRY_FIELDS: [&str; 2] = ["optional", "default"];
+
+fn within(err: ConvertError, key: &str) -> ConvertError {
+    err.within(Segment::Key(Cow::Owned(key.into())))
+}
+
+/// Gives the items of a group of fields, making sure every one is `allowed`
+fn fields_of<'v, 'a>(
+    items: &'v [SuperItem<'a>],
+    allowed: &[&str],
+) -> Result<&'v [SuperItem<'a>], ConvertError> {
+    match items.iter().find(|item| !allowed.contains(&&*item.key)) {
+        Some(item) => Err(within(ConvertError::expected("a known field"), &item.key)),
+        None => Ok(items),
+    }
+}
+
+fn group_of<'v, 'a>(value: &'v SuperValue<'a>) -> Result<&'v [SuperItem<'a>], ConvertError> {
+    match value {
+        SuperValue::Group(items) => Ok(items),
+        _ => Err(ConvertError::expected("a group of fields")),
+    }
+}
+
+/// Reads a field which is a bool, or nothing if it's just written as its key
+/// like `optional`
+fn flag(fields: &[SuperItem<'_>], key: &'static str) -> Result<bool, ConvertError> {
+    match fields.iter().find(|field| field.key == key) {
+        Some(SuperItem {
+            value: SuperValue::Nothing,
+            ..
+        }) => Ok(true),
+        Some(field) => bool::from_value(&field.value).map_err(|err| within(err, key)),
+        None => Ok(false),
+    }
+}
+
+fn schema_of(value: &SuperValue<'_>) -> Result<Schema, ConvertError> {
+    let mut schema = Schema::new();
+    for item in group_of(value)? {
+        let entry = entry_of(&item.key, &item.value).map_err(|err| within(err, &item.key))?;
+        schema.entries.push(entry);
+    }
+    Ok(schema)
+}
+
+fn entry_of(key: &str, value: &SuperValue<'_>) -> Result<Entry, ConvertError> {
+    let fields = fields_of(
+        group_of(value)?,
+        &[&RULE_FIELDS[..], &ENTRY_FIELDS].concat(),
+    )?;
+    let default = fields
+        .iter()
+        .find(|field| field.key == "default")
+        .map(|field| field.value.clone().into_owned());
+
+    Ok(Entry {
+        key: Cow::Owned(key.into()),
+        required: !flag(fields, "optional")? && default.is_none(),
+        default,
+        rule: rule_of(fields)?,
+    })
+}
+
+fn rule_of(fields: &[SuperItem<'_>]) -> Result<Rule, ConvertError> {
+    let field = |key| fields.iter().find(|field| field.key == key);
+    let mut rule = Rule::new(Kind::Any);
+
+    if let Some(field) = field("items") {
+        rule.items = Some(schema_of(&field.value).map_err(|err| within(err, "items"))?);
+    }
+    if let Some(field) = field("elements") {
+        let elements = group_of(&field.value)
+            .and_then(|found| fields_of(found, &RULE_FIELDS))
+            .and_then(rule_of)
+            .map_err(|err| within(err, "elements"))?;
+        rule.elements = Some(Box::new(elements));
+    }
+    rule.one_of = from_item::<Option<Vec<String>>>(fields, "one_of")?
+        .map(|names| names.into_iter().map(Cow::Owned).collect());
+    if let Some(field) = field("range") {
+        rule.range = bounds_of(&field.value).map_err(|err| within(err, "range"))?;
+    }
+    if let Some(field) = field("len") {
+        rule.len = bounds_of(&field.value).map_err(|err| within(err, "len"))?;
+    }
+    #[cfg(feature = "regex")]
+    if let Some(field) = field("pattern") {
+        rule.pattern = Some(pattern_of(&field.value).map_err(|err| within(err, "pattern"))?);
+    }
+    #[cfg(not(feature = "regex"))]
+    if field("pattern").is_some() {
+        let err = ConvertError::expected("the regex feature to be enabled");
+        return Err(within(err, "pattern"));
+    }
+    rule.nothing = flag(fields, "or_nothing")?;
+    if flag(fields, "deny_unknown")? {
+        rule.items.get_or_insert_with(Schema::new).deny_unknown = true;
+    }
+
+    rule.kind = match field("kind") {
+        Some(field) => kind_of(&field.value).map_err(|err| within(err, "kind"))?,
+        None if rule.items.is_some() => Kind::Group,
+        None if rule.elements.is_some() => Kind::List,
+        None if rule.one_of.is_some() => Kind::Name,
+        None => Kind::Any,
+    };
+    Ok(rule)
+}
+
+fn kind_of(value: &SuperValue<'_>) -> Result<Kind, ConvertError> {
+    match <&str>::from_value(value)? {
+        "any" => Ok(Kind::Any),
+        "bool" => Ok(Kind::Bool),
+        "integer" => Ok(Kind::Integer),
+        "float" => Ok(Kind::Float),
+        "name" => Ok(Kind::Name),
+        "bytes" => Ok(Kind::Bytes),
+        "list" => Ok(Kind::List),
+        "group" => Ok(Kind::Group),
+        _ => Err(ConvertError::expected("a kind of value")),
+    }
+}
+
+/// Reads the inclusive `min` and `max` of a range, where either can be left
+/// out
+fn bounds_of<'a, T: FromSuperValue<'a>>(
+    value: &SuperValue<'a>,
+) -> Result<(Bound<T>, Bound<T>), ConvertError> {
+    let fields = fields_of(group_of(value)?, &["min", "max"])?;
+    let bound = |found: Option<T>| found.map_or(Bound::Unbounded, Bound::Included);
+
+    Ok((
+        bound(from_item(fields, "min")?),
+        bound(from_item(fields, "max")?),
+    ))
+}
+
+#[cfg(feature = "regex")]
+fn pattern_of(value: &SuperValue<'_>) -> Result<Regex, ConvertError> {
+    Regex::new(<&str>::from_value(value)?).map_err(|_| ConvertError::expected("a valid pattern"))
+}
+
+impl SuperSchema for SuperValue<'_> {
+    fn rule() -> Rule {
+        Rule::new(Kind::Any)
+    }
+}
+
+impl SuperSchema for bool {
+    fn rule() -> Rule {
+        Rule::new(Kind::Bool)
+    }
+}
+
+macro_rules! integers {
+    ($($ty:ty),*) => {$(
+        impl SuperSchema for $ty {
+            fn rule() -> Rule {
+                Rule::new(Kind::Integer).range(<$ty>::MIN as f64..=<$ty>::MAX as f64)
+            }
+        }
+    )*};
+}
+
+integers!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl SuperSchema for f32 {
+    fn rule() -> Rule {
+        Rule::new(Kind::Float)
+    }
+}
+
+impl SuperSchema for f64 {
+    fn rule() -> Rule {
+        Rule::new(Kind::Float)
+    }
+}
+
+impl SuperSchema for str {
+    fn rule() -> Rule {
+        Rule::new(Kind::Name)
+    }
+}
+
+impl SuperSchema for String {
+    fn rule() -> Rule {
+        Rule::new(Kind::Name)
+    }
+}
+
+impl SuperSchema for Cow<'_, str> {
+    fn rule() -> Rule {
+        Rule::new(Kind::Name)
+    }
+}
+
+impl<T: SuperSchema + ?Sized> SuperSchema for &T {
+    fn rule() -> Rule {
+        T::rule()
+    }
+
+    fn required() -> bool {
+        T::required()
+    }
+}
+
+impl<T: SuperSchema> SuperSchema for Option<T> {
+    fn rule() -> Rule {
+        T::rule().or_nothing()
+    }
+
+    fn required() -> bool {
+        false
+    }
+}
+
+impl<T: SuperSchema> SuperSchema for Vec<T> {
+    fn rule() -> Rule {
+        Rule::list(T::rule())
+    }
+}
+
+impl From<Kind> for Rule {
+    fn from(kind: Kind) -> Self {
+        Self::new(kind)
+    }
+}
+
+impl From<Schema> for Rule {
+    fn from(schema: Schema) -> Self {
+        Self::group(schema)
+    }
+}
+
+impl Kind {
+    /// Checks if `value` is of this type
+    pub fn matches(self, value: &SuperValue<'_>) -> bool {
+        matches!(
+            (self, value),
+            (Self::Any, _)
+                | (Self::Bool, SuperValue::Bool(_))
+                | (Self::Integer, SuperValue::Integer(_))
+                | (Self::Float, SuperValue::Float(_) | SuperValue::Integer(_))
+                | (Self::Name, SuperValue::Name(_))
+                | (Self::Bytes, SuperValue::Bytes(_))
+                | (Self::List, SuperValue::List(_))
+                | (Self::Group, SuperValue::Group(_))
+        )
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Any => "anything",
+            Self::Bool => "a bool",
+            Self::Integer => "an integer",
+            Self::Float => "a float",
+            Self::Name => "a name",
+            Self::Bytes => "bytes",
+            Self::List => "a list",
+            Self::Group => "a group",
+        })
+    }
+}
+
+/// List or group whose end is being looked for by [Report::locate]
+struct Open {
+    /// Where its item or element started
+    start: usize,
+    /// Index of the next element, if it's a list
+    list: Option<usize>,
+}
+
+impl Report {
+    /// Checks if there aren't any violations
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Finds the span of every violation which doesn't have one yet in the
+    /// text which the config was parsed from, going over it only once
+    pub fn locate(&mut self, text: &str) {
+        if self
+            .violations
+            .iter()
+            .all(|violation| violation.span.is_some())
+        {
+            return;
+        }
+
+        let mut path = Path::new();
+        let mut stack: Vec<Open> = Vec::new();
+        let mut key = None;
+        // list or group which was just closed, kept back in case there's
+        // text after it which makes it a name
+        let mut closed: Option<(Path<'_>, Span)> = None;
+
+        for found in Tokens::new(text) {
+            let Ok((token, span)) = found else {
+                break;
+            };
+            if let Token::Trailing(_) = token {
+                if let Some((_, closed)) = &mut closed {
+                    closed.end = span.end;
+                }
+                continue;
+            }
+            if let Some((closed, span)) = closed.take() {
+                self.found(&closed, span);
+            }
+
+            match token {
+                Token::Key(raw) => key = Some((raw.to_key(), span.start)),
+                Token::Scalar(_) | Token::ListStart | Token::GroupStart => {
+                    let start = match stack.last_mut() {
+                        Some(Open {
+                            list: Some(next), ..
+                        }) => {
+                            path.push_index(*next);
+                            *next += 1;
+                            span.start
+                        }
+                        _ => {
+                            let (found, start) = key.take().unwrap_or_default();
+                            path.push_key(found);
+                            start
+                        }
+                    };
+
+                    match token {
+                        Token::Scalar(_) => {
+                            self.found(&path, Span { start, ..span });
+                            path.pop();
+                        }
+                        _ => stack.push(Open {
+                            start,
+                            list: (token == Token::ListStart).then_some(0),
+                        }),
+                    }
+                }
+                Token::ListEnd | Token::GroupEnd => {
+                    let Some(open) = stack.pop() else {
+                        break;
+                    };
+                    let span = Span {
+                        start: open.start,
+                        ..span
+                    };
+                    closed = Some((path.clone(), span));
+                    path.pop();
+                }
+                _ => (),
+            }
+        }
+
+        if let Some((closed, span)) = closed {
+            self.found(&closed, span);
+        }
+    }
+
+    /// Gives `span` to the violations which are at `path`, or which are
+    /// missing from the group at `path`
+    fn found(&mut self, path: &Path<'_>, span: Span) {
+        for violation in &mut self.violations {
+            let at = match violation.kind {
+                ViolationKind::Missing | ViolationKind::RequiredBy { .. } => {
+                    violation.path.segments.split_last().map(|(_, at)| at)
+                }
+                _ => Some(&violation.path.segments[..]),
+            };
+            if violation.span.is_none() && at == Some(&path.segments[..]) {
+                violation.span = Some(span);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (ind, violation) in self.violations.iter().enumerate() {
+            if ind != 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{}", violation)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl core::error::Error for Report {}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+
+        match &self.kind {
+            ViolationKind::Missing => f.write_str("missing item"),
+            ViolationKind::RequiredBy { when, is } => {
+                write!(f, "missing item, which is required when {} is {}", when, is)
+            }
+            ViolationKind::NotAtMost {
+                greater,
+                found,
+                limit,
+            } => write!(
+                f,
+                "expected at most {} which is {}, found {}",
+                greater, limit, found
+            ),
+            ViolationKind::Unknown { closest } => {
+                f.write_str("unknown item")?;
+                match closest {
+                    Some(closest) => write!(f, ", did you mean {}?", closest),
+                    None => Ok(()),
+                }
+            }
+            ViolationKind::Expected(kind) => write!(f, "expected {}", kind),
+            ViolationKind::NotOneOf { allowed, closest } => {
+                f.write_str("expected one of ")?;
+                for (ind, name) in allowed.iter().enumerate() {
+                    match ind {
+                        0 => (),
+                        _ if ind + 1 == allowed.len() => f.write_str(" or ")?,
+                        _ => f.write_str(", ")?,
+                    }
+                    f.write_str(name)?;
+                }
+                if let Some(closest) = closest {
+                    write!(f, ", did you mean {}?", closest)?;
+                }
+                Ok(())
+            }
+            ViolationKind::OutOfRange { found, range } => {
+                f.write_str("expected a number ")?;
+                write_range(f, range, "of ")?;
+                write!(f, ", found {}", found)
+            }
+            ViolationKind::WrongLength { found, range } => {
+                f.write_str("expected ")?;
+                write_range(f, range, "")?;
+                write!(f, " elements, found {}", found)
+            }
+            ViolationKind::NoMatch(pattern) => write!(f, "expected a name matching {}", pattern),
+            ViolationKind::Custom(message) => f.write_str(message),
+        }
+    }
+}
+
+fn bound<T: Copy, U>(bound: Bound<&T>, convert: impl FnOnce(T) -> U) -> Bound<U> {
+    match bound {
+        Bound::Included(found) => Bound::Included(convert(*found)),
+        Bound::Excluded(found) => Bound::Excluded(convert(*found)),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+/// Writes a range like `from 1 to 5`, with `of` before it if it's only
+/// bounded on one side
+fn write_range<T: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    range: &(Bound<T>, Bound<T>),
+    of: &str,
+) -> fmt::Result {
+    match range {
+        (Bound::Included(start), Bound::Included(end)) => write!(f, "from {} to {}", start, end),
+        (Bound::Included(start), Bound::Excluded(end)) => {
+            write!(f, "from {} to below {}", start, end)
+        }
+        (Bound::Included(start), Bound::Unbounded) => write!(f, "{}at least {}", of, start),
+        (Bound::Excluded(start), Bound::Unbounded) => write!(f, "{}more than {}", of, start),
+        (Bound::Unbounded, Bound::Included(end)) => write!(f, "{}at most {}", of, end),
+        (Bound::Unbounded, Bound::Excluded(end)) => write!(f, "{}less than {}", of, end),
+        (Bound::Excluded(start), Bound::Included(end)) => {
+            write!(f, "from above {} to {}", start, end)
+        }
+        (Bound::Excluded(start), Bound::Excluded(end)) => {
+            write!(f, "from above {} to below {}", start, end)
+        }
+        (Bound::Unbounded, Bound::Unbounded) => write!(f, "{}any size", of),
+    }
+}
+
+/// Parses a path of a [Relation], which is written in the code
+fn relative(path: &'static str) -> Path<'static> {
+    Path::parse(path).expect("paths of relations are valid")
+}
+
+/// Finds the name in `names` with the fewest edits away from `name`, if it's
+/// close enough that it was probably meant
+pub(crate) fn closest<'n, N: AsRef<str>>(name: &str, names: &'n [N]) -> Option<&'n N> {
+    names
+        .iter()
+        .map(|found| (distance(name, found.as_ref()), found))
+        .filter(|(edits, found)| *edits <= (found.as_ref().chars().count() / 3).max(1))
+        .min_by_key(|(edits, _)| *edits)
+        .map(|(_, found)| found)
+}
+
+/// Number of chars which have to be added, removed or replaced to turn
+/// `from` into `to`, where swapping two chars next to each other is one edit
+fn distance(from: &str, to: &str) -> usize {
+    let from: Vec<char> = from.chars().collect();
+    let to: Vec<char> = to.chars().collect();
+    // edits for the rows before last, before and the one being worked out
+    let mut before = vec![0; to.len() + 1];
+    let mut last: Vec<usize> = (0..=to.len()).collect();
+    let mut row = vec![0; to.len() + 1];
+
+    for ind in 1..=from.len() {
+        row[0] = ind;
+        for col in 1..=to.len() {
+            let replace = last[col - 1] + usize::from(from[ind - 1] != to[col - 1]);
+            row[col] = replace.min(last[col] + 1).min(row[col - 1] + 1);
+
+            if ind > 1 && col > 1 && from[ind - 1] == to[col - 2] && from[ind - 2] == to[col - 1] {
+                row[col] = row[col].min(before[col - 2] + 1);
+            }
+        }
+        core::mem::swap(&mut before, &mut last);
+        core::mem::swap(&mut last, &mut row);
+    }
+
+    last[to.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+    use alloc::string::{String, ToString};
+
+    fn schema() -> Schema {
+        Schema::new()
+            .required("name", Kind::Name)
+            .optional("ratio", Kind::Float)
+            .optional("ports", Rule::list(Kind::Integer))
+            .required(
+                "tls",
+                Schema::new()
+                    .required("enabled", Kind::Bool)
+                    .optional("extra", Kind::Any),
+            )
+    }
+
+    fn violations(text: &str) -> Vec<(String, ViolationKind)> {
+        schema()
+            .validate(&SuperConf::parse(text).unwrap())
+            .violations
+            .into_iter()
+            .map(|violation| (violation.path.to_string(), violation.kind))
+            .collect()
+    }
+
+    #[test]
+    fn valid() {
+        assert_eq!(
+            violations("name a\nratio 1\nports [1, 2]\ntls {enabled true, extra}\nother 1"),
+            []
+        );
+        assert_eq!(violations("name a\ntls {enabled false}\nname 1"), []);
+    }
+
+    #[test]
+    fn every_violation() {
+        assert_eq!(
+            violations("ratio x\nports [1, a, 3, b]\ntls {}"),
+            [
+                ("name".into(), ViolationKind::Missing),
+                ("ratio".into(), ViolationKind::Expected(Kind::Float)),
+                ("ports[1]".into(), ViolationKind::Expected(Kind::Integer)),
+                ("ports[3]".into(), ViolationKind::Expected(Kind::Integer)),
+                ("tls.enabled".into(), ViolationKind::Missing),
+            ]
+        );
+        assert_eq!(
+            violations("name a\ntls [enabled]"),
+            [("tls".into(), ViolationKind::Expected(Kind::Group))]
+        );
+    }
+
+    #[test]
+    fn spans() {
+        const TEXT: &str =
+            "ratio [1] x # c\nports [\n  1,\n  a]\ntls {extra {b [1] y}}\ntls {enabled 1}";
+        let report = schema().validate_text(TEXT).unwrap();
+        let spans: Vec<(String, &str)> = report
+            .violations
+            .iter()
+            .map(|violation| {
+                let span = violation.span.unwrap_or_default();
+                (violation.path.to_string(), &TEXT[span.start..span.end])
+            })
+            .collect();
+
+        assert_eq!(
+            spans,
+            [
+                ("name".into(), ""),
+                ("ratio".into(), "ratio [1] x"),
+                ("ports[1]".into(), "a"),
+                ("tls.enabled".into(), "tls {extra {b [1] y}}"),
+            ]
+        );
+        assert_eq!(schema().validate_text("a ["), Err(SuperError::Unbalanced));
+    }
+
+    #[test]
+    fn defaults() {
+        let schema = Schema::new()
+            .defaulted("level", Kind::Name, "info")
+            .required("db", Schema::new().defaulted("pool", Kind::Integer, 4))
+            .optional(
+                "hosts",
+                Rule::list(Schema::new().defaulted("port", Kind::Integer, 80)),
+            )
+            .optional("tls", Schema::new().optional("cert", Kind::Name))
+            .optional("cache", Schema::new().defaulted("size", Kind::Integer, 64));
+        let mut conf = SuperConf::parse("db {}\nhosts [{port 81}, {}, 1]\nlevel warn").unwrap();
+        schema.apply_defaults(&mut conf);
+
+        assert_eq!(
+            conf,
+            SuperConf::parse(
+                "db {pool 4}\nhosts [{port 81}, {port 80}, 1]\nlevel warn\ncache {size 64}"
+            )
+            .unwrap()
+        );
+
+        let mut conf = SuperConf::parse("").unwrap();
+        schema.apply_defaults(&mut conf);
+        assert_eq!(
+            conf,
+            SuperConf::parse("level info\ncache {size 64}").unwrap()
+        );
+        assert!(!schema.validate(&conf).is_valid());
+    }
+
+    #[test]
+    fn one_of() {
+        let schema = Schema::new().optional("level", Rule::one_of(["debug", "info", "warn"]));
+        let report = |text| schema.validate(&SuperConf::parse(text).unwrap()).violations;
+
+        assert_eq!(report("level info"), []);
+        assert_eq!(
+            report("level inf0")[0].kind,
+            ViolationKind::NotOneOf {
+                allowed: vec!["debug".into(), "info".into(), "warn".into()],
+                closest: Some("info".into())
+            }
+        );
+        assert_eq!(
+            report("level loud")[0].to_string(),
+            "level: expected one of debug, info or warn"
+        );
+        assert_eq!(
+            report("level 1")[0].kind,
+            ViolationKind::Expected(Kind::Name)
+        );
+    }
+
+    #[test]
+    fn ranges() {
+        let schema = Schema::new()
+            .optional("ratio", Rule::new(Kind::Float).range(0.0..1.0))
+            .optional("count", Rule::new(Kind::Integer).range(..=10u8))
+            .optional("tags", Rule::list(Kind::Name).len(..2));
+        let report = |text| {
+            schema
+                .validate(&SuperConf::parse(text).unwrap())
+                .to_string()
+        };
+
+        assert_eq!(report("ratio 0\ncount 10\ntags [a]"), "");
+        assert_eq!(
+            report("ratio 1\ncount 11\ntags [a, b]"),
+            "ratio: expected a number from 0 to below 1, found 1\n\
+             count: expected a number of at most 10, found 11\n\
+             tags: expected less than 2 elements, found 2"
+        );
+        assert!(!schema
+            .validate(&SuperConf::parse("ratio .nan").unwrap())
+            .is_valid());
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn patterns() {
+        let version = Regex::new(r"^v\d+\.\d+\.\d+$").unwrap();
+        let schema = Schema::new().optional(
+            "versions",
+            Rule::list(Rule::one_of(["latest", "v1.0.0"]).pattern(version)),
+        );
+        let kinds = |text| {
+            schema
+                .validate(&SuperConf::parse(text).unwrap())
+                .violations
+                .into_iter()
+                .map(|violation| violation.kind)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(kinds("versions [v1.0.0]"), []);
+        assert_eq!(
+            kinds("versions [latest, v1.0, 1]"),
+            [
+                ViolationKind::NoMatch(r"^v\d+\.\d+\.\d+$".into()),
+                ViolationKind::NotOneOf {
+                    allowed: vec!["latest".into(), "v1.0.0".into()],
+                    closest: Some("v1.0.0".into())
+                },
+                ViolationKind::Expected(Kind::Name),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        const TEXT: &str = "name a\nnmae b\ntls {enabled true, enabeld}\nports [[], 1]\nzzz";
+        let report = schema().deny_unknown().validate_text(TEXT).unwrap();
+        let found: Vec<(String, &str)> = report
+            .violations
+            .iter()
+            .map(|violation| {
+                let span = violation.span.unwrap();
+                (violation.to_string(), &TEXT[span.start..span.end])
+            })
+            .collect();
+
+        assert_eq!(
+            found,
+            [
+                ("ports[0]: expected an integer".into(), "[]"),
+                (
+                    "tls.enabeld: unknown item, did you mean enabled?".into(),
+                    "enabeld"
+                ),
+                ("nmae: unknown item, did you mean name?".into(), "nmae b"),
+                ("zzz: unknown item".into(), "zzz"),
+            ]
+        );
+        assert!(schema()
+            .validate_text(TEXT)
+            .unwrap()
+            .violations
+            .iter()
+            .all(|violation| !matches!(violation.kind, ViolationKind::Unknown { .. })));
+
+        // groups added after denying are denied too
+        let tls = || Schema::new().required("enabled", Kind::Bool);
+        for schema in [
+            Schema::new().deny_unknown().required("tls", tls()),
+            Schema::new().required("tls", tls()).deny_unknown(),
+        ] {
+            assert_eq!(
+                schema
+                    .validate_text("tls {enabled true, enabeld}\nzzz")
+                    .unwrap()
+                    .to_string(),
+                "tls.enabeld: unknown item, did you mean enabled?\nzzz: unknown item"
+            );
+        }
+    }
+
+    #[test]
+    fn relations() {
+        fn distinct(items: &[SuperItem]) -> Result<(), String> {
+            match items.iter().filter(|item| item.key == "a").count() {
+                0 | 1 => Ok(()),
+                _ => Err("a is repeated".into()),
+            }
+        }
+        let range = Schema::new().at_most("min", "max");
+        let schema = Schema::new()
+            .optional("ranges", Rule::list(range))
+            .optional("mode", Kind::Name)
+            .required_if("port", "mode", "server")
+            .required_if("tls.cert", "tls.enabled", true)
+            .relation(Relation::Custom(distinct));
+        let report = |text| schema.validate_text(text).unwrap();
+
+        assert!(report("mode client\nranges [{min 1, max 1}, {min x, max 0}, {}]").is_valid());
+        let found = report("ranges [{min 2.5, max 2}]\ntls {enabled true}\nmode server\na\na");
+        assert_eq!(
+            found.to_string(),
+            "ranges[0].min: expected at most ranges[0].max which is 2, found 2.5\n\
+             port: missing item, which is required when mode is server\n\
+             tls.cert: missing item, which is required when tls.enabled is true\n\
+             a is repeated"
+        );
+        assert_eq!(found.violations[0].span.map(|span| span.start), Some(9));
+        assert_eq!(found.violations[2].span.map(|span| span.start), Some(26));
+        assert_eq!(found.violations[3].span, None);
+
+        let found = schema.validate(&SuperConf::parse("mode 1\nranges [{min}]").unwrap());
+        assert_eq!(
+            found.violations[0].kind,
+            ViolationKind::Expected(Kind::Name)
+        );
+        assert_eq!(found.violations.len(), 1);
+    }
+
+    #[test]
+    fn validators() {
+        fn even(value: &SuperValue) -> Result<(), String> {
+            match value {
+                SuperValue::Integer(integer) if integer % 2 != 0 => Err("must be even".into()),
+                _ => Ok(()),
+            }
+        }
+        fn small(value: &SuperValue) -> Result<(), String> {
+            match value {
+                SuperValue::Integer(integer) if *integer > 10 => Err("must be small".into()),
+                _ => Ok(()),
+            }
+        }
+
+        let schema = Schema::new().optional(
+            "sizes",
+            Rule::list(
+                Rule::new(Kind::Integer)
+                    .range(0..)
+                    .validator(even)
+                    .validator(small),
+            ),
+        );
+        let report = schema.validate(&SuperConf::parse("sizes [2, 3, 13, -1, 12]").unwrap());
+
+        assert_eq!(
+            report.to_string(),
+            "sizes[1]: must be even\n\
+             sizes[2]: must be even\n\
+             sizes[2]: must be small\n\
+             sizes[3]: expected a number of at least 0, found -1\n\
+             sizes[4]: must be small"
+        );
+    }
+
+    #[test]
+    fn nothing() {
+        let rule = <Option<Vec<u8>>>::rule();
+        let schema = Schema::new().optional("a", rule);
+        let report = |text| {
+            schema
+                .validate(&SuperConf::parse(text).unwrap())
+                .to_string()
+        };
+
+        assert!(!<Option<u8>>::required());
+        assert_eq!(report("a"), "");
+        assert_eq!(report("a [1, 2]"), "");
+        assert_eq!(
+            report("a [256]"),
+            "a[0]: expected a number from 0 to 255, found 256"
+        );
+        assert_eq!(report("a 1"), "a: expected a list");
+        assert_eq!(Schema::of::<u8>().entries.len(), 0);
+    }
+
+    #[test]
+    fn from_conf() {
+        let conf = SuperConf::parse(
+            "items {
+                name {kind name}
+                ratio {kind float, optional}
+                ports {optional, elements {kind integer, range {min 0, max 65535}}}
+                mode {one_of [fast, slow], default fast}
+                tags {or_nothing, len {max 2}}
+                tls {deny_unknown, items {enabled {kind bool}}}
+            }",
+        )
+        .unwrap();
+        let schema = Schema::from_conf(&conf).unwrap();
+        let report = |text| {
+            schema
+                .validate(&SuperConf::parse(text).unwrap())
+                .to_string()
+        };
+
+        assert_eq!(
+            report("name a\nratio 1\nports [1]\ntags\ntls {enabled true}\nother 1"),
+            ""
+        );
+        assert_eq!(
+            report("ratio x\nports [1, 65536]\nmode medium\ntags [a, b, c]\ntls {enabled 1, x}"),
+            "name: missing item\n\
+             ratio: expected a float\n\
+             ports[1]: expected a number from 0 to 65535, found 65536\n\
+             mode: expected one of fast or slow\n\
+             tags: expected at most 2 elements, found 3\n\
+             tls.enabled: expected a bool\n\
+             tls.x: unknown item"
+        );
+        assert_eq!(schema.get("mode").map(|entry| entry.required), Some(false));
+        assert_eq!(
+            schema.get("mode").and_then(|entry| entry.default.clone()),
+            Some(SuperValue::Name("fast".into()))
+        );
+        assert_eq!(
+            schema.get("tls").map(|entry| entry.rule.kind),
+            Some(Kind::Group)
+        );
+        assert!(!schema.deny_unknown);
+        assert!(
+            Schema::from_conf(&SuperConf::parse("deny_unknown").unwrap())
+                .unwrap()
+                .deny_unknown
+        );
+
+        for (text, err) in [
+            ("itmes {}", "itmes: expected a known field"),
+            ("items [a]", "items: expected a group of fields"),
+            (
+                "items {a {kind text}}",
+                "items.a.kind: expected a kind of value",
+            ),
+            (
+                "items {a {optinal}}",
+                "items.a.optinal: expected a known field",
+            ),
+            (
+                "items {a {elements {default 1}}}",
+                "items.a.elements.default: expected a known field",
+            ),
+            (
+                "items {a {range {min x}}}",
+                "items.a.range.min: expected a number",
+            ),
+            ("deny_unknown 1", "deny_unknown: expected a bool"),
+        ] {
+            let conf = SuperConf::parse(text).unwrap();
+            assert_eq!(
+                Schema::from_conf(&conf)
+                    .map(|_| ())
+                    .map_err(|err| err.to_string()),
+                Err(err.into()),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn distances() {
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("ture", "true"), 1);
+        assert_eq!(distance("abc", "ca"), 3);
+        assert_eq!(distance("café", "cafe"), 1);
+        assert_eq!(closest("ture", &["true", "false"]), Some(&"true"));
+        assert_eq!(closest("x", &["true", "false"]), None);
+    }
+
+    #[test]
+    fn display() {
+        let violation = Violation {
+            path: Path::parse("tls.enabled").unwrap(),
+            span: None,
+            kind: ViolationKind::Expected(Kind::Bool),
+        };
+
+        assert_eq!(violation.to_string(), "tls.enabled: expected a bool");
+        assert_eq!(Report::default().to_string(), "");
+        assert_eq!(schema().get("tls").map(|entry| entry.required), Some(true));
+    }
+}
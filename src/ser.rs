@@ -0,0 +1,1286 @@
+//! Encoding of any serde type into configs, behind the `serde` feature
+//!
+//! This is the opposite of the [de](crate::de) module, so structs and maps
+//! become groups, sequences and tuples become lists and enum variants with
+//! data become a group with a single item named after the variant. Empty
+//! strings become nothing, as they can't be written as a name inside lists.
+
+use super::emit::Emitter;
+use super::{Emit, EmitOptions, Segment, SerdeError, SuperConf, SuperItem, SuperValue};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use core::fmt::{self, Write};
+use serde::ser::{self, Impossible, Serialize};
+
+/// Encodes `value` into superconf text, which must be a struct or map
+pub fn to_string<T: ?Sized + Serialize>(value: &T) -> Result<String, SerdeError> {
+    to_string_with(value, &EmitOptions::default())
+}
+
+/// Encodes `value` into superconf text like [to_string], using custom options
+pub fn to_string_with<T: ?Sized + Serialize>(
+    value: &T,
+    options: &EmitOptions,
+) -> Result<String, SerdeError> {
+    Ok(to_conf(value)?.emit_with(options))
+}
+
+/// Encodes `value` straight into `writer` as superconf text using [Serializer],
+/// which must be a struct or map
+pub fn to_writer<W: Write, T: ?Sized + Serialize>(
+    writer: &mut W,
+    value: &T,
+) -> Result<(), SerdeError> {
+    value.serialize(&mut Serializer::new(writer, &EmitOptions::default()))
+}
+
+/// Encodes `value` into a config, which must be a struct or map
+pub fn to_conf<T: ?Sized + Serialize>(value: &T) -> Result<SuperConf<'static>, SerdeError> {
+    match to_value(value)? {
+        SuperValue::Group(items) => Ok(SuperConf {
+            items: (*items).into_iter().collect(),
+        }),
+        _ => Err(ser::Error::custom(
+            "only structs and maps can be at the top level",
+        )),
+    }
+}
+
+/// Encodes `value` into a single value
+pub fn to_value<T: ?Sized + Serialize>(value: &T) -> Result<SuperValue<'static>, SerdeError> {
+    value.serialize(ValueSerializer)
+}
+
+impl ser::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        <Self as serde::de::Error>::custom(msg)
+    }
+}
+
+fn variant(name: &'static str, value: SuperValue<'static>) -> SuperValue<'static> {
+    SuperValue::group(vec![SuperItem {
+        key: Cow::Borrowed(name),
+        value,
+        meta: None,
+    }])
+}
+
+fn integer<T: TryInto<i64> + fmt::Display + Copy>(
+    num: T,
+) -> Result<SuperValue<'static>, SerdeError> {
+    num.try_into()
+        .map(SuperValue::Integer)
+        .map_err(|_| ser::Error::custom(format_args!("integer `{}` is too large", num)))
+}
+
+/// Serializer making [SuperValue]s, used by [to_value]
+struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = SuperValue<'static>;
+    type Error = SerdeError;
+    type SerializeSeq = List;
+    type SerializeTuple = List;
+    type SerializeTupleStruct = List;
+    type SerializeTupleVariant = List;
+    type SerializeMap = Group;
+    type SerializeStruct = Group;
+    type SerializeStructVariant = Group;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, SerdeError> {
+        Ok(SuperValue::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, SerdeError> {
+        integer(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, SerdeError> {
+        integer(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, SerdeError> {
+        integer(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, SerdeError> {
+        integer(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, SerdeError> {
+        integer(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, SerdeError> {
+        integer(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, SerdeError> {
+        integer(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, SerdeError> {
+        integer(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, SerdeError> {
+        integer(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, SerdeError> {
+        integer(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, SerdeError> {
+        Ok(SuperValue::Float(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, SerdeError> {
+        Ok(SuperValue::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, SerdeError> {
+        Ok(SuperValue::Name(Cow::Owned(v.to_string())))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, SerdeError> {
+        match v.is_empty() {
+            true => Ok(SuperValue::Nothing),
+            false => Ok(SuperValue::Name(Cow::Owned(v.into()))),
+        }
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, SerdeError> {
+        Ok(SuperValue::List(Box::new(
+            v.iter()
+                .map(|byte| SuperValue::Integer((*byte).into()))
+                .collect(),
+        )))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, SerdeError> {
+        Ok(SuperValue::Nothing)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, SerdeError> {
+        Ok(SuperValue::Nothing)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, SerdeError> {
+        Ok(SuperValue::Nothing)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, SerdeError> {
+        Ok(SuperValue::Name(Cow::Borrowed(variant)))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, SerdeError> {
+        let value = value
+            .serialize(self)
+            .map_err(|err| err.within(Segment::Key(Cow::Borrowed(name))))?;
+
+        Ok(variant(name, value))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<List, SerdeError> {
+        Ok(List {
+            values: Vec::with_capacity(len.unwrap_or(0)),
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<List, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<List, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<List, SerdeError> {
+        Ok(List {
+            values: Vec::with_capacity(len),
+            variant: Some(variant),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Group, SerdeError> {
+        Ok(Group {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+            variant: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Group, SerdeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Group, SerdeError> {
+        Ok(Group {
+            items: Vec::with_capacity(len),
+            key: None,
+            variant: Some(variant),
+        })
+    }
+}
+
+/// Builder for sequences, tuples and tuple variants
+struct List {
+    values: Vec<SuperValue<'static>>,
+    variant: Option<&'static str>,
+}
+
+impl List {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let index = self.values.len();
+        let value = value
+            .serialize(ValueSerializer)
+            .map_err(|err| err.within(Segment::Index(index)))?;
+
+        self.values.push(value);
+        Ok(())
+    }
+
+    fn finish(self) -> SuperValue<'static> {
+        let list = SuperValue::list(self.values);
+
+        match self.variant {
+            Some(name) => variant(name, list),
+            None => list,
+        }
+    }
+}
+
+impl ser::SerializeSeq for List {
+    type Ok = SuperValue<'static>;
+    type Error = SerdeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for List {
+    type Ok = SuperValue<'static>;
+    type Error = SerdeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for List {
+    type Ok = SuperValue<'static>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleVariant for List {
+    type Ok = SuperValue<'static>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+/// Builder for maps, structs and struct variants
+struct Group {
+    items: Vec<SuperItem<'static>>,
+    key: Option<Cow<'static, str>>,
+    variant: Option<&'static str>,
+}
+
+impl Group {
+    fn push<T: ?Sized + Serialize>(
+        &mut self,
+        key: Cow<'static, str>,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        let value = value
+            .serialize(ValueSerializer)
+            .map_err(|err| err.within(Segment::Key(key.clone())))?;
+
+        self.items.push(SuperItem {
+            key,
+            value,
+            meta: None,
+        });
+        Ok(())
+    }
+
+    fn finish(self) -> SuperValue<'static> {
+        let group = SuperValue::group(self.items);
+
+        match self.variant {
+            Some(name) => variant(name, group),
+            None => group,
+        }
+    }
+}
+
+impl ser::SerializeMap for Group {
+    type Ok = SuperValue<'static>;
+    type Error = SerdeError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), SerdeError> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| <SerdeError as ser::Error>::custom("value given before key"))?;
+
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStruct for Group {
+    type Ok = SuperValue<'static>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.push(Cow::Borrowed(key), value)
+    }
+
+    fn end(self) -> Result<Self::Ok, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStructVariant for Group {
+    type Ok = SuperValue<'static>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.push(Cow::Borrowed(key), value)
+    }
+
+    fn end(self) -> Result<Self::Ok, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+/// Serializer which writes superconf text straight into a [Write] as it goes,
+/// so other formats can be converted with `serde_transcode` without building
+/// a tree in between
+///
+/// Everything nested is written inline and in the order it's given, so out of
+/// the [EmitOptions] only the spacing and float formatting apply. Going the
+/// other way doesn't need anything special, as `&SuperConf` is a deserializer
+/// itself.
+pub struct Serializer<'w, W: Write> {
+    emitter: Emitter<'w, W>,
+    depth: usize,
+    /// If there's a key waiting for the space between it and its value, which
+    /// isn't written if the value is nothing
+    space: bool,
+    /// If the last value written was nothing
+    nothing: bool,
+}
+
+impl<'w, W: Write> Serializer<'w, W> {
+    /// Creates a serializer writing into `out`
+    pub fn new(out: &'w mut W, options: &'w EmitOptions) -> Self {
+        Self {
+            emitter: Emitter::new(out, options),
+            depth: 0,
+            space: false,
+            nothing: false,
+        }
+    }
+
+    /// Gets ready to write a value which isn't nothing
+    fn start(&mut self) -> Result<(), SerdeError> {
+        if self.depth == 0 {
+            return Err(top_level());
+        }
+
+        if core::mem::take(&mut self.space) {
+            self.write(" ")?;
+        }
+
+        self.nothing = false;
+        Ok(())
+    }
+
+    fn nothing(&mut self) -> Result<(), SerdeError> {
+        if self.depth == 0 {
+            return Err(top_level());
+        }
+
+        self.space = false;
+        self.nothing = true;
+        Ok(())
+    }
+
+    fn write(&mut self, text: &str) -> Result<(), SerdeError> {
+        self.emitter.write_str(text).map_err(formatter)
+    }
+
+    fn scalar(&mut self, value: SuperValue) -> Result<(), SerdeError> {
+        self.start()?;
+        self.emitter.value(&value).map_err(formatter)
+    }
+
+    fn list(&mut self, variant: Option<&'static str>) -> Result<Compound<'_, 'w, W>, SerdeError> {
+        self.open_variant(variant)?;
+        self.start()?;
+        self.write("[")?;
+        self.depth += 1;
+
+        Ok(Compound {
+            ser: self,
+            first: true,
+            top: false,
+            variant: variant.is_some(),
+        })
+    }
+
+    fn group(&mut self, variant: Option<&'static str>) -> Result<Compound<'_, 'w, W>, SerdeError> {
+        let top = self.depth == 0 && variant.is_none();
+
+        if !top {
+            self.open_variant(variant)?;
+            self.start()?;
+            self.write("{")?;
+        }
+
+        self.depth += 1;
+        Ok(Compound {
+            ser: self,
+            first: true,
+            top,
+            variant: variant.is_some(),
+        })
+    }
+
+    /// Starts the group with a single item which enum variants are written as
+    fn open_variant(&mut self, variant: Option<&'static str>) -> Result<(), SerdeError> {
+        if let Some(variant) = variant {
+            self.start()?;
+            self.write("{")?;
+            self.emitter.key(variant).map_err(formatter)?;
+            self.space = true;
+        }
+
+        Ok(())
+    }
+}
+
+fn top_level() -> SerdeError {
+    ser::Error::custom("only structs and maps can be at the top level")
+}
+
+fn formatter(_: fmt::Error) -> SerdeError {
+    ser::Error::custom("formatter error")
+}
+
+impl<'s, 'w, W: Write> ser::Serializer for &'s mut Serializer<'w, W> {
+    type Ok = ();
+    type Error = SerdeError;
+    type SerializeSeq = Compound<'s, 'w, W>;
+    type SerializeTuple = Compound<'s, 'w, W>;
+    type SerializeTupleStruct = Compound<'s, 'w, W>;
+    type SerializeTupleVariant = Compound<'s, 'w, W>;
+    type SerializeMap = Compound<'s, 'w, W>;
+    type SerializeStruct = Compound<'s, 'w, W>;
+    type SerializeStructVariant = Compound<'s, 'w, W>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), SerdeError> {
+        self.scalar(SuperValue::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), SerdeError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), SerdeError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), SerdeError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), SerdeError> {
+        self.scalar(SuperValue::Integer(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), SerdeError> {
+        match integer(v)? {
+            SuperValue::Integer(v) => self.serialize_i64(v),
+            _ => unreachable!(),
+        }
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), SerdeError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), SerdeError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), SerdeError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), SerdeError> {
+        self.serialize_i128(v.into())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), SerdeError> {
+        match integer(v)? {
+            SuperValue::Integer(v) => self.serialize_i64(v),
+            _ => unreachable!(),
+        }
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), SerdeError> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), SerdeError> {
+        self.scalar(SuperValue::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), SerdeError> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), SerdeError> {
+        match v.is_empty() {
+            true => self.nothing(),
+            false => self.scalar(SuperValue::Name(Cow::Borrowed(v))),
+        }
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), SerdeError> {
+        let mut list = self.list(None)?;
+        for byte in v {
+            ser::SerializeSeq::serialize_element(&mut list, byte)?;
+        }
+        ser::SerializeSeq::end(list)
+    }
+
+    fn serialize_none(self) -> Result<(), SerdeError> {
+        self.nothing()
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SerdeError> {
+        self.nothing()
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), SerdeError> {
+        self.nothing()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), SerdeError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.open_variant(Some(variant))?;
+        self.depth += 1;
+        value
+            .serialize(&mut *self)
+            .map_err(|err| err.within(Segment::Key(Cow::Borrowed(variant))))?;
+        self.depth -= 1;
+        self.nothing = false;
+        self.write("}")
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerdeError> {
+        self.list(None)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerdeError> {
+        self.list(None)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, SerdeError> {
+        self.list(None)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerdeError> {
+        self.list(Some(variant))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerdeError> {
+        self.group(None)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, SerdeError> {
+        self.group(None)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SerdeError> {
+        self.group(Some(variant))
+    }
+}
+
+/// Writer for the contents of a list or group, used by [Serializer]
+pub struct Compound<'s, 'w, W: Write> {
+    ser: &'s mut Serializer<'w, W>,
+    first: bool,
+    /// If this is the top level, which has one item per line and no brackets
+    top: bool,
+    /// If this is inside of the group which enum variants are written as
+    variant: bool,
+}
+
+impl<W: Write> Compound<'_, '_, W> {
+    /// Writes what goes between the opening bracket or the last element and
+    /// the next one
+    fn separate(&mut self) -> Result<(), SerdeError> {
+        let first = core::mem::replace(&mut self.first, false);
+
+        match (self.top, first) {
+            (true, _) => Ok(()),
+            (false, true) => self.ser.emitter.pad(false).map_err(formatter),
+            (false, false) => self.ser.emitter.comma().map_err(formatter),
+        }
+    }
+
+    fn element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.separate()?;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn key(&mut self, key: &str) -> Result<(), SerdeError> {
+        self.separate()?;
+        self.ser.emitter.key(key).map_err(formatter)?;
+        self.ser.space = true;
+        Ok(())
+    }
+
+    fn value<T: ?Sized + Serialize>(
+        &mut self,
+        key: Cow<'static, str>,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        value
+            .serialize(&mut *self.ser)
+            .map_err(|err| err.within(Segment::Key(key)))?;
+
+        match self.top {
+            true => self.ser.write("\n"),
+            false => Ok(()),
+        }
+    }
+
+    fn field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.key(key)?;
+        self.value(Cow::Borrowed(key), value)
+    }
+
+    fn end_list(self) -> Result<(), SerdeError> {
+        // a trailing blank element is skipped when parsing, so keep the final
+        // nothing by adding its comma explicitly
+        if !self.first && self.ser.nothing {
+            self.ser.write(",")?;
+        }
+
+        self.close("]")
+    }
+
+    fn end_group(self) -> Result<(), SerdeError> {
+        match self.top {
+            true => {
+                self.ser.depth -= 1;
+                Ok(())
+            }
+            false => self.close("}"),
+        }
+    }
+
+    fn close(self, bracket: &str) -> Result<(), SerdeError> {
+        self.ser.depth -= 1;
+        self.ser.emitter.pad(self.first).map_err(formatter)?;
+        self.ser.write(bracket)?;
+        self.ser.nothing = false;
+
+        match self.variant {
+            true => self.ser.write("}"),
+            false => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> ser::SerializeSeq for Compound<'_, '_, W> {
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        self.end_list()
+    }
+}
+
+impl<W: Write> ser::SerializeTuple for Compound<'_, '_, W> {
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        self.end_list()
+    }
+}
+
+impl<W: Write> ser::SerializeTupleStruct for Compound<'_, '_, W> {
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        self.end_list()
+    }
+}
+
+impl<W: Write> ser::SerializeTupleVariant for Compound<'_, '_, W> {
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        self.end_list()
+    }
+}
+
+impl<W: Write> ser::SerializeMap for Compound<'_, '_, W> {
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), SerdeError> {
+        let key = key.serialize(KeySerializer)?;
+        self.key(&key)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.value(Cow::Borrowed("?"), value)
+    }
+
+    fn serialize_entry<K: ?Sized + Serialize, V: ?Sized + Serialize>(
+        &mut self,
+        key: &K,
+        value: &V,
+    ) -> Result<(), SerdeError> {
+        let key = key.serialize(KeySerializer)?;
+        self.key(&key)?;
+        self.value(key, value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        self.end_group()
+    }
+}
+
+impl<W: Write> ser::SerializeStruct for Compound<'_, '_, W> {
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        self.end_group()
+    }
+}
+
+impl<W: Write> ser::SerializeStructVariant for Compound<'_, '_, W> {
+    type Ok = ();
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), SerdeError> {
+        self.end_group()
+    }
+}
+
+/// Serializer for the keys of maps, which can be strings or anything that
+/// displays as a single word such as integers and unit variants
+struct KeySerializer;
+
+impl KeySerializer {
+    fn display(v: impl fmt::Display) -> Result<Cow<'static, str>, SerdeError> {
+        Ok(Cow::Owned(v.to_string()))
+    }
+
+    fn invalid() -> SerdeError {
+        ser::Error::custom("map keys must be strings, integers, bools or unit variants")
+    }
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = Cow<'static, str>;
+    type Error = SerdeError;
+    type SerializeSeq = Impossible<Self::Ok, SerdeError>;
+    type SerializeTuple = Impossible<Self::Ok, SerdeError>;
+    type SerializeTupleStruct = Impossible<Self::Ok, SerdeError>;
+    type SerializeTupleVariant = Impossible<Self::Ok, SerdeError>;
+    type SerializeMap = Impossible<Self::Ok, SerdeError>;
+    type SerializeStruct = Impossible<Self::Ok, SerdeError>;
+    type SerializeStructVariant = Impossible<Self::Ok, SerdeError>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, SerdeError> {
+        Self::display(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, SerdeError> {
+        Self::display(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, SerdeError> {
+        Self::display(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, SerdeError> {
+        Self::display(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, SerdeError> {
+        Self::display(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, SerdeError> {
+        Self::display(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, SerdeError> {
+        Self::display(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, SerdeError> {
+        Self::display(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, SerdeError> {
+        Self::display(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, SerdeError> {
+        Self::display(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, SerdeError> {
+        Self::display(v)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, SerdeError> {
+        Self::display(v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, SerdeError> {
+        Self::display(v)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<Self::Ok, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, SerdeError> {
+        Ok(Cow::Borrowed(variant))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SerdeError> {
+        Err(Self::invalid())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_str, Parse};
+    use alloc::collections::BTreeMap;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Settings {
+        title: String,
+        volume: u8,
+        version: String,
+        window: Window,
+        recent: Vec<String>,
+        theme: Option<String>,
+        shortcuts: BTreeMap<String, char>,
+        mode: Mode,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Window {
+        width: u32,
+        scale: f64,
+        fullscreen: bool,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Mode {
+        Light,
+        Custom { hue: i16 },
+        Pair(u8, u8),
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut shortcuts = BTreeMap::new();
+        shortcuts.insert("save file".into(), 's');
+        shortcuts.insert("quit".into(), 'q');
+
+        let settings = Settings {
+            title: "my [app], v2".into(),
+            volume: 80,
+            version: "1.0".into(),
+            window: Window {
+                width: 1280,
+                scale: 1.5,
+                fullscreen: false,
+            },
+            recent: vec!["a.txt".into(), "".into(), "true".into()],
+            theme: None,
+            shortcuts,
+            mode: Mode::Custom { hue: -20 },
+        };
+        let text = to_string(&settings).unwrap();
+
+        assert_eq!(from_str::<Settings>(&text).unwrap(), settings);
+    }
+
+    #[test]
+    fn layout() {
+        let window = Window {
+            width: 640,
+            scale: 2.0,
+            fullscreen: true,
+        };
+
+        assert_eq!(
+            to_string(&window).unwrap(),
+            "width 640\nscale 2.0\nfullscreen true\n"
+        );
+        assert_eq!(
+            to_value(&[Mode::Light, Mode::Pair(1, 2)]).unwrap(),
+            SuperValue::list(vec![
+                SuperValue::Name("Light".into()),
+                variant(
+                    "Pair",
+                    SuperValue::list(vec![SuperValue::Integer(1), SuperValue::Integer(2)])
+                )
+            ])
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(to_string(&1).is_err());
+        assert_eq!(
+            to_value(&vec![0, u64::MAX]).unwrap_err().to_string(),
+            "[1]: integer `18446744073709551615` is too large"
+        );
+    }
+
+    #[test]
+    fn streaming() {
+        let mut shortcuts = BTreeMap::new();
+        shortcuts.insert("save file".into(), 's');
+
+        let settings = Settings {
+            title: "[]".into(),
+            volume: 0,
+            version: "-1".into(),
+            window: Window {
+                width: 1,
+                scale: -0.5,
+                fullscreen: true,
+            },
+            recent: vec!["".into(), "b".into(), "".into()],
+            theme: Some("dark mode".into()),
+            shortcuts,
+            mode: Mode::Pair(3, 4),
+        };
+        let mut text = String::new();
+        to_writer(&mut text, &settings).unwrap();
+
+        assert_eq!(from_str::<Settings>(&text).unwrap(), settings);
+        assert_eq!(text.lines().count(), 8);
+
+        let options = EmitOptions {
+            pad_brackets: true,
+            ..EmitOptions::default()
+        };
+        let mut modes = BTreeMap::new();
+        modes.insert("mode", Mode::Custom { hue: 2 });
+        modes.insert("empty", Mode::Light);
+        let mut text = String::new();
+        modes
+            .serialize(&mut Serializer::new(&mut text, &options))
+            .unwrap();
+        assert_eq!(text, "empty Light\nmode {Custom { hue 2 }}\n");
+    }
+
+    #[test]
+    fn transcode() {
+        let json = r#"{"name": "server", "ports": [80, 443], "tls": {"enabled": true, "ratio": 0.5}, "proxy": null}"#;
+        let mut text = String::new();
+        serde_transcode::transcode(
+            &mut serde_json::Deserializer::from_str(json),
+            &mut Serializer::new(&mut text, &EmitOptions::default()),
+        )
+        .unwrap();
+        assert_eq!(
+            text,
+            "name server\nports [80, 443]\ntls {enabled true, ratio 0.5}\nproxy\n"
+        );
+
+        let conf = SuperConf::parse(&text).unwrap();
+        let mut back = Vec::new();
+        serde_transcode::transcode(&conf, &mut serde_json::Serializer::new(&mut back)).unwrap();
+        assert_eq!(
+            String::from_utf8(back).unwrap(),
+            r#"{"name":"server","ports":[80,443],"tls":{"enabled":true,"ratio":0.5},"proxy":null}"#
+        );
+
+        let mut text = String::new();
+        assert!(serde_transcode::transcode(
+            &mut serde_json::Deserializer::from_str("[1]"),
+            &mut Serializer::new(&mut text, &EmitOptions::default()),
+        )
+        .is_err());
+    }
+}
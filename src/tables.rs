@@ -0,0 +1,118 @@
+//! Conversions to and from [toml::Value], behind the `toml` feature
+//!
+//! Types map onto each other directly, so names are strings, lists are arrays
+//! and groups are tables. TOML doesn't have anything like nothing, so it
+//! becomes an empty string, which converts back into nothing again. Datetimes
+//! become names of their text, and groups with duplicate keys only keep the
+//! last.
+
+use super::{SuperConf, SuperItem, SuperValue};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use toml::{Table, Value};
+
+impl<'a> SuperValue<'a> {
+    /// Converts into a TOML value, see the [module](self) for how
+    pub fn to_toml(&self) -> Value {
+        match self {
+            Self::Nothing => Value::String(String::new()),
+            Self::Name(name) => Value::String(name.to_string()),
+            Self::Bool(boolean) => Value::Boolean(*boolean),
+            Self::Integer(integer) => Value::Integer(*integer),
+            Self::Float(float) => Value::Float(*float),
+            Self::Bytes(data) => Value::String(data.to_string()),
+            Self::List(values) => Value::Array(values.iter().map(Self::to_toml).collect()),
+            Self::Group(items) => Value::Table(items_to_toml(items)),
+        }
+    }
+
+    /// Converts from a TOML value, borrowing all strings from it
+    pub fn from_toml(value: &'a Value) -> Self {
+        match value {
+            Value::String(string) if string.is_empty() => Self::Nothing,
+            Value::String(string) => Self::Name(Cow::Borrowed(string)),
+            Value::Integer(integer) => Self::Integer(*integer),
+            Value::Float(float) => Self::Float(*float),
+            Value::Boolean(boolean) => Self::Bool(*boolean),
+            Value::Datetime(datetime) => Self::Name(Cow::Owned(datetime.to_string())),
+            Value::Array(values) => {
+                Self::List(Box::new(values.iter().map(Self::from_toml).collect()))
+            }
+            Value::Table(table) => Self::group(items_from_toml(table)),
+        }
+    }
+}
+
+impl<'a> SuperConf<'a> {
+    /// Converts into a TOML table of every item, see [SuperValue::to_toml]
+    pub fn to_toml(&self) -> Table {
+        items_to_toml(&self.items)
+    }
+
+    /// Converts from a TOML table, such as a whole parsed document
+    pub fn from_toml(table: &'a Table) -> Self {
+        Self {
+            items: items_from_toml(table),
+        }
+    }
+}
+
+fn items_to_toml(items: &[SuperItem]) -> Table {
+    items
+        .iter()
+        .map(|item| (item.key.to_string(), item.value.to_toml()))
+        .collect()
+}
+
+fn items_from_toml(table: &Table) -> Vec<SuperItem<'_>> {
+    table
+        .iter()
+        .map(|(key, value)| SuperItem {
+            key: Cow::Borrowed(key),
+            value: SuperValue::from_toml(value),
+            meta: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+
+    #[test]
+    fn round_trip() {
+        let conf = SuperConf::parse(
+            "name server\nports [80, 443]\ntls {enabled true, ratio 0.5}\nproxy\n",
+        )
+        .unwrap();
+        let table = conf.to_toml();
+
+        assert_eq!(
+            table,
+            "name = 'server'\nports = [80, 443]\nproxy = ''\ntls = { enabled = true, ratio = 0.5 }"
+                .parse::<Table>()
+                .unwrap()
+        );
+        assert_eq!(
+            SuperConf::from_toml(&table).get("tls.ratio"),
+            conf.get("tls.ratio")
+        );
+        assert_eq!(
+            SuperConf::from_toml(&table).get("proxy"),
+            Some(&SuperValue::Nothing)
+        );
+    }
+
+    #[test]
+    fn datetimes() {
+        let table: Table = "when = 1979-05-27T07:32:00Z".parse().unwrap();
+
+        assert_eq!(
+            SuperConf::from_toml(&table).get("when"),
+            Some(&SuperValue::Name("1979-05-27T07:32:00Z".into()))
+        );
+    }
+}
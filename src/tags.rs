@@ -0,0 +1,155 @@
+//! Type tags written before a value like `int:8080` or `str:0042`, which
+//! force the type of the value when the one it would be read as is wrong,
+//! such as ids with leading zeros which would otherwise become numbers
+
+use super::convert::ConvertError;
+use super::{Path, SuperConf, SuperItem, SuperValue};
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+
+impl SuperConf<'_> {
+    /// Replaces every tagged name with a value of the type its tag is for,
+    /// leaving names which aren't valid for their tag as they are
+    ///
+    /// Tags are `int:`, `float:` and `bool:`, which read the rest of the name
+    /// like [SuperValue::coerce_int] and the others do, `str:` which keeps
+    /// the rest of it as a name and `color:` which keeps a valid color as a
+    /// name starting with `#` for [SuperValue::as_rgba].
+    ///
+    /// ```
+    /// use superconf::{Parse, SuperConf, SuperValue};
+    ///
+    /// let mut conf = SuperConf::parse("port int:8080\nid str:0042\ndebug bool:yes\nname int:x").unwrap();
+    /// conf.apply_tags();
+    ///
+    /// assert_eq!(conf.get("port"), Some(&SuperValue::Integer(8080)));
+    /// assert_eq!(conf.get("id"), Some(&SuperValue::Name("0042".into())));
+    /// assert_eq!(conf.get("debug"), Some(&SuperValue::Bool(true)));
+    /// assert_eq!(conf.get("name"), Some(&SuperValue::Name("int:x".into())));
+    /// ```
+    pub fn apply_tags(&mut self) {
+        let _ = tag_items(&mut self.items, &mut Path::new(), false);
+    }
+
+    /// Strict counterpart to [SuperConf::apply_tags], giving an error at the
+    /// first name which isn't valid for its tag
+    pub fn apply_tags_strict(&mut self) -> Result<(), ConvertError> {
+        tag_items(&mut self.items, &mut Path::new(), true)
+    }
+}
+
+fn tag_items(
+    items: &mut [SuperItem<'_>],
+    path: &mut Path<'static>,
+    strict: bool,
+) -> Result<(), ConvertError> {
+    for item in items {
+        path.push_key(String::from(&*item.key));
+        tag_value(&mut item.value, path, strict)?;
+        path.pop();
+    }
+    Ok(())
+}
+
+fn tag_value(
+    value: &mut SuperValue<'_>,
+    path: &mut Path<'static>,
+    strict: bool,
+) -> Result<(), ConvertError> {
+    match value {
+        SuperValue::Name(name) => {
+            let Some((tag, rest)) = name.split_once(':') else {
+                return Ok(());
+            };
+            let color = super::color::rgba(rest)
+                .map(|_| SuperValue::Name(format!("#{}", rest.trim_start_matches('#')).into()))
+                .ok_or_else(|| ConvertError::expected("a color"));
+            // the text after the tag borrows from the value which is replaced,
+            // so it's gone by then
+            let tagged = {
+                let rest = SuperValue::Name(Cow::Borrowed(rest));
+                match tag {
+                    "int" => rest.coerce_int().map(SuperValue::Integer),
+                    "float" => rest.coerce_float().map(SuperValue::Float),
+                    "bool" => rest.coerce_bool().map(SuperValue::Bool),
+                    "str" => Ok(rest.into_owned()),
+                    "color" => color,
+                    _ => return Ok(()),
+                }
+            };
+
+            match tagged {
+                Ok(tagged) => *value = tagged,
+                Err(err) if strict => {
+                    return Err(ConvertError {
+                        path: path.clone(),
+                        ..err
+                    })
+                }
+                Err(_) => (),
+            }
+            Ok(())
+        }
+        SuperValue::Group(items) => tag_items(items, path, strict),
+        SuperValue::List(values) => {
+            for (index, value) in values.iter_mut().enumerate() {
+                path.push_index(index);
+                tag_value(value, path, strict)?;
+                path.pop();
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConvertErrorKind, Parse};
+
+    #[test]
+    fn tags() {
+        let mut conf = SuperConf::parse(
+            "a int:0042\nb [str:1, float:2, str:, bool:off]\nc {d int:1.0, e int:1.5, f url:x, g color:ABCDEF, h color:x}",
+        )
+        .unwrap();
+        conf.apply_tags();
+
+        let name = |name| Some(SuperValue::Name(Cow::Borrowed(name)));
+        assert_eq!(conf.get("a"), Some(&SuperValue::Integer(42)));
+        assert_eq!(
+            conf.get("b"),
+            Some(&SuperValue::list(alloc::vec![
+                name("1").unwrap(),
+                SuperValue::Float(2.0),
+                name("").unwrap(),
+                SuperValue::Bool(false),
+            ]))
+        );
+        assert_eq!(conf.get("c.d"), Some(&SuperValue::Integer(1)));
+        assert_eq!(conf.get("c.e").cloned(), name("int:1.5"));
+        assert_eq!(conf.get("c.f").cloned(), name("url:x"));
+        assert_eq!(conf.get("c.g").cloned(), name("#ABCDEF"));
+        assert_eq!(
+            conf.get("c.g").unwrap().as_rgba(),
+            Ok([0xab, 0xcd, 0xef, 0xff])
+        );
+        assert_eq!(conf.get("c.h").cloned(), name("color:x"));
+    }
+
+    #[test]
+    fn strict() {
+        let mut conf = SuperConf::parse("a str:x\nb [1, {c bool:maybe}]").unwrap();
+
+        assert_eq!(
+            conf.apply_tags_strict(),
+            Err(ConvertError {
+                path: Path::parse("b[1].c").unwrap().into_owned(),
+                kind: ConvertErrorKind::Expected("a bool"),
+            })
+        );
+        assert_eq!(conf.get("a"), Some(&SuperValue::Name("x".into())));
+    }
+}
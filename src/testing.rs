@@ -0,0 +1,101 @@
+//! Strategies for property testing with proptest, behind the `testing` feature
+//!
+//! Like the [Arbitrary](https://docs.rs/arbitrary) implementations, generated
+//! values never contain NaN floats or empty names and keys, so they always
+//! survive being emitted and parsed again unchanged.
+
+use super::{Emit, EmitOptions, SuperConf, SuperItem, SuperValue};
+use alloc::borrow::Cow;
+use alloc::string::String;
+use proptest::prelude::*;
+
+/// Strategy for any single value, with lists and groups nested a few levels
+pub fn value() -> impl Strategy<Value = SuperValue<'static>> {
+    let scalar = prop_oneof![
+        Just(SuperValue::Nothing),
+        text().prop_map(SuperValue::Name),
+        any::<bool>().prop_map(SuperValue::Bool),
+        any::<i64>().prop_map(SuperValue::Integer),
+        any::<f64>()
+            .prop_filter("NaN isn't equal to itself", |float| !float.is_nan())
+            .prop_map(SuperValue::Float),
+    ];
+
+    scalar.prop_recursive(4, 64, 6, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6).prop_map(SuperValue::list),
+            prop::collection::vec(item_with(inner), 0..6).prop_map(SuperValue::group),
+        ]
+    })
+}
+
+/// Strategy for an item with any key and value
+pub fn item() -> impl Strategy<Value = SuperItem<'static>> {
+    item_with(value())
+}
+
+/// Strategy for a whole config
+pub fn conf() -> impl Strategy<Value = SuperConf<'static>> {
+    prop::collection::vec(item(), 0..8).prop_map(|items| SuperConf { items })
+}
+
+/// Strategy for valid document text alongside the config it parses into,
+/// which is written out using a random mix of the [EmitOptions]
+pub fn document() -> impl Strategy<Value = (String, SuperConf<'static>)> {
+    (conf(), options()).prop_map(|(conf, options)| (conf.emit_with(&options), conf))
+}
+
+fn options() -> impl Strategy<Value = EmitOptions> {
+    (
+        0..8usize,
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        prop_oneof![Just(usize::MAX), 1..8usize],
+        prop::option::of(0..80usize),
+    )
+        .prop_map(
+            |(indent, expand_groups, space_after_comma, pad_brackets, list_wrap, max_width)| {
+                EmitOptions {
+                    indent,
+                    expand_groups,
+                    space_after_comma,
+                    pad_brackets,
+                    list_wrap,
+                    max_width,
+                    ..EmitOptions::default()
+                }
+            },
+        )
+}
+
+fn item_with(
+    value: impl Strategy<Value = SuperValue<'static>>,
+) -> impl Strategy<Value = SuperItem<'static>> {
+    (text(), value).prop_map(|(key, value)| SuperItem {
+        key,
+        value,
+        meta: None,
+    })
+}
+
+fn text() -> impl Strategy<Value = Cow<'static, str>> {
+    prop_oneof![
+        "[a-z_][a-z0-9_-]{0,8}",
+        any::<String>().prop_filter("empty text can't be written", |text| !text.is_empty()),
+    ]
+    .prop_map(Cow::Owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+
+    proptest! {
+        #[test]
+        fn documents_parse((text, conf) in document()) {
+            prop_assert_eq!(SuperConf::parse(&text), Ok(conf));
+        }
+    }
+}
@@ -0,0 +1,980 @@
+//! Splitting of text into [Token]s, which [Reader](crate::Reader) and the
+//! tree parser are built on, for tools such as formatters or syntax
+//! highlighters which need to know where everything was written
+//!
+//! Tokens are given in the order they were written and never overlap, with
+//! only whitespace which isn't a newline left out of them.
+
+use super::SuperError;
+#[cfg(feature = "alloc")]
+use super::{cursor::scalar, unescape, SuperValue};
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
+use core::fmt;
+use core::str::Chars;
+
+/// Deepest that brackets can be nested, counting brackets inside of names
+/// too
+pub const MAX_DEPTH: usize = 128;
+
+/// Bytes which never end or change the meaning of the text they're in, which
+/// is any visible ascii apart from brackets, commas, escapes and comments
+const PLAIN: [bool; 256] = {
+    let mut plain = [false; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        plain[byte] = (byte as u8).is_ascii_graphic()
+            && !matches!(byte as u8, b'[' | b']' | b'{' | b'}' | b',' | b'\\' | b'#');
+        byte += 1;
+    }
+    plain
+};
+
+/// Piece of text given by [Tokens], where every [Token::Key] is followed by
+/// the tokens of exactly one value
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Token<'a> {
+    /// Key of an item at the top level or inside of a group
+    Key(Raw<'a>),
+    /// Value which isn't a list or group, which is empty for nothing
+    Scalar(Raw<'a>),
+    /// Text straight after the closing bracket of a list or group, like the
+    /// `x` in `[1] x`, which makes the whole of it a name instead
+    Trailing(Raw<'a>),
+    ListStart,
+    ListEnd,
+    GroupStart,
+    GroupEnd,
+    /// Comma between elements or items
+    Comma,
+    /// Newline which separates items or is just whitespace
+    Newline,
+    /// Comment from its `#` up to the end of its line
+    Comment(&'a str),
+}
+
+/// Byte range of the text which a [Token] was read from
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
+
+/// Text as it was written, still containing any escapes
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct Raw<'a> {
+    text: &'a str,
+    /// If there are any escapes, which is found out while the text is
+    /// scanned so that they don't have to be looked for again
+    escaped: bool,
+}
+
+impl fmt::Debug for Raw<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Raw").field(&self.text).finish()
+    }
+}
+
+impl<'a> Raw<'a> {
+    pub(crate) fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            escaped: text.contains('\\'),
+        }
+    }
+
+    pub fn as_str(self) -> &'a str {
+        self.text
+    }
+
+    /// Checks if there are any escapes, without looking through the text
+    pub fn is_escaped(self) -> bool {
+        self.escaped
+    }
+
+    /// Iterates over the text with its escapes removed, without allocating
+    pub fn chars(self) -> Unescaped<'a> {
+        Unescaped(self.text.chars())
+    }
+
+    /// Removes escapes, only allocating if there are any present
+    #[cfg(feature = "alloc")]
+    pub fn to_key(self) -> Cow<'a, str> {
+        match self.escaped {
+            true => unescape(self.text),
+            false => Cow::Borrowed(self.text),
+        }
+    }
+
+    /// Reads the text as a value, the same as [Parse](crate::Parse) would
+    #[cfg(feature = "alloc")]
+    pub fn to_value(self) -> SuperValue<'a> {
+        scalar(self)
+    }
+}
+
+/// Chars of [Raw] text with its escapes removed, see [Raw::chars]
+#[derive(Debug, Clone)]
+pub struct Unescaped<'a>(Chars<'a>);
+
+impl Iterator for Unescaped<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        match self.0.next()? {
+            '\\' => self.0.next(),
+            c => Some(c),
+        }
+    }
+}
+
+/// Splits text into [Token]s and their [Span]s as they're asked for, in a
+/// single pass without allocating
+///
+/// Brackets can only be nested [MAX_DEPTH] deep, past which
+/// [SuperError::TooDeep] is given. Any error ends the tokens.
+///
+/// ```
+/// use superconf::tokens::{Span, Token, Tokens};
+///
+/// let tokens: Vec<_> = Tokens::new("a [1, 2] # b")
+///     .map(|found| found.unwrap().0)
+///     .filter(|token| matches!(token, Token::Comma | Token::Comment(_)))
+///     .collect();
+/// assert_eq!(tokens, [Token::Comma, Token::Comment("# b")]);
+///
+/// let (_, span) = Tokens::new("a [1]").nth(2).unwrap().unwrap();
+/// assert_eq!(span, Span { start: 3, end: 4 });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tokens<'a> {
+    input: &'a str,
+    pos: usize,
+    /// Bit for each open bracket, set if it's a group
+    groups: u128,
+    depth: usize,
+    /// If items at the top level are separated by newlines, which they
+    /// aren't when the text is only one item or value
+    lines: bool,
+    state: State,
+}
+
+/// Where [Tokens] or a [Reader](crate::Reader) is up to, which can be kept
+/// while parsing is suspended and then handed back along with the same text
+///
+/// ```
+/// use superconf::{Event, Reader};
+///
+/// let text = "port 8080\nhosts [a, b]";
+/// let mut reader = Reader::new(text);
+/// reader.next();
+/// let snapshot = reader.snapshot();
+///
+/// let mut resumed = Reader::resume(text, snapshot).unwrap();
+/// assert!(matches!(resumed.next(), Some(Ok(Event::Scalar(_)))));
+/// assert_eq!(resumed.count(), reader.count() - 1);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Snapshot {
+    pos: usize,
+    groups: u128,
+    depth: usize,
+    lines: bool,
+    state: State,
+}
+
+/// Text found by [Tokens::scan_to], without any trivia on the end
+struct Scanned {
+    start: usize,
+    end: usize,
+    /// If it stopped at an unescaped space
+    space: bool,
+    /// If there's a backslash anywhere in it, apart from inside comments
+    escaped: bool,
+    /// Where the first comment inside of it starts, which is skipped over
+    /// without looking for backslashes
+    comment: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+enum State {
+    /// Before an item or the end of a group
+    Items,
+    /// Before the value of an item, which has `base` brackets open around it
+    Value {
+        base: usize,
+    },
+    /// Straight after a key which has nothing as its value
+    Nothing,
+    /// Before an element or the end of a list, where the element starts at
+    /// `start`
+    Element {
+        start: usize,
+    },
+    /// After an element, before a comma or the end of a list
+    AfterElement,
+    /// Straight after the closing bracket of a list or group
+    Closed,
+    Done,
+}
+
+impl<'a> Tokens<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            groups: 0,
+            depth: 0,
+            lines: true,
+            state: State::Items,
+        }
+    }
+
+    /// Tokens of text which is only one item
+    #[cfg(feature = "alloc")]
+    pub(crate) fn item(input: &'a str) -> Self {
+        Self {
+            lines: false,
+            ..Self::new(input)
+        }
+    }
+
+    /// Tokens of text which is only one value
+    #[cfg(feature = "alloc")]
+    pub(crate) fn value(input: &'a str) -> Self {
+        Self {
+            lines: false,
+            state: State::Value { base: 0 },
+            ..Self::new(input)
+        }
+    }
+
+    /// Picks up from a [Snapshot] taken for the same `input`, which is
+    /// nothing if it can't have been
+    pub fn resume(input: &'a str, snapshot: Snapshot) -> Option<Self> {
+        let valid = match snapshot.state {
+            State::Value { base } => base <= snapshot.depth,
+            State::Element { start } => start <= snapshot.pos && input.is_char_boundary(start),
+            _ => true,
+        };
+        if !valid || !input.is_char_boundary(snapshot.pos) || snapshot.depth > MAX_DEPTH {
+            return None;
+        }
+
+        Some(Self {
+            input,
+            pos: snapshot.pos,
+            groups: snapshot.groups,
+            depth: snapshot.depth,
+            lines: snapshot.lines,
+            state: snapshot.state,
+        })
+    }
+
+    /// Takes a [Snapshot] of where the tokens are up to, which is small
+    /// enough to be kept around or saved while parsing is suspended
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            pos: self.pos,
+            groups: self.groups,
+            depth: self.depth,
+            lines: self.lines,
+            state: self.state,
+        }
+    }
+
+    pub(crate) fn input(&self) -> &'a str {
+        self.input
+    }
+
+    /// Where in the text it's up to, which is where it stopped if it gave an
+    /// error
+    #[cfg(feature = "alloc")]
+    pub(crate) fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Number of brackets open, including any inside of names
+    #[cfg(feature = "alloc")]
+    pub(crate) fn depth(&self) -> usize {
+        self.depth
+    }
+
+    fn step(&mut self) -> Result<Option<(Token<'a>, Span)>, SuperError> {
+        match self.state {
+            State::Items => self.item_step(),
+            State::Value { base } => match self.trivia(self.seps(base), base) {
+                Some(found) => Ok(Some(found)),
+                None => self.value_step(base),
+            },
+            State::Nothing => {
+                self.state = State::Items;
+                Ok(Some(self.token(Token::Scalar(Raw::new("")), self.pos)))
+            }
+            State::Element { start } => match self.trivia(b",", self.depth) {
+                Some(found) => Ok(Some(found)),
+                None => self.element_step(start),
+            },
+            State::AfterElement => {
+                if let Some(found) = self.trivia(b",", self.depth) {
+                    return Ok(Some(found));
+                }
+                match self.peek() {
+                    Some(b',') => {
+                        self.pos += 1;
+                        self.state = State::Element { start: self.pos };
+                        Ok(Some(self.token(Token::Comma, self.pos - 1)))
+                    }
+                    _ => self.close(false).map(Some),
+                }
+            }
+            State::Closed => self.closed_step(),
+            State::Done => Ok(None),
+        }
+    }
+
+    fn item_step(&mut self) -> Result<Option<(Token<'a>, Span)>, SuperError> {
+        let base = self.depth;
+        let seps = self.seps(base);
+        if let Some(found) = self.trivia(seps, base) {
+            return Ok(Some(found));
+        }
+
+        match self.peek() {
+            Some(sep) if seps.contains(&sep) => {
+                self.pos += 1;
+                let token = match sep {
+                    b'\n' => Token::Newline,
+                    _ => Token::Comma,
+                };
+                return Ok(Some(self.token(token, self.pos - 1)));
+            }
+            None if self.depth == 0 => {
+                self.state = State::Done;
+                return Ok(None);
+            }
+            None | Some(b']' | b'}') => return self.close(true).map(Some),
+            Some(_) => (),
+        }
+
+        let mut key = self.scan_to(seps, base, true)?;
+        let mut ahead = self.clone();
+        while ahead.trivia(seps, base).is_some() {}
+
+        // keys are split off at the first space as they are, so any other
+        // whitespace before it stays in the key unless there's no value
+        match key.space && !ahead.at_boundary(seps, base) {
+            true => {
+                self.state = State::Value { base };
+                key.end = self.pos - 1;
+            }
+            false => {
+                self.pos = key.end;
+                self.state = State::Nothing;
+            }
+        }
+        Ok(Some(self.scanned(Token::Key, key)))
+    }
+
+    fn element_step(&mut self, start: usize) -> Result<Option<(Token<'a>, Span)>, SuperError> {
+        match self.peek() {
+            // a blank last element is skipped so `[]` and trailing commas work
+            Some(b']') if self.input[start..self.pos].trim().is_empty() => {
+                self.close(false).map(Some)
+            }
+            None | Some(b',' | b']' | b'}') => {
+                self.state = State::AfterElement;
+                Ok(Some(self.token(Token::Scalar(Raw::new("")), self.pos)))
+            }
+            Some(_) => self.value_step(self.depth),
+        }
+    }
+
+    fn value_step(&mut self, base: usize) -> Result<Option<(Token<'a>, Span)>, SuperError> {
+        let group = match self.peek() {
+            Some(b'[') if self.depth == base => false,
+            Some(b'{') if self.depth == base => true,
+            _ => {
+                let found = self.scan_to(self.seps(base), base, false)?;
+                self.pos = found.end;
+                self.state = self.after_value();
+                return Ok(Some(self.scanned(Token::Scalar, found)));
+            }
+        };
+
+        self.push(group)?;
+        self.pos += 1;
+        let token = match group {
+            true => {
+                self.state = State::Items;
+                Token::GroupStart
+            }
+            false => {
+                self.state = State::Element { start: self.pos };
+                Token::ListStart
+            }
+        };
+        Ok(Some(self.token(token, self.pos - 1)))
+    }
+
+    /// Gives whatever comes after a closing bracket as [Token::Trailing] if
+    /// it isn't the end of the value
+    fn closed_step(&mut self) -> Result<Option<(Token<'a>, Span)>, SuperError> {
+        if let Some(found) = self.trivia(self.seps(self.depth), self.depth) {
+            return Ok(Some(found));
+        }
+
+        match self.trailing()? {
+            Some(found) => Ok(Some(self.scanned(Token::Trailing, found))),
+            None => self.step(),
+        }
+    }
+
+    /// Moves past the rest of the list or group which was just started
+    /// without giving its tokens, giving where any text after it is which
+    /// makes the whole of it a name
+    pub(crate) fn skip_nested(&mut self) -> Result<Option<Span>, SuperError> {
+        match self.nested_end().and_then(|_| self.trailing()) {
+            Ok(found) => Ok(found.map(|found| Span::new(found.start, found.end))),
+            Err(err) => {
+                self.state = State::Done;
+                Err(err)
+            }
+        }
+    }
+
+    fn nested_end(&mut self) -> Result<(), SuperError> {
+        let group = self.is_group(self.depth);
+        self.scan_to(b"", self.depth, false)?;
+        self.close(group)?;
+
+        while self.trivia(self.seps(self.depth), self.depth).is_some() {}
+        Ok(())
+    }
+
+    fn trailing(&mut self) -> Result<Option<Scanned>, SuperError> {
+        let base = self.depth;
+        let seps = self.seps(base);
+        self.state = self.after_value();
+        if self.at_boundary(seps, base) {
+            return Ok(None);
+        }
+
+        let found = self.scan_to(seps, base, false)?;
+        self.pos = found.end;
+        Ok(Some(found))
+    }
+
+    /// Moves past the closing bracket of the innermost list or group
+    fn close(&mut self, group: bool) -> Result<(Token<'a>, Span), SuperError> {
+        if self.depth == 0 || !self.is_closed(group) {
+            return Err(SuperError::Unbalanced);
+        }
+
+        self.pos += 1;
+        self.depth -= 1;
+        self.state = State::Closed;
+
+        let token = match group {
+            true => Token::GroupEnd,
+            false => Token::ListEnd,
+        };
+        Ok(self.token(token, self.pos - 1))
+    }
+
+    /// Gives a token holding text which was scanned
+    fn scanned(&self, token: fn(Raw<'a>) -> Token<'a>, found: Scanned) -> (Token<'a>, Span) {
+        let text = &self.input[found.start..found.end];
+        // comments only need looking through if there's more text after them
+        let escaped = found.escaped
+            || found.comment.is_some_and(|comment| {
+                comment < found.end && self.input[comment..found.end].contains('\\')
+            });
+        let raw = Raw { text, escaped };
+        (token(raw), Span::new(found.start, found.end))
+    }
+
+    /// Gives a token which is a single byte long, or empty if it's at the end
+    /// of the text
+    fn token(&self, token: Token<'a>, start: usize) -> (Token<'a>, Span) {
+        let end = match token {
+            Token::Scalar(_) => start,
+            _ => start + 1,
+        };
+        (token, Span::new(start, end))
+    }
+
+    /// Checks if the next byte closes the innermost bracket, which is of the
+    /// kind given by `group`
+    fn is_closed(&self, group: bool) -> bool {
+        let expected = match group {
+            true => b'}',
+            false => b']',
+        };
+        self.depth != 0 && self.is_group(self.depth) == group && self.peek() == Some(expected)
+    }
+
+    fn after_value(&self) -> State {
+        match self.depth == 0 || self.is_group(self.depth) {
+            true => State::Items,
+            false => State::AfterElement,
+        }
+    }
+
+    fn seps(&self, depth: usize) -> &'static [u8] {
+        match depth {
+            0 if self.lines => b"\n",
+            0 => b"",
+            _ if self.is_group(depth) => b",\n",
+            _ => b",",
+        }
+    }
+
+    fn is_group(&self, depth: usize) -> bool {
+        self.groups & (1 << (depth - 1)) != 0
+    }
+
+    fn push(&mut self, group: bool) -> Result<(), SuperError> {
+        if self.depth == MAX_DEPTH {
+            return Err(SuperError::TooDeep);
+        }
+
+        self.groups &= !(1 << self.depth);
+        self.groups |= (group as u128) << self.depth;
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn at_boundary(&self, seps: &[u8], base: usize) -> bool {
+        match self.peek() {
+            None => true,
+            Some(byte) => {
+                self.depth == base && (seps.contains(&byte) || matches!(byte, b']' | b'}'))
+            }
+        }
+    }
+
+    /// Skips over whitespace up to the next comment or newline, which is
+    /// given, stopping at anything else or any of `seps`
+    fn trivia(&mut self, seps: &[u8], base: usize) -> Option<(Token<'a>, Span)> {
+        while let Some(byte) = self.peek() {
+            let start = self.pos;
+
+            if byte == b'#' {
+                self.skip_comment();
+                return Some((
+                    Token::Comment(&self.input[start..self.pos]),
+                    Span::new(start, self.pos),
+                ));
+            } else if self.depth == base && seps.contains(&byte) {
+                return None;
+            }
+
+            match self.char_at() {
+                _ if byte == b'\n' => {
+                    self.pos += 1;
+                    return Some(self.token(Token::Newline, start));
+                }
+                (len, true) => self.pos += len,
+                (_, false) => return None,
+            }
+        }
+
+        None
+    }
+
+    /// Gives the length of the char which is next and if it's whitespace,
+    /// without decoding it if it's ascii
+    fn char_at(&self) -> (usize, bool) {
+        match self.peek() {
+            Some(byte) if byte.is_ascii() => (1, matches!(byte, b' ' | b'\t'..=b'\r')),
+            _ => {
+                let c = self.input[self.pos..].chars().next().unwrap_or_default();
+                (c.len_utf8(), c.is_whitespace())
+            }
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        self.pos = find_newline(&self.input.as_bytes()[self.pos..])
+            .map_or(self.input.len(), |ind| self.pos + ind);
+    }
+
+    /// Scans up to the next boundary, or up to the next unescaped space if
+    /// `space` is set
+    fn scan_to(&mut self, seps: &[u8], base: usize, space: bool) -> Result<Scanned, SuperError> {
+        let start = self.pos;
+        let mut end = self.pos;
+        let mut escaped = false;
+        let mut comment = None;
+
+        while let Some(byte) = self.peek() {
+            match byte {
+                // runs of bytes which can only ever be part of the text are
+                // skipped over in one go
+                _ if PLAIN[byte as usize] => {
+                    self.pos += self.input.as_bytes()[self.pos..]
+                        .iter()
+                        .position(|byte| !PLAIN[*byte as usize])
+                        .unwrap_or(self.input.len() - self.pos);
+                    end = self.pos;
+                }
+                b'\\' => {
+                    escaped = true;
+                    self.pos += 1;
+                    self.pos += self.input[self.pos..]
+                        .chars()
+                        .next()
+                        .map_or(0, char::len_utf8);
+                    end = self.pos;
+                }
+                b'#' => {
+                    comment.get_or_insert(self.pos);
+                    self.skip_comment();
+                }
+                b' ' if space => break,
+                _ if self.depth == base && seps.contains(&byte) => break,
+                b'[' | b'{' => {
+                    self.push(byte == b'{')?;
+                    self.pos += 1;
+                    end = self.pos;
+                }
+                b']' | b'}' if self.depth == base => break,
+                b']' | b'}' => {
+                    if self.is_group(self.depth) != (byte == b'}') {
+                        return Err(SuperError::Unbalanced);
+                    }
+                    self.depth -= 1;
+                    self.pos += 1;
+                    end = self.pos;
+                }
+                _ => {
+                    let (len, blank) = self.char_at();
+                    self.pos += len;
+                    if !blank {
+                        end = self.pos;
+                    }
+                }
+            }
+        }
+
+        let space = space && self.peek() == Some(b' ');
+        if space {
+            self.pos += 1;
+        }
+
+        Ok(Scanned {
+            start,
+            end,
+            space,
+            escaped,
+            comment,
+        })
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Result<(Token<'a>, Span), SuperError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.step() {
+            Ok(found) => found.map(Ok),
+            Err(err) => {
+                self.state = State::Done;
+                Some(Err(err.at(self.pos)))
+            }
+        }
+    }
+}
+
+/// What a piece of text given by [Classes] is, for syntax highlighting
+#[cfg(feature = "alloc")]
+#[derive(Debug, PartialEq, Clone)]
+pub enum Class {
+    Key,
+    /// Value which isn't a list or group, or text after a closing bracket
+    Value,
+    /// Bracket or comma
+    Punctuation,
+    Comment,
+    /// Text which couldn't be read, alongside why
+    Error(SuperError),
+}
+
+/// Splits text into [Class]es and their [Span]s, carrying on past any errors
+/// so that the whole of the text is always covered
+///
+/// After an error, the rest of its line is given as [Class::Error] and the
+/// tokens carry on from the next line as if it was at the top level. Brackets
+/// which are never closed are given as the error at the end, after the tokens
+/// inside of them. Newlines aren't given.
+///
+/// ```
+/// use superconf::tokens::{Class, Classes, Span};
+/// use superconf::SuperError;
+///
+/// let found: Vec<_> = Classes::new("a {b]\nc 1").collect();
+/// assert_eq!(
+///     found[4],
+///     (Class::Error(SuperError::Unbalanced), Span { start: 4, end: 5 })
+/// );
+///
+/// let classes: Vec<_> = found.into_iter().map(|(class, _)| class).collect();
+/// assert_eq!(classes[5..], [Class::Key, Class::Value]);
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct Classes<'a> {
+    tokens: Tokens<'a>,
+    /// Where each list or group which is still open starts
+    open: alloc::vec::Vec<usize>,
+    /// Where the last token given ends
+    end: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> Classes<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            tokens: Tokens::new(input),
+            open: alloc::vec::Vec::new(),
+            end: 0,
+        }
+    }
+
+    /// Gives the text from the last token up to the end of the line which
+    /// `err` was found on, starting again after it
+    fn recover(&mut self, err: SuperError) -> (Class, Span) {
+        let input = self.tokens.input;
+        let pos = self.tokens.pos;
+        if pos == input.len() {
+            if let Some(start) = self.open.pop() {
+                return (Class::Error(err), Span::new(start, start + 1));
+            }
+        }
+
+        let end = find_newline(&input.as_bytes()[pos..]).map_or(input.len(), |ind| pos + ind);
+        let gap = &input[self.end..pos];
+        let start = self.end + gap.len() - gap.trim_start().len();
+
+        self.tokens = Tokens {
+            pos: end,
+            ..Tokens::new(input)
+        };
+        self.open.clear();
+        self.end = end;
+        (Class::Error(err), Span::new(start, end))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Iterator for Classes<'_> {
+    type Item = (Class, Span);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (token, span) = match self.tokens.next()? {
+                Ok(found) => found,
+                Err(err) => return Some(self.recover(err)),
+            };
+            self.end = span.end;
+
+            let class = match token {
+                Token::Key(_) => Class::Key,
+                Token::Scalar(_) | Token::Trailing(_) => Class::Value,
+                Token::ListStart | Token::GroupStart => {
+                    self.open.push(span.start);
+                    Class::Punctuation
+                }
+                Token::ListEnd | Token::GroupEnd => {
+                    self.open.pop();
+                    Class::Punctuation
+                }
+                Token::Comma => Class::Punctuation,
+                Token::Comment(_) => Class::Comment,
+                Token::Newline => continue,
+            };
+            return Some((class, span));
+        }
+    }
+}
+
+/// Finds the first newline, using memchr if the `memchr` feature is enabled
+fn find_newline(bytes: &[u8]) -> Option<usize> {
+    #[cfg(feature = "memchr")]
+    return memchr::memchr(b'\n', bytes);
+
+    #[cfg(not(feature = "memchr"))]
+    bytes.iter().position(|byte| *byte == b'\n')
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn tokens(input: &str) -> Vec<(Token<'_>, &str)> {
+        Tokens::new(input)
+            .map(|found| found.map(|(token, span)| (token, &input[span.start..span.end])))
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn spans() {
+        use Token::*;
+
+        assert_eq!(
+            tokens("# top\nport 80\nhosts [a, # b\n c]\n\nx {} y\nempty"),
+            [
+                (Comment("# top"), "# top"),
+                (Newline, "\n"),
+                (Key(Raw::new("port")), "port"),
+                (Scalar(Raw::new("80")), "80"),
+                (Newline, "\n"),
+                (Key(Raw::new("hosts")), "hosts"),
+                (ListStart, "["),
+                (Scalar(Raw::new("a")), "a"),
+                (Comma, ","),
+                (Comment("# b"), "# b"),
+                (Newline, "\n"),
+                (Scalar(Raw::new("c")), "c"),
+                (ListEnd, "]"),
+                (Newline, "\n"),
+                (Newline, "\n"),
+                (Key(Raw::new("x")), "x"),
+                (GroupStart, "{"),
+                (GroupEnd, "}"),
+                (Trailing(Raw::new("y")), "y"),
+                (Newline, "\n"),
+                (Key(Raw::new("empty")), "empty"),
+                (Scalar(Raw::new("")), ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn covers_text() {
+        let text = "a {b 1,\n c [2, , 3]  } # d\n\te\\ f  [] g\n";
+        let mut pos = 0;
+
+        for (token, span) in Tokens::new(text).map(Result::unwrap) {
+            let gap = &text[pos..span.start];
+            assert!(
+                gap.chars().all(|c| c.is_whitespace() && c != '\n'),
+                "{:?}",
+                token
+            );
+            pos = span.end;
+        }
+        assert_eq!(pos, text.len());
+    }
+
+    #[test]
+    fn escapes() {
+        let escaped = |input| {
+            Tokens::new(input)
+                .filter_map(|found| match found.unwrap().0 {
+                    Token::Key(raw) | Token::Scalar(raw) => Some(raw.is_escaped()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(escaped("a\\ b c\nd e"), [true, false, false, false]);
+        assert_eq!(escaped("a [b # \\\n c] # \\"), [false, true]);
+        assert_eq!(escaped("a{#\\\n b} c"), [true, false]);
+        assert_eq!(
+            Tokens::new("a{#\\\n b} c").next().unwrap().unwrap().0,
+            Token::Key(Raw::new("a{#\\\n"))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        for input in ["a [1", "a {b]", "a ]", "{"] {
+            let mut found = Tokens::new(input);
+            assert_eq!(
+                found.find_map(Result::err),
+                Some(SuperError::Unbalanced),
+                "{}",
+                input
+            );
+            assert_eq!(found.next(), None);
+        }
+    }
+
+    fn classes(input: &str) -> Vec<(Class, &str)> {
+        Classes::new(input)
+            .map(|(class, span)| (class, &input[span.start..span.end]))
+            .collect()
+    }
+
+    #[test]
+    fn classified() {
+        use Class::*;
+
+        assert_eq!(
+            classes("a [1, {b 2}] # c\nd {e} f"),
+            [
+                (Key, "a"),
+                (Punctuation, "["),
+                (Value, "1"),
+                (Punctuation, ","),
+                (Punctuation, "{"),
+                (Key, "b"),
+                (Value, "2"),
+                (Punctuation, "}"),
+                (Punctuation, "]"),
+                (Comment, "# c"),
+                (Key, "d"),
+                (Punctuation, "{"),
+                (Key, "e"),
+                (Value, ""),
+                (Punctuation, "}"),
+                (Value, "f"),
+            ]
+        );
+        assert_eq!(
+            classes("a x[1} y\n b\nc ]\nx {d [1\n  e 2"),
+            [
+                (Key, "a"),
+                (Error(SuperError::Unbalanced), "x[1} y"),
+                (Key, "b"),
+                (Value, ""),
+                (Key, "c"),
+                (Value, ""),
+                (Error(SuperError::Unbalanced), "]"),
+                (Key, "x"),
+                (Punctuation, "{"),
+                (Key, "d"),
+                (Punctuation, "["),
+                (Value, "1\n  e 2"),
+                (Error(SuperError::Unbalanced), "["),
+            ]
+        );
+        assert_eq!(
+            classes(&["a ", &"[".repeat(200), "\nb 1"].concat())[MAX_DEPTH + 1..],
+            [
+                (Error(SuperError::TooDeep), &*"[".repeat(72)),
+                (Key, "b"),
+                (Value, "1"),
+            ]
+        );
+    }
+}
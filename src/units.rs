@@ -0,0 +1,220 @@
+//! Reading of numbers written with a unit after them, like `500ms`, `1.5s`
+//! or `10MiB`, converted into one unit so every application doesn't need to
+//! parse them itself
+
+use super::convert::{ConvertError, ConvertErrorKind};
+use super::SuperValue;
+use core::convert::TryFrom;
+
+/// Suffixes of durations, alongside how many milliseconds each one is
+const DURATIONS: [(&str, u64); 6] = [
+    ("ms", 1),
+    ("s", 1000),
+    ("m", 60 * 1000),
+    ("min", 60 * 1000),
+    ("h", 60 * 60 * 1000),
+    ("d", 24 * 60 * 60 * 1000),
+];
+
+/// Suffixes of sizes alongside how many bytes each one is, which are matched
+/// in any case
+const SIZES: [(&str, u64); 13] = [
+    ("b", 1),
+    ("kb", 1000),
+    ("mb", 1000 * 1000),
+    ("gb", 1000 * 1000 * 1000),
+    ("tb", 1000 * 1000 * 1000 * 1000),
+    ("k", 1 << 10),
+    ("kib", 1 << 10),
+    ("m", 1 << 20),
+    ("mib", 1 << 20),
+    ("g", 1 << 30),
+    ("gib", 1 << 30),
+    ("t", 1 << 40),
+    ("tib", 1 << 40),
+];
+
+impl SuperValue<'_> {
+    /// Reads a duration in milliseconds, written with a unit of `ms`, `s`,
+    /// `m` or `min`, `h` or `d`, where plain numbers are already in
+    /// milliseconds
+    ///
+    /// ```
+    /// use superconf::{Parse, SuperValue};
+    ///
+    /// assert_eq!(SuperValue::parse("1.5s").unwrap().as_millis(), Ok(1500));
+    /// assert_eq!(SuperValue::parse("2 h").unwrap().as_millis(), Ok(7_200_000));
+    /// assert_eq!(SuperValue::parse("250").unwrap().as_millis(), Ok(250));
+    /// ```
+    pub fn as_millis(&self) -> Result<u64, ConvertError> {
+        scaled(self, "a duration", |unit| {
+            DURATIONS
+                .iter()
+                .find(|(found, _)| *found == unit)
+                .map(|(_, scale)| *scale)
+        })
+    }
+
+    /// Reads a size in bytes, written with a unit such as `kb` for 1000 bytes
+    /// or `k` or `kib` for 1024 bytes in any case, up to terabytes, where
+    /// plain numbers are already in bytes
+    ///
+    /// ```
+    /// use superconf::{Parse, SuperValue};
+    ///
+    /// assert_eq!(SuperValue::parse("10MiB").unwrap().as_bytes(), Ok(10 << 20));
+    /// assert_eq!(SuperValue::parse("1.5kB").unwrap().as_bytes(), Ok(1500));
+    /// ```
+    pub fn as_bytes(&self) -> Result<u64, ConvertError> {
+        scaled(self, "a size", |unit| {
+            SIZES
+                .iter()
+                .find(|(found, _)| found.eq_ignore_ascii_case(unit))
+                .map(|(_, scale)| *scale)
+        })
+    }
+
+    /// Reads a ratio, written as a number such as `0.25` or a percentage
+    /// such as `25%`
+    pub fn as_ratio(&self) -> Result<f64, ConvertError> {
+        let (number, unit) = match self {
+            Self::Integer(integer) => return Ok(*integer as f64),
+            Self::Float(float) => return Ok(*float),
+            Self::Name(name) => split(name).ok_or_else(|| ConvertError::expected("a ratio"))?,
+            _ => return Err(ConvertError::expected("a ratio")),
+        };
+        let number: f64 = number
+            .parse()
+            .map_err(|_| ConvertError::expected("a ratio"))?;
+
+        match unit {
+            "" => Ok(number),
+            "%" => Ok(number / 100.0),
+            _ => Err(ConvertError::new(ConvertErrorKind::UnknownUnit)),
+        }
+    }
+}
+
+/// Reads a positive number which is multiplied by the scale of its unit, as
+/// given by `scale`
+fn scaled(
+    value: &SuperValue<'_>,
+    what: &'static str,
+    scale: impl Fn(&str) -> Option<u64>,
+) -> Result<u64, ConvertError> {
+    let (number, unit) = match value {
+        SuperValue::Integer(integer) => {
+            return u64::try_from(*integer).map_err(|_| ConvertError::expected(what))
+        }
+        SuperValue::Float(float) => return from_float(*float, what),
+        SuperValue::Name(name) => split(name).ok_or_else(|| ConvertError::expected(what))?,
+        _ => return Err(ConvertError::expected(what)),
+    };
+    let scale = match unit {
+        "" => 1,
+        unit => scale(unit).ok_or_else(|| ConvertError::new(ConvertErrorKind::UnknownUnit))?,
+    };
+
+    if number.contains('.') {
+        let number: f64 = number.parse().map_err(|_| ConvertError::expected(what))?;
+        return from_float(number * scale as f64, what);
+    }
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(scale))
+        .ok_or_else(|| ConvertError::new(ConvertErrorKind::OutOfRange))
+}
+
+/// Rounds a positive float to the nearest integer
+fn from_float(float: f64, what: &'static str) -> Result<u64, ConvertError> {
+    match float {
+        _ if float.is_nan() || float < 0.0 => Err(ConvertError::expected(what)),
+        // 2^64, which is the first float too large for an integer
+        _ if float + 0.5 >= 18_446_744_073_709_551_616.0 => {
+            Err(ConvertError::new(ConvertErrorKind::OutOfRange))
+        }
+        _ => Ok((float + 0.5) as u64),
+    }
+}
+
+/// Splits text into its number and its unit, which is nothing if it doesn't
+/// start with a digit
+fn split(text: &str) -> Option<(&str, &str)> {
+    let text = text.trim();
+    let end = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+
+    match text[..end].starts_with(|c: char| c.is_ascii_digit()) {
+        true => Some((&text[..end], text[end..].trim_start())),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+
+    fn value(text: &str) -> SuperValue<'_> {
+        SuperValue::parse(text).unwrap()
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(value("500ms").as_millis(), Ok(500));
+        assert_eq!(value("10 min").as_millis(), Ok(600_000));
+        assert_eq!(value("1d").as_millis(), Ok(86_400_000));
+        assert_eq!(value("0.0015s").as_millis(), Ok(2));
+        assert_eq!(value("12.0").as_millis(), Ok(12));
+        assert_eq!(
+            value("5 fortnights").as_millis(),
+            Err(ConvertError::new(ConvertErrorKind::UnknownUnit))
+        );
+        assert_eq!(
+            value("-5s").as_millis(),
+            Err(ConvertError::expected("a duration"))
+        );
+        assert_eq!(
+            value("-5").as_millis(),
+            Err(ConvertError::expected("a duration"))
+        );
+        assert_eq!(
+            value("9999999999999999d").as_millis(),
+            Err(ConvertError::new(ConvertErrorKind::OutOfRange))
+        );
+        assert_eq!(
+            value("1e30").as_millis(),
+            Err(ConvertError::new(ConvertErrorKind::OutOfRange))
+        );
+    }
+
+    #[test]
+    fn sizes_and_ratios() {
+        assert_eq!(value("4096").as_bytes(), Ok(4096));
+        assert_eq!(value("2G").as_bytes(), Ok(2 << 30));
+        assert_eq!(value("3 TB").as_bytes(), Ok(3_000_000_000_000));
+        assert_eq!(value("16b").as_bytes(), Ok(16));
+        assert_eq!(
+            value("16 bits").as_bytes(),
+            Err(ConvertError::new(ConvertErrorKind::UnknownUnit))
+        );
+        assert_eq!(
+            value("[1]").as_bytes(),
+            Err(ConvertError::expected("a size"))
+        );
+
+        assert_eq!(value("25%").as_ratio(), Ok(0.25));
+        assert_eq!(value("1").as_ratio(), Ok(1.0));
+        assert_eq!(value("0.5").as_ratio(), Ok(0.5));
+        assert_eq!(
+            value("5x").as_ratio(),
+            Err(ConvertError::new(ConvertErrorKind::UnknownUnit))
+        );
+        assert_eq!(
+            value("half").as_ratio(),
+            Err(ConvertError::expected("a ratio"))
+        );
+    }
+}
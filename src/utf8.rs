@@ -0,0 +1,185 @@
+//! Parsing of configs straight from bytes with [SuperConf::parse_bytes], for
+//! buffers read from flash or a socket which haven't been checked as text,
+//! or with [SuperConf::parse_detect] for files in whichever encoding
+
+use super::{Parse, SuperConf, SuperError};
+use alloc::string::String;
+use core::str;
+
+/// How [SuperConf::parse_bytes] handles bytes which aren't valid UTF-8
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Utf8Mode {
+    /// Gives [SuperError::InvalidUtf8] for anything which isn't valid UTF-8
+    #[default]
+    Strict,
+    /// Replaces anything which isn't valid UTF-8 with `U+FFFD`, which copies
+    /// the whole config so that it no longer borrows from the bytes
+    Lossy,
+    /// Only allows ASCII, giving [SuperError::InvalidUtf8] for any byte
+    /// above `0x7F`, which is quicker to check than UTF-8 as a whole
+    Ascii,
+}
+
+impl<'a> SuperConf<'a> {
+    /// Parses a config from bytes, checking that they're text according to
+    /// `mode` instead of needing [str::from_utf8] first
+    ///
+    /// ```
+    /// use superconf::{SuperConf, SuperError, SuperValue, Utf8Mode};
+    ///
+    /// let bytes = b"name caf\xe9\nport 80";
+    ///
+    /// assert_eq!(
+    ///     SuperConf::parse_bytes(bytes, Utf8Mode::Strict),
+    ///     Err(SuperError::InvalidUtf8)
+    /// );
+    ///
+    /// let conf = SuperConf::parse_bytes(bytes, Utf8Mode::Lossy).unwrap();
+    /// assert_eq!(conf.get("name"), Some(&SuperValue::Name("caf\u{fffd}".into())));
+    /// ```
+    pub fn parse_bytes(input: &'a [u8], mode: Utf8Mode) -> Result<Self, SuperError> {
+        match mode {
+            Utf8Mode::Strict => Self::parse(
+                str::from_utf8(input)
+                    .map_err(|err| SuperError::InvalidUtf8.at(err.valid_up_to()))?,
+            ),
+            Utf8Mode::Lossy => match str::from_utf8(input) {
+                Ok(text) => Self::parse(text),
+                Err(_) => {
+                    let text = String::from_utf8_lossy(input);
+                    let conf = SuperConf::parse(&text)?;
+                    Ok(conf.into_owned())
+                }
+            },
+            Utf8Mode::Ascii => match input.is_ascii() {
+                // SAFETY: ascii is always valid utf-8, so checking it again
+                // would only be a second pass over the bytes
+                true => Self::parse(unsafe { str::from_utf8_unchecked(input) }),
+                false => {
+                    let ind = input.iter().position(|byte| !byte.is_ascii());
+                    Err(SuperError::InvalidUtf8.at(ind.unwrap_or_default()))
+                }
+            },
+        }
+    }
+
+    /// Parses a config from bytes in whichever encoding they're in, which is
+    /// UTF-16 if they start with its byte order mark and UTF-8 otherwise,
+    /// replacing anything invalid with `U+FFFD` like [Utf8Mode::Lossy]
+    ///
+    /// This is for files written by tools which save as UTF-16, as many on
+    /// Windows do, and always copies the config so it doesn't borrow the bytes.
+    ///
+    /// ```
+    /// use superconf::{SuperConf, SuperValue};
+    ///
+    /// let bytes: Vec<u8> = std::iter::once(0xfeff)
+    ///     .chain("port 80".encode_utf16())
+    ///     .flat_map(u16::to_le_bytes)
+    ///     .collect();
+    ///
+    /// let conf = SuperConf::parse_detect(&bytes).unwrap();
+    /// assert_eq!(conf.get("port"), Some(&SuperValue::Integer(80)));
+    /// ```
+    pub fn parse_detect(input: &[u8]) -> Result<SuperConf<'static>, SuperError> {
+        let text = match input {
+            [0xff, 0xfe, rest @ ..] => utf16(rest, u16::from_le_bytes),
+            [0xfe, 0xff, rest @ ..] => utf16(rest, u16::from_be_bytes),
+            [0xef, 0xbb, 0xbf, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+            _ => String::from_utf8_lossy(input).into_owned(),
+        };
+        let conf = SuperConf::parse(&text)?;
+        Ok(conf.into_owned())
+    }
+}
+
+/// Decodes UTF-16 with each unit read from its bytes by `unit`, where a byte
+/// left over at the end is invalid like an unpaired surrogate
+fn utf16(input: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let units = input.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    let mut text: String = char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect();
+
+    if input.len() % 2 == 1 {
+        text.push(char::REPLACEMENT_CHARACTER);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SuperValue;
+
+    #[test]
+    fn modes() {
+        let text = "name café\nport 80";
+
+        for mode in [Utf8Mode::Strict, Utf8Mode::Lossy] {
+            assert_eq!(
+                SuperConf::parse_bytes(text.as_bytes(), mode),
+                SuperConf::parse(text)
+            );
+        }
+        assert_eq!(
+            SuperConf::parse_bytes(text.as_bytes(), Utf8Mode::Ascii),
+            Err(SuperError::InvalidUtf8)
+        );
+        assert_eq!(
+            SuperConf::parse_bytes(b"port 80", Utf8Mode::Ascii)
+                .unwrap()
+                .get("port"),
+            Some(&SuperValue::Integer(80))
+        );
+
+        let invalid = b"a [\xff, b]";
+        assert_eq!(
+            SuperConf::parse_bytes(invalid, Utf8Mode::Strict),
+            Err(SuperError::InvalidUtf8)
+        );
+        assert_eq!(
+            SuperConf::parse_bytes(invalid, Utf8Mode::Lossy).unwrap(),
+            SuperConf::parse("a [\u{fffd}, b]").unwrap()
+        );
+        assert_eq!(
+            SuperConf::parse_bytes(b"a [\xff", Utf8Mode::Lossy),
+            Err(SuperError::Unbalanced)
+        );
+    }
+
+    #[test]
+    fn detected() {
+        let text = "name café\nport 80";
+        let utf16 = |bom: u16, unit: fn(u16) -> [u8; 2]| -> alloc::vec::Vec<u8> {
+            core::iter::once(bom)
+                .chain(text.encode_utf16())
+                .flat_map(unit)
+                .collect()
+        };
+
+        for bytes in [
+            text.as_bytes().to_vec(),
+            [&[0xef, 0xbb, 0xbf][..], text.as_bytes()].concat(),
+            utf16(0xfeff, u16::to_le_bytes),
+            utf16(0xfeff, u16::to_be_bytes),
+        ] {
+            assert_eq!(SuperConf::parse_detect(&bytes), SuperConf::parse(text));
+        }
+
+        let unpaired = [0xff, 0xfe, b'a', 0, b' ', 0, 0x00, 0xd8, b'b'];
+        assert_eq!(
+            SuperConf::parse_detect(&unpaired).unwrap(),
+            SuperConf::parse("a \u{fffd}\u{fffd}").unwrap()
+        );
+        assert_eq!(
+            SuperConf::parse_detect(b"a caf\xe9").unwrap(),
+            SuperConf::parse("a caf\u{fffd}").unwrap()
+        );
+        assert_eq!(
+            SuperConf::parse_detect(&[0xfe, 0xff, 0, b'[']),
+            Err(SuperError::Unbalanced)
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! JavaScript bindings made with wasm-bindgen, behind the `wasm` feature, so
+//! web editors can check configs with the same parser as everything else
+//!
+//! Configs become plain objects, where nothing is `null`, names are strings,
+//! lists are arrays and groups are objects. Integers become numbers, which
+//! loses precision past 2^53, and groups with duplicate keys only keep the
+//! last.
+
+use super::{Emit, Parse, SuperConf, SuperItem, SuperValue};
+use alloc::string::{String, ToString};
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+/// Parsed config, which is `SuperConf` on the JavaScript side
+#[wasm_bindgen(js_name = SuperConf)]
+pub struct WasmConf(SuperConf<'static>);
+
+#[wasm_bindgen(js_class = SuperConf)]
+impl WasmConf {
+    /// Parses `text`, throwing an error if it's invalid
+    #[wasm_bindgen(constructor)]
+    pub fn new(text: &str) -> Result<WasmConf, JsError> {
+        match SuperConf::parse(text) {
+            Ok(conf) => Ok(Self(conf.into_owned())),
+            Err(err) => Err(JsError::new(&err.to_string())),
+        }
+    }
+
+    /// Gets the value at a path such as `server.hosts[2]` as a JavaScript
+    /// value, or `undefined` if there isn't one
+    pub fn get(&self, path: &str) -> JsValue {
+        self.0.get(path).map_or(JsValue::UNDEFINED, to_js)
+    }
+
+    /// Writes the config back out as text
+    pub fn emit(&self) -> String {
+        self.0.emit()
+    }
+
+    /// Converts the whole config into an object
+    #[wasm_bindgen(js_name = toObject)]
+    pub fn to_object(&self) -> JsValue {
+        items_to_js(&self.0.items)
+    }
+}
+
+/// Parses `text` straight into an object, throwing an error if it's invalid
+#[wasm_bindgen]
+pub fn parse(text: &str) -> Result<JsValue, JsError> {
+    Ok(WasmConf::new(text)?.to_object())
+}
+
+/// Checks if `text` is valid, giving the error message if it isn't
+#[wasm_bindgen]
+pub fn validate(text: &str) -> Option<String> {
+    SuperConf::parse(text).err().map(|err| err.to_string())
+}
+
+fn to_js(value: &SuperValue) -> JsValue {
+    match value {
+        SuperValue::Nothing => JsValue::NULL,
+        SuperValue::Name(name) => JsValue::from_str(name),
+        SuperValue::Bool(boolean) => JsValue::from_bool(*boolean),
+        SuperValue::Integer(integer) => JsValue::from_f64(*integer as f64),
+        SuperValue::Float(float) => JsValue::from_f64(*float),
+        SuperValue::Bytes(data) => JsValue::from_str(data),
+        SuperValue::List(values) => values.iter().map(to_js).collect::<Array>().into(),
+        SuperValue::Group(items) => items_to_js(items),
+    }
+}
+
+fn items_to_js(items: &[SuperItem]) -> JsValue {
+    let object = Object::new();
+
+    for item in items {
+        // setting a property on a plain object can't fail
+        Reflect::set(&object, &JsValue::from_str(&item.key), &to_js(&item.value)).ok();
+    }
+
+    object.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // everything else makes JavaScript values, which needs a JavaScript host
+    #[test]
+    fn validates() {
+        assert_eq!(validate("port 8080\nhosts [a, b]"), None);
+        assert_eq!(
+            validate("hosts [a, b"),
+            Some("brackets are unbalanced".into())
+        );
+    }
+}
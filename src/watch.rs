@@ -0,0 +1,383 @@
+//! Hot reloading of config files with a [Watcher], behind the `std` feature
+//!
+//! The file is checked by reading it again every [Watcher::interval], so it
+//! keeps working when editors replace the file instead of writing to it.
+
+use super::schema::Report;
+use super::{Diff, LoadError, Parse, Path, Schema, SuperConf, SuperError, SuperValue};
+use core::fmt;
+use core::time::Duration;
+use std::boxed::Box;
+use std::path::PathBuf;
+use std::string::String;
+use std::vec::Vec;
+
+/// Callback which is given every [Reload] of a [Watcher]
+pub type Subscriber = Box<dyn FnMut(&Reload<'_>) + Send>;
+
+/// Callback which is given the new value at a path whenever it changes,
+/// which is nothing if it was removed
+pub type Callback = Box<dyn FnMut(Option<&SuperValue<'static>>) + Send>;
+
+/// Values layered on top of every config which is loaded, such as overrides
+/// from the command line or environment variables
+pub type Layer = Box<dyn FnMut(&mut SuperConf<'static>) -> Result<(), SuperError> + Send>;
+
+/// Config file which is parsed again whenever it changes, handing the new
+/// config to its subscribers alongside what changed
+///
+/// Configs which don't parse or don't match the schema are reported without
+/// replacing the config which was loaded before, so a daemon keeps running
+/// with the last good one.
+///
+/// ```
+/// use superconf::{Parse, SuperConf, Watcher};
+///
+/// let path = std::env::temp_dir().join("superconf-watcher-doc.conf");
+/// std::fs::write(&path, "port 80").unwrap();
+///
+/// let mut watcher = Watcher::new(&path)
+///     .unwrap()
+///     .subscribe(|reload| println!("config reloaded:\n{}", reload.diff.render()));
+///
+/// std::fs::write(&path, "port 8080").unwrap();
+/// assert!(watcher.poll().unwrap());
+/// assert_eq!(watcher.conf(), &SuperConf::parse("port 8080").unwrap());
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub struct Watcher {
+    path: PathBuf,
+    /// Text which was read last, which the file is compared against
+    text: String,
+    conf: SuperConf<'static>,
+    schema: Option<Schema>,
+    layers: Vec<Layer>,
+    subscribers: Vec<Subscriber>,
+    callbacks: Vec<(Path<'static>, Callback)>,
+    /// How long [Watcher::run] waits between checking the file
+    pub interval: Duration,
+}
+
+/// Config which was just reloaded by a [Watcher], given to its subscribers
+#[derive(Debug)]
+pub struct Reload<'r> {
+    /// Config from before the file changed
+    pub old: &'r SuperConf<'static>,
+    pub new: &'r SuperConf<'static>,
+    /// Every change from the old config to the new one, which is never empty
+    pub diff: Diff<'r>,
+}
+
+/// Error from loading or reloading the file of a [Watcher]
+#[derive(Debug)]
+pub enum WatchError {
+    /// When the file couldn't be read or parsed, or a layer couldn't be
+    /// applied
+    Load(LoadError),
+    /// When the config doesn't match the schema, with the spans of each
+    /// violation found
+    Invalid(Report),
+}
+
+impl Watcher {
+    /// Loads the file at `path`, which is then checked every second
+    pub fn new<P: Into<PathBuf>>(path: P) -> Result<Self, WatchError> {
+        Self::load(path.into(), None)
+    }
+
+    /// Loads the file at `path`, only taking configs which match `schema` and
+    /// filling in their defaults
+    pub fn with_schema<P: Into<PathBuf>>(path: P, schema: Schema) -> Result<Self, WatchError> {
+        Self::load(path.into(), Some(schema))
+    }
+
+    fn load(path: PathBuf, schema: Option<Schema>) -> Result<Self, WatchError> {
+        let mut watcher = Self {
+            path,
+            text: String::new(),
+            conf: SuperConf { items: Vec::new() },
+            schema,
+            layers: Vec::new(),
+            subscribers: Vec::new(),
+            callbacks: Vec::new(),
+            interval: Duration::from_secs(1),
+        };
+        let text = std::fs::read_to_string(&watcher.path).map_err(LoadError::from)?;
+        watcher.conf = watcher.read(text)?;
+
+        Ok(watcher)
+    }
+
+    /// Adds a callback which is given every reload which changed the config
+    pub fn subscribe(mut self, subscriber: impl FnMut(&Reload<'_>) + Send + 'static) -> Self {
+        self.subscribers.push(Box::new(subscriber));
+        self
+    }
+
+    /// Adds a callback which is only given the value at `path` when it changes,
+    /// after every [layer](Watcher::layer) has been applied
+    ///
+    /// # Panics
+    ///
+    /// If `path` isn't a valid path.
+    pub fn on_change(
+        mut self,
+        path: &str,
+        callback: impl FnMut(Option<&SuperValue<'static>>) + Send + 'static,
+    ) -> Self {
+        let path = Path::parse(path).expect("path to watch is valid");
+        self.callbacks.push((path.into_owned(), Box::new(callback)));
+        self
+    }
+
+    /// Adds values which are layered on top of the file every time it's
+    /// loaded, before it's checked against the schema, loading the current
+    /// config again with them
+    ///
+    /// ```
+    /// use superconf::{SuperValue, Watcher};
+    ///
+    /// let path = std::env::temp_dir().join("superconf-layer-doc.conf");
+    /// std::fs::write(&path, "port 80").unwrap();
+    ///
+    /// let watcher = Watcher::new(&path)
+    ///     .unwrap()
+    ///     .layer(|conf| conf.apply_env("APP", |name| std::env::var(name).ok()))
+    ///     .unwrap()
+    ///     .on_change("port", |port| println!("port is now {:?}", port));
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn layer(
+        mut self,
+        layer: impl FnMut(&mut SuperConf<'static>) -> Result<(), SuperError> + Send + 'static,
+    ) -> Result<Self, WatchError> {
+        self.layers.push(Box::new(layer));
+        let text = core::mem::take(&mut self.text);
+        self.conf = self.read(text)?;
+
+        Ok(self)
+    }
+
+    /// Config which was loaded last, with every layer applied
+    pub fn conf(&self) -> &SuperConf<'static> {
+        &self.conf
+    }
+
+    /// Checks the file once, reloading it and telling every subscriber if it
+    /// changed, which gives if the config was changed
+    pub fn poll(&mut self) -> Result<bool, WatchError> {
+        let text = std::fs::read_to_string(&self.path).map_err(LoadError::from)?;
+        if text == self.text {
+            return Ok(false);
+        }
+
+        let conf = self.read(text)?;
+        let old = core::mem::replace(&mut self.conf, conf);
+        let diff = old.diff(&self.conf);
+        if diff.is_empty() {
+            return Ok(false);
+        }
+
+        let reload = Reload {
+            old: &old,
+            new: &self.conf,
+            diff,
+        };
+        for subscriber in &mut self.subscribers {
+            subscriber(&reload);
+        }
+        for (path, callback) in &mut self.callbacks {
+            let value = self.conf.get_path(path);
+            if old.get_path(path) != value {
+                callback(value);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Checks the file every [Watcher::interval] forever, handing any errors
+    /// to `on_error`, for running on its own thread
+    pub fn run(&mut self, mut on_error: impl FnMut(WatchError)) -> ! {
+        loop {
+            std::thread::sleep(self.interval);
+            if let Err(err) = self.poll() {
+                on_error(err);
+            }
+        }
+    }
+
+    /// Parses and checks `text`, remembering it so that a file which is
+    /// broken is only reported once until it changes again
+    fn read(&mut self, text: String) -> Result<SuperConf<'static>, WatchError> {
+        self.text = text;
+        let mut conf = SuperConf::parse(&self.text)
+            .map_err(LoadError::from)?
+            .into_owned();
+        for layer in &mut self.layers {
+            layer(&mut conf).map_err(LoadError::from)?;
+        }
+
+        if let Some(schema) = &self.schema {
+            let mut report = schema.validate(&conf);
+            if !report.is_valid() {
+                report.locate(&self.text);
+                return Err(WatchError::Invalid(report));
+            }
+            schema.apply_defaults(&mut conf);
+        }
+        Ok(conf)
+    }
+}
+
+impl fmt::Debug for Watcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Watcher")
+            .field("path", &self.path)
+            .field("conf", &self.conf)
+            .field("interval", &self.interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for WatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Load(err) => err.fmt(f),
+            Self::Invalid(report) => write!(f, "config doesn't match its schema:\n{}", report),
+        }
+    }
+}
+
+impl std::error::Error for WatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Load(err) => Some(err),
+            Self::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<LoadError> for WatchError {
+    fn from(err: LoadError) -> Self {
+        Self::Load(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::Kind;
+    use crate::{SuperError, SuperErrorKind, SuperValue};
+    use std::string::ToString;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn reloads() {
+        let path = std::env::temp_dir().join("superconf-watcher.conf");
+        std::fs::write(&path, "port 80\nname a").unwrap();
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut watcher = Watcher::new(&path).unwrap().subscribe({
+            let seen = Arc::clone(&seen);
+            move |reload| seen.lock().unwrap().push(reload.diff.render())
+        });
+
+        assert!(!watcher.poll().unwrap());
+        std::fs::write(&path, "port 8080\nname a").unwrap();
+        assert!(watcher.poll().unwrap());
+        std::fs::write(&path, "port    8080\nname a # same").unwrap();
+        assert!(!watcher.poll().unwrap());
+
+        std::fs::write(&path, "port [").unwrap();
+        assert!(matches!(
+            watcher.poll(),
+            Err(WatchError::Load(LoadError::Parse(SuperError {
+                kind: SuperErrorKind::Unbalanced,
+                ..
+            })))
+        ));
+        assert!(!watcher.poll().unwrap());
+        assert_eq!(watcher.conf().get("port"), Some(&SuperValue::Integer(8080)));
+
+        std::fs::write(&path, "port 1").unwrap();
+        assert!(watcher.poll().unwrap());
+        assert_eq!(
+            *seen.lock().unwrap(),
+            ["~ port 80 → 8080", "~ port 8080 → 1\n- name a"]
+        );
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            watcher.poll(),
+            Err(WatchError::Load(LoadError::Io(_)))
+        ));
+    }
+
+    #[test]
+    fn validates() {
+        let path = std::env::temp_dir().join("superconf-watcher-schema.conf");
+        let schema = || {
+            Schema::new()
+                .required("port", Kind::Integer)
+                .defaulted("workers", Kind::Integer, 4i64)
+        };
+        std::fs::write(&path, "port x").unwrap();
+        assert!(matches!(
+            Watcher::with_schema(&path, schema()),
+            Err(WatchError::Invalid(_))
+        ));
+
+        std::fs::write(&path, "port 80").unwrap();
+        let mut watcher = Watcher::with_schema(&path, schema()).unwrap();
+        assert_eq!(watcher.conf().get("workers"), Some(&SuperValue::Integer(4)));
+
+        std::fs::write(&path, "name a").unwrap();
+        let Err(WatchError::Invalid(report)) = watcher.poll() else {
+            panic!("config without a port was taken")
+        };
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(watcher.conf().get("port"), Some(&SuperValue::Integer(80)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn changed_paths() {
+        let path = std::env::temp_dir().join("superconf-watcher-paths.conf");
+        std::fs::write(&path, "logging {level info}\nport 80").unwrap();
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let record = |name| {
+            let seen = Arc::clone(&seen);
+            move |value: Option<&SuperValue>| {
+                seen.lock()
+                    .unwrap()
+                    .push((name, value.map(ToString::to_string)))
+            }
+        };
+        let mut watcher = Watcher::new(&path)
+            .unwrap()
+            .layer(|conf| conf.apply_env("APP", |name| (name == "APP_PORT").then(|| "9090".into())))
+            .unwrap()
+            .on_change("logging.level", record("level"))
+            .on_change("port", record("port"));
+        assert_eq!(watcher.conf().get("port"), Some(&SuperValue::Integer(9090)));
+
+        std::fs::write(&path, "logging {level info}\nport 81").unwrap();
+        assert!(!watcher.poll().unwrap());
+        std::fs::write(&path, "logging {level debug}\nport 81\nname a").unwrap();
+        assert!(watcher.poll().unwrap());
+        std::fs::write(&path, "port 81\nname b").unwrap();
+        assert!(watcher.poll().unwrap());
+        std::fs::write(&path, "name c").unwrap();
+        assert!(watcher.poll().unwrap());
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                ("level", Some("debug".into())),
+                ("level", None),
+                ("port", None)
+            ]
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
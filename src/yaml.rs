@@ -0,0 +1,161 @@
+//! Conversions to and from [serde_yaml::Value], behind the `yaml` feature
+//!
+//! Types map onto each other directly, so nothing is null, names are strings,
+//! lists are sequences and groups are mappings. YAML has more features than
+//! superconf, which are handled like so:
+//!
+//! - Anchors and aliases are already expanded into copies by serde_yaml, and
+//!   merge keys stay as normal `<<` items unless [Value::apply_merge] is used
+//!   before converting
+//! - Tagged values become a group with a single item named after the tag, the
+//!   same as enum variants with data in the [ser](crate::ser) module
+//! - Number and bool keys become names of their text, whilst any other kind of
+//!   key gives an error
+//! - Empty strings become nothing, as an empty name can't be written in lists
+//! - Groups with duplicate keys only keep the last
+
+use super::{ConvertError, SuperConf, SuperItem, SuperValue};
+use crate::path::Segment;
+use alloc::borrow::Cow;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use serde_yaml::{Mapping, Value};
+
+impl<'a> SuperValue<'a> {
+    /// Converts into a YAML value, see the [module](self) for how
+    pub fn to_yaml(&self) -> Value {
+        match self {
+            Self::Nothing => Value::Null,
+            Self::Name(name) => Value::String(name.to_string()),
+            Self::Bool(boolean) => Value::Bool(*boolean),
+            Self::Integer(integer) => Value::Number((*integer).into()),
+            Self::Float(float) => Value::Number((*float).into()),
+            Self::Bytes(data) => Value::String(data.to_string()),
+            Self::List(values) => Value::Sequence(values.iter().map(Self::to_yaml).collect()),
+            Self::Group(items) => Value::Mapping(items_to_yaml(items)),
+        }
+    }
+
+    /// Converts from a YAML value, borrowing all strings from it apart from
+    /// keys and tags which have to be turned into text
+    pub fn from_yaml(value: &'a Value) -> Result<Self, ConvertError> {
+        Ok(match value {
+            Value::Null => Self::Nothing,
+            Value::Bool(boolean) => Self::Bool(*boolean),
+            Value::Number(number) => match (number.as_i64(), number.as_f64()) {
+                (Some(integer), _) => Self::Integer(integer),
+                (None, Some(float)) => Self::Float(float),
+                (None, None) => Self::Nothing,
+            },
+            Value::String(string) if string.is_empty() => Self::Nothing,
+            Value::String(string) => Self::Name(Cow::Borrowed(string)),
+            Value::Sequence(values) => Self::list(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(ind, value)| {
+                        Self::from_yaml(value).map_err(|err| err.within(Segment::Index(ind)))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Mapping(mapping) => Self::group(items_from_yaml(mapping)?),
+            Value::Tagged(tagged) => {
+                let tag = tagged.tag.to_string();
+                let key = tag.trim_start_matches('!').to_string();
+
+                Self::group(vec![SuperItem {
+                    value: Self::from_yaml(&tagged.value)
+                        .map_err(|err| err.within(Segment::Key(Cow::Owned(key.clone()))))?,
+                    key: Cow::Owned(key),
+                    meta: None,
+                }])
+            }
+        })
+    }
+}
+
+impl<'a> SuperConf<'a> {
+    /// Converts into a YAML mapping of every item, see [SuperValue::to_yaml]
+    pub fn to_yaml(&self) -> Value {
+        Value::Mapping(items_to_yaml(&self.items))
+    }
+
+    /// Converts from a YAML mapping, such as a whole parsed document
+    pub fn from_yaml(value: &'a Value) -> Result<Self, ConvertError> {
+        match value {
+            Value::Mapping(mapping) => Ok(Self {
+                items: items_from_yaml(mapping)?,
+            }),
+            _ => Err(ConvertError::expected("a mapping")),
+        }
+    }
+}
+
+fn items_to_yaml(items: &[SuperItem]) -> Mapping {
+    items
+        .iter()
+        .map(|item| (Value::String(item.key.to_string()), item.value.to_yaml()))
+        .collect()
+}
+
+fn items_from_yaml(mapping: &Mapping) -> Result<Vec<SuperItem<'_>>, ConvertError> {
+    mapping
+        .iter()
+        .map(|(key, value)| {
+            let key = match key {
+                Value::String(string) => Cow::Borrowed(string.as_str()),
+                Value::Bool(boolean) => Cow::Owned(boolean.to_string()),
+                Value::Number(number) => Cow::Owned(number.to_string()),
+                _ => return Err(ConvertError::expected("a string, number or bool key")),
+            };
+
+            Ok(SuperItem {
+                value: SuperValue::from_yaml(value)
+                    .map_err(|err| err.within(Segment::Key(Cow::Owned(key.to_string()))))?,
+                key,
+                meta: None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parse;
+
+    #[test]
+    fn round_trip() {
+        let conf = SuperConf::parse(
+            "name server\nports [80, 443]\ntls {enabled true, ratio 0.5}\nproxy\n",
+        )
+        .unwrap();
+        let yaml = conf.to_yaml();
+
+        assert_eq!(
+            serde_yaml::to_string(&yaml).unwrap(),
+            "name: server\nports:\n- 80\n- 443\ntls:\n  enabled: true\n  ratio: 0.5\nproxy: null\n"
+        );
+        assert_eq!(SuperConf::from_yaml(&yaml).unwrap(), conf);
+    }
+
+    #[test]
+    fn yaml_features() {
+        let yaml: Value = serde_yaml::from_str(
+            "base: &base {port: 80}\ncopy: *base\npoint: !Point {x: 1}\n2: two\n",
+        )
+        .unwrap();
+        let conf = SuperConf::from_yaml(&yaml).unwrap();
+
+        assert_eq!(conf.get("copy.port"), Some(&SuperValue::Integer(80)));
+        assert_eq!(conf.get("point.Point.x"), Some(&SuperValue::Integer(1)));
+        assert_eq!(conf.get("2"), Some(&SuperValue::Name("two".into())));
+
+        let yaml: Value = serde_yaml::from_str("list: [{[1]: a}]").unwrap();
+        assert_eq!(
+            SuperConf::from_yaml(&yaml).unwrap_err().to_string(),
+            "list[0]: expected a string, number or bool key"
+        );
+    }
+}
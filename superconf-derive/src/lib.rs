@@ -0,0 +1,301 @@
+//! Derive macros for superconf's `SuperConfig` and `SuperSchema` traits, use
+//! them through the `derive` feature of superconf instead of depending on
+//! this directly
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Expr, Fields, Generics, Lifetime, LitStr,
+    Type, TypeParamBound,
+};
+
+/// Implements `SuperConfig`, `FromSuperValue` and `ToSuperValue` for a struct
+/// with named fields, mapping each field onto an item of the same key
+///
+/// Fields can be renamed with `#[superconf(rename = "key")]` and can fall back
+/// to their [Default] when missing with `#[superconf(default)]`.
+#[proc_macro_derive(SuperConfig, attributes(superconf))]
+pub fn derive_super_config(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Implements `SuperSchema` for a struct with named fields, giving a group
+/// with an entry for each field using its type's rule
+///
+/// Fields are read the same as for `SuperConfig`, and can be given a default
+/// with `#[schema(default = ...)]` where anything a `SuperValue` can be made
+/// from is allowed. Items which aren't fields are denied for a struct with
+/// `#[schema(deny_unknown)]`, along with unknown items inside of its fields.
+#[proc_macro_derive(SuperSchema, attributes(schema, superconf))]
+pub fn derive_super_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand_schema(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+struct Field {
+    ident: syn::Ident,
+    ty: Type,
+    key: LitStr,
+    default: bool,
+    /// Default given with `#[schema(default = ...)]`
+    schema_default: Option<Expr>,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = fields(&input, "SuperConfig")?;
+
+    let name = &input.ident;
+    let lifetime = input
+        .generics
+        .lifetimes()
+        .next()
+        .map(|param| param.lifetime.clone());
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    // the lifetime which converted values borrow from, which is the struct's
+    // own first lifetime so fields like `&'a str` can borrow from configs
+    let (mut from_generics, lifetime) = match lifetime {
+        Some(lifetime) => (input.generics.clone(), lifetime),
+        None => {
+            let lifetime: Lifetime = parse_quote!('__superconf);
+            let mut generics = input.generics.clone();
+            generics.params.insert(0, parse_quote!(#lifetime));
+            (generics, lifetime)
+        }
+    };
+    bound(
+        &mut from_generics,
+        parse_quote!(::superconf::FromSuperValue<#lifetime>),
+    );
+    let mut config_generics = from_generics.clone();
+    bound(
+        &mut config_generics,
+        parse_quote!(::superconf::ToSuperValue),
+    );
+    let mut to_generics = input.generics.clone();
+    bound(&mut to_generics, parse_quote!(::superconf::ToSuperValue));
+
+    let (from_impl, _, from_where) = from_generics.split_for_impl();
+    let (config_impl, _, config_where) = config_generics.split_for_impl();
+    let (to_impl, _, to_where) = to_generics.split_for_impl();
+
+    let from_fields = fields
+        .iter()
+        .map(|field| {
+            let Field { ident, key, .. } = field;
+
+            match field.default {
+                true => quote! {
+                    #ident: ::superconf::convert::from_item_or(
+                        items,
+                        #key,
+                        ::core::default::Default::default,
+                    )?
+                },
+                false => quote! { #ident: ::superconf::convert::from_item(items, #key)? },
+            }
+        })
+        .collect::<Vec<_>>();
+    let to_fields = fields.iter().map(|Field { ident, key, .. }| {
+        quote! {
+            items.push(::superconf::SuperItem {
+                key: ::superconf::convert::Cow::Borrowed(#key),
+                value: ::superconf::ToSuperValue::to_value(&self.#ident),
+                meta: ::core::option::Option::None,
+            });
+        }
+    });
+    let len = fields.len();
+    let to_items = quote! {{
+        let mut items = ::superconf::convert::Vec::with_capacity(#len);
+        #(#to_fields)*
+        items
+    }};
+
+    Ok(quote! {
+        impl #config_impl ::superconf::SuperConfig<#lifetime> for #name #ty_generics #config_where {
+            fn from_items(
+                items: &[::superconf::SuperItem<#lifetime>],
+            ) -> ::core::result::Result<Self, ::superconf::ConvertError> {
+                ::core::result::Result::Ok(Self { #(#from_fields,)* })
+            }
+
+            fn to_items(&self) -> ::superconf::convert::Vec<::superconf::SuperItem<'_>> {
+                #to_items
+            }
+        }
+
+        impl #from_impl ::superconf::FromSuperValue<#lifetime> for #name #ty_generics #from_where {
+            fn from_value(
+                value: &::superconf::SuperValue<#lifetime>,
+            ) -> ::core::result::Result<Self, ::superconf::ConvertError> {
+                match value {
+                    ::superconf::SuperValue::Group(items) => {
+                        ::core::result::Result::Ok(Self { #(#from_fields,)* })
+                    }
+                    _ => ::core::result::Result::Err(::superconf::ConvertError::expected("a group")),
+                }
+            }
+        }
+
+        impl #to_impl ::superconf::ToSuperValue for #name #ty_generics #to_where {
+            fn to_value(&self) -> ::superconf::SuperValue<'_> {
+                ::superconf::SuperValue::group(#to_items)
+            }
+        }
+    })
+}
+
+fn expand_schema(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = fields(&input, "SuperSchema")?;
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    bound(
+        &mut generics,
+        parse_quote!(::superconf::schema::SuperSchema),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let mut deny_unknown = false;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("schema"))
+    {
+        attr.parse_nested_meta(|meta| match meta.path.is_ident("deny_unknown") {
+            true => {
+                deny_unknown = true;
+                Ok(())
+            }
+            false => Err(meta.error("expected `deny_unknown`")),
+        })?;
+    }
+
+    let entries = fields.iter().map(|field| {
+        let Field { ty, key, .. } = field;
+        let default = match (&field.schema_default, field.default) {
+            (Some(default), _) => quote! {
+                ::core::option::Option::Some(::superconf::SuperValue::from(#default).into_owned())
+            },
+            (None, true) => quote! {
+                ::core::option::Option::Some(
+                    ::superconf::ToSuperValue::to_value(
+                        &<#ty as ::core::default::Default>::default(),
+                    )
+                    .into_owned(),
+                )
+            },
+            (None, false) => quote! { ::core::option::Option::None },
+        };
+        // fields with a default never have to be there
+        let required = match field.default || field.schema_default.is_some() {
+            true => quote! { false },
+            false => quote! { <#ty as ::superconf::schema::SuperSchema>::required() },
+        };
+
+        quote! {
+            ::superconf::schema::Entry {
+                key: ::superconf::convert::Cow::Borrowed(#key),
+                required: #required,
+                default: #default,
+                rule: <#ty as ::superconf::schema::SuperSchema>::rule(),
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::superconf::schema::SuperSchema for #name #ty_generics #where_clause {
+            fn rule() -> ::superconf::schema::Rule {
+                ::superconf::schema::Rule::group(::superconf::schema::Schema {
+                    entries: ::superconf::convert::vec![#(#entries),*],
+                    deny_unknown: #deny_unknown,
+                    ..::core::default::Default::default()
+                })
+            }
+        }
+    })
+}
+
+fn fields(input: &DeriveInput, derive: &str) -> syn::Result<Vec<Field>> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().map(field).collect(),
+            _ => Err(unsupported(input, derive)),
+        },
+        _ => Err(unsupported(input, derive)),
+    }
+}
+
+fn field(field: &syn::Field) -> syn::Result<Field> {
+    let ident = field.ident.clone().expect("named fields have idents");
+    let mut key = LitStr::new(&ident.to_string(), ident.span());
+    let mut default = false;
+    let mut schema_default = None;
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("schema"))
+    {
+        attr.parse_nested_meta(|meta| match meta.path.is_ident("default") {
+            true => {
+                schema_default = Some(meta.value()?.parse()?);
+                Ok(())
+            }
+            false => Err(meta.error("expected `default`")),
+        })?;
+    }
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("superconf"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                key = meta.value()?.parse()?;
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                default = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `rename` or `default`"))
+            }
+        })?;
+    }
+
+    Ok(Field {
+        ident,
+        ty: field.ty.clone(),
+        key,
+        default,
+        schema_default,
+    })
+}
+
+/// Adds `bound` to every type parameter of `generics`
+fn bound(generics: &mut Generics, bound: TypeParamBound) {
+    for param in generics.type_params_mut() {
+        param.bounds.push(bound.clone());
+    }
+}
+
+fn unsupported(input: &DeriveInput, derive: &str) -> syn::Error {
+    syn::Error::new_spanned(
+        &input.ident,
+        format!(
+            "{} can only be derived for structs with named fields",
+            derive
+        ),
+    )
+}
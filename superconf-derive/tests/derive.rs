@@ -0,0 +1,128 @@
+use superconf::schema::{Schema, ViolationKind};
+use superconf::{Emit, FromSuperValue, Parse, SuperConf, SuperConfig, SuperSchema, SuperValue};
+
+#[derive(Debug, PartialEq, SuperConfig, SuperSchema)]
+struct Config<'a> {
+    name: &'a str,
+    #[superconf(rename = "listen port")]
+    port: u16,
+    #[superconf(default)]
+    workers: u8,
+    tls: Option<Tls>,
+    hosts: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, SuperConfig, SuperSchema)]
+#[schema(deny_unknown)]
+struct Tls {
+    enabled: bool,
+    #[schema(default = 0.5)]
+    ratio: f64,
+}
+
+#[derive(Debug, PartialEq, SuperConfig, SuperSchema)]
+struct Wrapper<T> {
+    inner: T,
+}
+
+#[test]
+fn from_conf() {
+    let conf = SuperConf::parse(
+        "name example\nlisten\\ port 8080\ntls {enabled true, ratio 0.5}\nhosts [a, b]",
+    )
+    .unwrap();
+
+    assert_eq!(
+        Config::from_conf(&conf).unwrap(),
+        Config {
+            name: "example",
+            port: 8080,
+            workers: 0,
+            tls: Some(Tls {
+                enabled: true,
+                ratio: 0.5
+            }),
+            hosts: vec!["a".into(), "b".into()]
+        }
+    );
+}
+
+#[test]
+fn to_conf() {
+    let config = Config {
+        name: "example",
+        port: 80,
+        workers: 4,
+        tls: None,
+        hosts: vec!["a b".into()],
+    };
+    let text = config.to_conf().emit();
+
+    assert_eq!(
+        text,
+        "name example\nlisten\\ port 80\nworkers 4\ntls\nhosts [a b]\n"
+    );
+    assert_eq!(
+        Config::from_conf(&SuperConf::parse(&text).unwrap()).unwrap(),
+        config
+    );
+}
+
+#[test]
+fn errors() {
+    let conf = SuperConf::parse("inner {enabled yes}").unwrap();
+
+    assert_eq!(
+        Wrapper::<Tls>::from_conf(&conf).unwrap_err().to_string(),
+        "inner.enabled: expected a bool"
+    );
+    assert_eq!(
+        Wrapper::<u8>::from_value(&SuperValue::Integer(1))
+            .unwrap_err()
+            .to_string(),
+        "expected a group"
+    );
+    assert_eq!(
+        Config::from_conf(&SuperConf::parse("name x").unwrap())
+            .unwrap_err()
+            .to_string(),
+        "listen port: missing item"
+    );
+}
+
+#[test]
+fn schema() {
+    let schema = Schema::of::<Config>();
+    let text = "name example\nlisten\\ port 8080\ntls {enabled true}\nhosts [a, b]";
+    let mut conf = SuperConf::parse(text).unwrap();
+
+    assert!(schema.validate(&conf).is_valid());
+    schema.apply_defaults(&mut conf);
+    assert_eq!(conf.get("workers"), Some(&SuperValue::Integer(0)));
+    assert_eq!(conf.get("tls.ratio"), Some(&SuperValue::Float(0.5)));
+    assert_eq!(Config::from_conf(&conf).unwrap().tls.unwrap().ratio, 0.5);
+
+    let report = schema
+        .validate_text("listen\\ port -1\ntls\nhosts [a, 1]")
+        .unwrap();
+    assert_eq!(
+        report.to_string(),
+        "name: missing item\n\
+         listen port: expected a number from 0 to 65535, found -1\n\
+         hosts[1]: expected a name"
+    );
+
+    let schema = Schema::of::<Wrapper<Tls>>();
+    let report = schema.validate(&SuperConf::parse("inner {enabled yes}").unwrap());
+    assert_eq!(report.violations[0].path.to_string(), "inner.enabled");
+    assert!(matches!(
+        report.violations[0].kind,
+        ViolationKind::Expected(_)
+    ));
+
+    let report = schema.validate(&SuperConf::parse("inner {enabled true, raito 1}").unwrap());
+    assert_eq!(
+        report.to_string(),
+        "inner.raito: unknown item, did you mean ratio?"
+    );
+}
@@ -0,0 +1,275 @@
+//! Parses superconf files at compile time with [include_superconf!], so a
+//! malformed config, or one which doesn't match its schema, fails the build
+//! instead of the program at runtime
+
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::quote;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+use superconf::schema::Schema;
+use superconf::{Meta, Parse, SuperConf, SuperItem, SuperValue};
+use syn::parse::{Parse as SynParse, ParseStream};
+use syn::{parse_macro_input, Ident, LitStr, Token};
+
+/// Reads and parses a config file at compile time, expanding to a
+/// `SuperConf<'static>` which borrows all of its text from the binary
+///
+/// The path is relative to the crate's `Cargo.toml` and the build is rerun
+/// whenever the file changes. As [SuperConf] owns the [Vec]s of its items this
+/// can't be a `static` directly, but building it doesn't need any parsing.
+///
+/// A schema can be given as a second file, written as a config as read by
+/// [Schema::from_conf], and the build fails with every violation if the
+/// config doesn't match it. It's also rerun whenever the schema changes.
+///
+/// ```ignore
+/// let defaults = superconf_include::include_superconf!("defaults.conf");
+/// let checked = superconf_include::include_superconf!("defaults.conf", schema = "defaults.schema");
+/// ```
+#[proc_macro]
+pub fn include_superconf(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as Input);
+
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Paths given to [include_superconf!], as `"path"` or
+/// `"path", schema = "path"`
+struct Input {
+    path: LitStr,
+    schema: Option<LitStr>,
+}
+
+impl SynParse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        if input.is_empty() {
+            return Ok(Self { path, schema: None });
+        }
+
+        input.parse::<Token![,]>()?;
+        let name: Ident = input.parse()?;
+        if name != "schema" {
+            return Err(syn::Error::new(name.span(), "expected `schema`"));
+        }
+        input.parse::<Token![=]>()?;
+        let schema = Some(input.parse()?);
+        input.parse::<Option<Token![,]>>()?;
+
+        Ok(Self { path, schema })
+    }
+}
+
+fn expand(input: &Input) -> syn::Result<TokenStream2> {
+    let (full, text) = read(&input.path)?;
+    let conf = parse(&input.path, &full, &text)?;
+
+    let mut rerun = vec![full.to_string_lossy().into_owned()];
+    if let Some(path) = &input.schema {
+        let (schema_full, schema_text) = read(path)?;
+        let schema =
+            Schema::from_conf(&parse(path, &schema_full, &schema_text)?).map_err(|err| {
+                syn::Error::new(
+                    path.span(),
+                    format!(
+                        "couldn't read a schema from `{}`: {}",
+                        schema_full.display(),
+                        err
+                    ),
+                )
+            })?;
+
+        let mut report = schema.validate(&conf);
+        if !report.is_valid() {
+            report.locate(&text);
+            let mut message = format!(
+                "`{}` doesn't match `{}`:",
+                full.display(),
+                schema_full.display()
+            );
+            for violation in &report.violations {
+                let line = violation
+                    .span
+                    .map(|span| text[..span.start].matches('\n').count() + 1)
+                    .unwrap_or(1);
+                message.push_str(&format!("\n{}:{}: {}", full.display(), line, violation));
+            }
+            return Err(syn::Error::new(input.path.span(), message));
+        }
+        rerun.push(schema_full.to_string_lossy().into_owned());
+    }
+
+    let items = items(&conf.items);
+    Ok(quote! {{
+        #(const _: &[u8] = include_bytes!(#rerun);)*
+        ::superconf::SuperConf { items: #items }
+    }})
+}
+
+/// Reads the file at `path` relative to the crate, giving its full path too
+fn read(path: &LitStr) -> syn::Result<(PathBuf, String)> {
+    let full = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap_or_default()).join(path.value());
+    let text = fs::read_to_string(&full).map_err(|err| {
+        syn::Error::new(
+            path.span(),
+            format!("couldn't read `{}`: {}", full.display(), err),
+        )
+    })?;
+    Ok((full, text))
+}
+
+fn parse<'a>(path: &LitStr, full: &Path, text: &'a str) -> syn::Result<SuperConf<'a>> {
+    SuperConf::parse(text).map_err(|err| {
+        let line = err.line(text).unwrap_or(1);
+        syn::Error::new(
+            path.span(),
+            format!("couldn't parse `{}:{}`: {}", full.display(), line, err),
+        )
+    })
+}
+
+fn items(items: &[SuperItem]) -> TokenStream2 {
+    let items = items.iter().map(|SuperItem { key, value, meta }| {
+        let key: &str = key;
+        let value = value_tokens(value);
+        let meta = match meta {
+            Some(meta) => meta_tokens(meta),
+            None => quote! { ::core::option::Option::None },
+        };
+
+        quote! {
+            ::superconf::SuperItem {
+                key: ::superconf::convert::Cow::Borrowed(#key),
+                value: #value,
+                meta: #meta,
+            }
+        }
+    });
+
+    quote! { ::superconf::convert::vec![#(#items),*] }
+}
+
+fn meta_tokens(meta: &Meta) -> TokenStream2 {
+    let doc = option_tokens(meta.doc.as_deref());
+    let attrs = meta.attrs.iter().map(|attr| {
+        let name = &attr.name;
+        let arg = option_tokens(attr.arg.as_deref());
+        quote! {
+            ::superconf::Attr {
+                name: ::core::convert::From::from(#name),
+                arg: #arg,
+            }
+        }
+    });
+
+    quote! {
+        ::core::option::Option::Some(::superconf::convert::Box::new(::superconf::Meta {
+            doc: #doc,
+            attrs: ::superconf::convert::vec![#(#attrs),*],
+        }))
+    }
+}
+
+fn option_tokens(text: Option<&str>) -> TokenStream2 {
+    match text {
+        Some(text) => quote! { ::core::option::Option::Some(::core::convert::From::from(#text)) },
+        None => quote! { ::core::option::Option::None },
+    }
+}
+
+fn value_tokens(value: &SuperValue) -> TokenStream2 {
+    match value {
+        SuperValue::Nothing => quote! { ::superconf::SuperValue::Nothing },
+        SuperValue::Name(name) => {
+            let name: &str = name;
+            quote! { ::superconf::SuperValue::Name(::superconf::convert::Cow::Borrowed(#name)) }
+        }
+        SuperValue::Bytes(data) => {
+            let data: &str = data;
+            quote! {
+                ::superconf::SuperValue::Bytes(::superconf::convert::Box::new(
+                    ::superconf::convert::Cow::Borrowed(#data)
+                ))
+            }
+        }
+        SuperValue::Bool(boolean) => quote! { ::superconf::SuperValue::Bool(#boolean) },
+        SuperValue::Integer(integer) => {
+            let integer = Literal::i64_suffixed(*integer);
+            quote! { ::superconf::SuperValue::Integer(#integer) }
+        }
+        SuperValue::Float(float) => {
+            let float = match float {
+                _ if float.is_nan() => quote! { ::core::f64::NAN },
+                _ if *float == f64::INFINITY => quote! { ::core::f64::INFINITY },
+                _ if *float == f64::NEG_INFINITY => quote! { ::core::f64::NEG_INFINITY },
+                _ => {
+                    let float = Literal::f64_suffixed(*float);
+                    quote! { #float }
+                }
+            };
+            quote! { ::superconf::SuperValue::Float(#float) }
+        }
+        SuperValue::List(values) => {
+            let values = values.iter().map(value_tokens);
+            quote! {
+                ::superconf::SuperValue::list(::superconf::convert::vec![#(#values),*])
+            }
+        }
+        SuperValue::Group(group) => {
+            let group = items(group);
+            quote! { ::superconf::SuperValue::group(#group) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proc_macro2::Span;
+
+    fn expand(path: &str, schema: &str) -> Result<(), String> {
+        let input = Input {
+            path: LitStr::new(path, Span::call_site()),
+            schema: Some(LitStr::new(schema, Span::call_site())),
+        };
+        super::expand(&input).map(|_| ()).map_err(|err| {
+            let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+            err.to_string().replace(&dir, "")
+        })
+    }
+
+    #[test]
+    fn schemas() {
+        assert_eq!(expand("tests/example.conf", "tests/example.schema"), Ok(()));
+        assert_eq!(
+            expand("tests/invalid.conf", "tests/example.schema"),
+            Err(
+                "`/tests/invalid.conf` doesn't match `/tests/example.schema`:\n\
+                 /tests/invalid.conf:2: port: expected a number from 1 to 65535, found 0\n\
+                 /tests/invalid.conf:1: ratio: missing item\n\
+                 /tests/invalid.conf:4: tls.hosts[1]: expected a name\n\
+                 /tests/invalid.conf:6: color: unknown item"
+                    .into()
+            )
+        );
+        assert_eq!(
+            expand("tests/example.conf", "tests/example.conf"),
+            Err(
+                "couldn't read a schema from `/tests/example.conf`: name: expected a known field"
+                    .into()
+            )
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            expand("tests/unbalanced.conf", "tests/example.schema"),
+            Err("couldn't parse `/tests/unbalanced.conf:3`: brackets are unbalanced".into())
+        );
+    }
+}
@@ -0,0 +1,24 @@
+use superconf::{Parse, SuperConf};
+use superconf_include::include_superconf;
+
+#[test]
+fn matches_parsed() {
+    let conf = include_superconf!("tests/example.conf");
+
+    assert_eq!(
+        conf,
+        SuperConf::parse(include_str!("example.conf")).unwrap()
+    );
+    assert_eq!(conf.items[1].doc(), Some("port to listen on"));
+    assert_eq!(conf.items[1].attrs()[0].arg.as_deref(), Some("port"));
+}
+
+#[test]
+fn matches_schema() {
+    let conf = include_superconf!("tests/example.conf", schema = "tests/example.schema");
+
+    assert_eq!(
+        conf,
+        SuperConf::parse(include_str!("example.conf")).unwrap()
+    );
+}